@@ -0,0 +1,460 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+#[cfg(feature = "schemars")]
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, Deserializer, Visitor},
+    Deserialize, Serialize, Serializer,
+};
+use std::{error::Error, fmt, str::FromStr};
+
+/// The modifier keys that must be held down as part of a [`KeyCombination`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct KeyModifiers {
+    pub alt: bool,
+    pub control: bool,
+    /// The Command key on macOS, or the Windows or Super key elsewhere.
+    pub meta: bool,
+    pub shift: bool,
+}
+
+impl KeyModifiers {
+    fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "Alt" => Some(&mut self.alt),
+            "Control" => Some(&mut self.control),
+            "Meta" => Some(&mut self.meta),
+            "Shift" => Some(&mut self.shift),
+            _ => None,
+        }
+    }
+}
+
+/// A single key, pressed while holding down zero or more modifier keys.
+///
+/// The string form is the one used by a single alternative of the ARIA
+/// `aria-keyshortcuts` attribute, e.g. `"Control+Shift+A"`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct KeyCombination {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub modifiers: KeyModifiers,
+    /// The name of the non-modifier key, as in the `key` property of
+    /// a DOM `KeyboardEvent`, except that the space and plus keys are named
+    /// `"Space"` and `"Plus"` respectively. Printable keys should use
+    /// the character that is typed when the key is pressed with no
+    /// modifiers, e.g. `"a"` rather than `"A"` unless `shift` is set.
+    pub key: Box<str>,
+}
+
+impl KeyCombination {
+    pub fn new(modifiers: KeyModifiers, key: impl Into<Box<str>>) -> Self {
+        Self {
+            modifiers,
+            key: key.into(),
+        }
+    }
+}
+
+impl fmt::Display for KeyCombination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let KeyModifiers {
+            alt,
+            control,
+            meta,
+            shift,
+        } = self.modifiers;
+        for (is_set, name) in [
+            (control, "Control"),
+            (alt, "Alt"),
+            (shift, "Shift"),
+            (meta, "Meta"),
+        ] {
+            if is_set {
+                write!(f, "{}+", name)?;
+            }
+        }
+        f.write_str(&self.key)
+    }
+}
+
+impl FromStr for KeyCombination {
+    type Err = ParseKeyShortcutsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.contains(char::is_whitespace) {
+            return Err(ParseKeyShortcutsError::MissingKey(s.into()));
+        }
+        let mut parts = s.split('+');
+        let key = parts.next_back().unwrap();
+        let mut modifiers = KeyModifiers::default();
+        for part in parts {
+            match modifiers.flag_mut(part) {
+                Some(flag) if !*flag => *flag = true,
+                Some(_) => return Err(ParseKeyShortcutsError::DuplicateModifier(s.into())),
+                None if part.is_empty() => {
+                    return Err(ParseKeyShortcutsError::MissingKey(s.into()))
+                }
+                None => return Err(ParseKeyShortcutsError::MultipleKeys(s.into())),
+            }
+        }
+        if key.is_empty() || KeyModifiers::default().flag_mut(key).is_some() {
+            return Err(ParseKeyShortcutsError::MissingKey(s.into()));
+        }
+        Ok(Self::new(modifiers, key))
+    }
+}
+
+/// The keyboard shortcuts that activate or focus a node.
+///
+/// The string form is that of the ARIA `aria-keyshortcuts` attribute:
+/// the [`accelerators`] separated by spaces, e.g. `"Control+O Meta+O"`.
+/// The [`mnemonic`] has no equivalent in that form, so it is omitted
+/// when formatting and is always `None` after parsing.
+///
+/// This is also the serialized form, so the mnemonic isn't serialized
+/// either; [`Node::access_key`] can carry it instead.
+///
+/// [`accelerators`]: KeyShortcuts::accelerators
+/// [`mnemonic`]: KeyShortcuts::mnemonic
+/// [`Node::access_key`]: crate::Node::access_key
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeyShortcuts {
+    /// The key that activates the node while its container (e.g. menu
+    /// or dialog) is active, usually shown as an underlined letter
+    /// in the node's label. If the key only works with a modifier
+    /// (typically Alt), that modifier should be included.
+    pub mnemonic: Option<KeyCombination>,
+    /// Alternative key combinations that activate the node from anywhere
+    /// in the window, in order of preference.
+    pub accelerators: Vec<KeyCombination>,
+}
+
+impl KeyShortcuts {
+    pub fn is_empty(&self) -> bool {
+        self.mnemonic.is_none() && self.accelerators.is_empty()
+    }
+}
+
+impl fmt::Display for KeyShortcuts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, accelerator) in self.accelerators.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", accelerator)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for KeyShortcuts {
+    type Err = ParseKeyShortcutsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            mnemonic: None,
+            accelerators: s
+                .split_whitespace()
+                .map(KeyCombination::from_str)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Parses an `aria-keyshortcuts` string, ignoring any key combinations
+/// that aren't valid, as browsers do.
+impl From<&str> for Box<KeyShortcuts> {
+    fn from(s: &str) -> Self {
+        Box::new(KeyShortcuts {
+            mnemonic: None,
+            accelerators: s
+                .split_whitespace()
+                .filter_map(|combination| combination.parse().ok())
+                .collect(),
+        })
+    }
+}
+
+impl From<String> for Box<KeyShortcuts> {
+    fn from(s: String) -> Self {
+        s.as_str().into()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeyShortcuts {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct KeyShortcutsVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for KeyShortcutsVisitor {
+    type Value = KeyShortcuts;
+
+    #[inline]
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("aria-keyshortcuts string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<KeyShortcuts, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyShortcuts {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(KeyShortcutsVisitor)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for KeyShortcuts {
+    #[inline]
+    fn schema_name() -> String {
+        "KeyShortcuts".into()
+    }
+
+    #[inline]
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        String::json_schema(gen)
+    }
+}
+
+/// A problem found when parsing a [`KeyCombination`] or [`KeyShortcuts`].
+/// Each variant holds the offending key combination.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseKeyShortcutsError {
+    /// The combination has no non-modifier key, e.g. `"Control+"`
+    /// or `"Shift"`.
+    MissingKey(Box<str>),
+    /// The combination has more than one non-modifier key, or a modifier
+    /// that isn't `Alt`, `Control`, `Meta` or `Shift`, e.g. `"A+B"`.
+    MultipleKeys(Box<str>),
+    /// The combination includes the same modifier more than once,
+    /// e.g. `"Shift+Shift+A"`.
+    DuplicateModifier(Box<str>),
+}
+
+impl fmt::Display for ParseKeyShortcutsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey(s) => write!(f, "key combination {:?} has no key", s),
+            Self::MultipleKeys(s) => {
+                write!(f, "key combination {:?} has more than one key", s)
+            }
+            Self::DuplicateModifier(s) => {
+                write!(f, "key combination {:?} repeats a modifier", s)
+            }
+        }
+    }
+}
+
+impl Error for ParseKeyShortcutsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combination(modifiers: KeyModifiers, key: &str) -> KeyCombination {
+        KeyCombination::new(modifiers, key)
+    }
+
+    const CONTROL: KeyModifiers = KeyModifiers {
+        alt: false,
+        control: true,
+        meta: false,
+        shift: false,
+    };
+
+    #[test]
+    fn parse_single_combination() {
+        assert_eq!(
+            Ok(combination(KeyModifiers::default(), "a")),
+            "a".parse::<KeyCombination>()
+        );
+        assert_eq!(
+            Ok(combination(
+                KeyModifiers {
+                    shift: true,
+                    ..CONTROL
+                },
+                "A"
+            )),
+            "Control+Shift+A".parse::<KeyCombination>()
+        );
+        assert_eq!(
+            Ok(combination(
+                KeyModifiers {
+                    alt: true,
+                    meta: true,
+                    ..Default::default()
+                },
+                "F4"
+            )),
+            "Alt+Meta+F4".parse::<KeyCombination>()
+        );
+    }
+
+    #[test]
+    fn plus_and_space_key_names() {
+        let plus = "Control+Plus".parse::<KeyCombination>().unwrap();
+        assert_eq!(combination(CONTROL, "Plus"), plus);
+        assert_eq!("Control+Plus", plus.to_string());
+        let space = "Control+Space".parse::<KeyCombination>().unwrap();
+        assert_eq!(combination(CONTROL, "Space"), space);
+        assert_eq!("Control+Space", space.to_string());
+    }
+
+    #[test]
+    fn modifiers_are_formatted_in_canonical_order() {
+        let parsed = "Meta+Shift+Alt+Control+x"
+            .parse::<KeyCombination>()
+            .unwrap();
+        assert_eq!(
+            KeyModifiers {
+                alt: true,
+                control: true,
+                meta: true,
+                shift: true,
+            },
+            parsed.modifiers
+        );
+        assert_eq!("Control+Alt+Shift+Meta+x", parsed.to_string());
+        assert_eq!(
+            "Control+Shift+x".parse::<KeyCombination>(),
+            "Shift+Control+x".parse::<KeyCombination>()
+        );
+    }
+
+    #[test]
+    fn multiple_alternatives() {
+        let shortcuts = "  Control+O\tMeta+O  Alt+F "
+            .parse::<KeyShortcuts>()
+            .unwrap();
+        assert_eq!(None, shortcuts.mnemonic);
+        assert_eq!(
+            vec![
+                combination(CONTROL, "O"),
+                combination(
+                    KeyModifiers {
+                        meta: true,
+                        ..Default::default()
+                    },
+                    "O"
+                ),
+                combination(
+                    KeyModifiers {
+                        alt: true,
+                        ..Default::default()
+                    },
+                    "F"
+                ),
+            ],
+            shortcuts.accelerators
+        );
+        assert_eq!("Control+O Meta+O Alt+F", shortcuts.to_string());
+        assert_eq!(Ok(KeyShortcuts::default()), "".parse::<KeyShortcuts>());
+    }
+
+    #[test]
+    fn mnemonic_is_not_formatted() {
+        let shortcuts = KeyShortcuts {
+            mnemonic: Some(combination(
+                KeyModifiers {
+                    alt: true,
+                    ..Default::default()
+                },
+                "f",
+            )),
+            accelerators: vec![combination(CONTROL, "f")],
+        };
+        assert!(!shortcuts.is_empty());
+        assert_eq!("Control+f", shortcuts.to_string());
+        assert!(KeyShortcuts::default().is_empty());
+    }
+
+    #[test]
+    fn invalid_combinations() {
+        for (s, error) in [
+            ("", ParseKeyShortcutsError::MissingKey("".into())),
+            ("Shift", ParseKeyShortcutsError::MissingKey("Shift".into())),
+            (
+                "Control+",
+                ParseKeyShortcutsError::MissingKey("Control+".into()),
+            ),
+            (
+                "Control+Alt",
+                ParseKeyShortcutsError::MissingKey("Control+Alt".into()),
+            ),
+            ("A+B", ParseKeyShortcutsError::MultipleKeys("A+B".into())),
+            (
+                "Hyper+A",
+                ParseKeyShortcutsError::MultipleKeys("Hyper+A".into()),
+            ),
+            (
+                "Shift+Shift+A",
+                ParseKeyShortcutsError::DuplicateModifier("Shift+Shift+A".into()),
+            ),
+        ] {
+            assert_eq!(Err(error), s.parse::<KeyCombination>());
+        }
+        assert_eq!(
+            Err(ParseKeyShortcutsError::MissingKey("Meta+".into())),
+            "Control+O Meta+".parse::<KeyShortcuts>()
+        );
+    }
+
+    #[test]
+    fn conversion_from_string_skips_invalid_combinations() {
+        assert_eq!(
+            Box::new(KeyShortcuts {
+                mnemonic: None,
+                accelerators: vec![combination(CONTROL, "O"), combination(CONTROL, "P")],
+            }),
+            Box::<KeyShortcuts>::from("Control+O Hyper+A Control+ Control+P")
+        );
+        assert!(Box::<KeyShortcuts>::from(String::new()).is_empty());
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            "key combination \"A+B\" has more than one key",
+            ParseKeyShortcutsError::MultipleKeys("A+B".into()).to_string()
+        );
+        assert_eq!(
+            "key combination \"Shift+Shift+A\" repeats a modifier",
+            ParseKeyShortcutsError::DuplicateModifier("Shift+Shift+A".into()).to_string()
+        );
+    }
+}
@@ -32,6 +32,12 @@ use std::{fmt, mem::size_of_val};
 mod geometry;
 pub use geometry::{Affine, Point, Rect, Size, Vec2};
 
+mod key_shortcuts;
+pub use key_shortcuts::{KeyCombination, KeyModifiers, KeyShortcuts, ParseKeyShortcutsError};
+
+mod validation;
+pub use validation::ValidationError;
+
 /// The type of an accessibility node.
 ///
 /// The majority of these roles come from the ARIA specification. Reference
@@ -340,6 +346,13 @@ pub enum Action {
     SetValue,
 
     ShowContextMenu,
+
+    /// Copy the selected text to the clipboard.
+    Copy,
+    /// Copy the selected text to the clipboard, then delete it.
+    Cut,
+    /// Replace the selected text with the contents of the clipboard.
+    Paste,
 }
 
 impl Action {
@@ -485,6 +498,9 @@ pub enum DescriptionFrom {
     /// HTML-AAM 5.9.2
     TableCaption,
     Title,
+    /// E.g. from an HTML placeholder attribute on a text field, when it
+    /// isn't already used as the name.
+    Placeholder,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -794,6 +810,7 @@ enum PropertyValue {
     Rect(Rect),
     TextSelection(Box<TextSelection>),
     CustomActionVec(Vec<CustomAction>),
+    KeyShortcuts(Box<KeyShortcuts>),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -839,7 +856,6 @@ enum PropertyId {
     HtmlTag,
     InnerHtml,
     InputType,
-    KeyShortcuts,
     Language,
     LiveRelevant,
     Placeholder,
@@ -920,6 +936,7 @@ enum PropertyId {
     Bounds,
     TextSelection,
     CustomActions,
+    KeyShortcuts,
 
     // This MUST be last.
     Unset,
@@ -1025,9 +1042,44 @@ pub struct NodeBuilder {
     props: Vec<PropertyValue>,
 }
 
+/// A set of changes to an existing [`Node`], for cases where rebuilding
+/// the whole node would be wasteful, such as when only the numeric value
+/// of a slider or the text selection of a large text field has changed.
+///
+/// The role, actions, flags, and properties that aren't mentioned
+/// in the patch keep their current values when the patch is applied.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodePatch {
+    role: Option<Role>,
+    added_actions: u32,
+    removed_actions: u32,
+    set_flags: u32,
+    cleared_flags: u32,
+    props: Vec<(PropertyId, PropertyValue)>,
+}
+
+/// The parts of a [`Node`] that differ between two versions of it,
+/// as returned by [`Node::diff`].
+///
+/// For each flag and property of a node, this has a getter with the same
+/// name as the corresponding getter of [`Node`], which returns true if
+/// that flag or property differs. A property that is set in only one
+/// of the versions is considered to differ.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeDiff {
+    role: bool,
+    actions: u32,
+    flags: u32,
+    props: u128,
+}
+
 impl NodeClass {
     fn get_property<'a>(&self, props: &'a [PropertyValue], id: PropertyId) -> &'a PropertyValue {
-        let index = self.indices.0[id as usize];
+        self.get_property_at(props, id as usize)
+    }
+
+    fn get_property_at<'a>(&self, props: &'a [PropertyValue], id: usize) -> &'a PropertyValue {
+        let index = self.indices.0[id];
         if index == PropertyId::Unset as u8 {
             &PropertyValue::None
         } else {
@@ -1074,6 +1126,20 @@ impl NodeBuilder {
     }
 }
 
+impl NodePatch {
+    fn set_property(&mut self, id: PropertyId, value: PropertyValue) {
+        if let Some(entry) = self.props.iter_mut().find(|(entry_id, _)| *entry_id == id) {
+            entry.1 = value;
+        } else {
+            self.props.push((id, value));
+        }
+    }
+
+    fn clear_property(&mut self, id: PropertyId) {
+        self.set_property(id, PropertyValue::None);
+    }
+}
+
 macro_rules! flag_methods {
     ($($(#[$doc:meta])* ($id:ident, $getter:ident, $setter:ident, $clearer:ident)),+) => {
         impl Node {
@@ -1098,6 +1164,24 @@ macro_rules! flag_methods {
                 self.flags &= !((Flag::$id).mask());
             })*
         }
+        impl NodeDiff {
+            $(#[inline]
+            pub fn $getter(&self) -> bool {
+                (self.flags & (Flag::$id).mask()) != 0
+            })*
+        }
+        impl NodePatch {
+            $(#[inline]
+            pub fn $setter(&mut self) {
+                self.set_flags |= (Flag::$id).mask();
+                self.cleared_flags &= !((Flag::$id).mask());
+            }
+            #[inline]
+            pub fn $clearer(&mut self) {
+                self.cleared_flags |= (Flag::$id).mask();
+                self.set_flags &= !((Flag::$id).mask());
+            })*
+        }
     }
 }
 
@@ -1150,6 +1234,11 @@ macro_rules! box_type_setters {
                 self.set_property(id, PropertyValue::$variant(value.into()));
             })*
         }
+        impl NodePatch {
+            $(fn $method(&mut self, id: PropertyId, value: impl Into<Box<$type>>) {
+                self.set_property(id, PropertyValue::$variant(value.into()));
+            })*
+        }
     }
 }
 
@@ -1160,6 +1249,11 @@ macro_rules! copy_type_setters {
                 self.set_property(id, PropertyValue::$variant(value));
             })*
         }
+        impl NodePatch {
+            $(fn $method(&mut self, id: PropertyId, value: $type) {
+                self.set_property(id, PropertyValue::$variant(value));
+            })*
+        }
     }
 }
 
@@ -1181,6 +1275,11 @@ macro_rules! vec_type_methods {
                 }
             })*
         }
+        impl NodePatch {
+            $(fn $setter(&mut self, id: PropertyId, value: impl Into<Vec<$type>>) {
+                self.set_property(id, PropertyValue::$variant(value.into()));
+            })*
+        }
     }
 }
 
@@ -1208,6 +1307,22 @@ macro_rules! property_methods {
                 self.clear_property(PropertyId::$id);
             })*
         }
+        impl NodeDiff {
+            $(#[inline]
+            pub fn $getter(&self) -> bool {
+                self.has_property(PropertyId::$id)
+            })*
+        }
+        impl NodePatch {
+            $(#[inline]
+            pub fn $setter(&mut self, value: $setter_param) {
+                self.$type_setter(PropertyId::$id, value);
+            }
+            #[inline]
+            pub fn $clearer(&mut self) {
+                self.clear_property(PropertyId::$id);
+            })*
+        }
     }
 }
 
@@ -1348,6 +1463,22 @@ macro_rules! unique_enum_property_methods {
                 self.clear_property(PropertyId::$id);
             })*
         }
+        impl NodeDiff {
+            $(#[inline]
+            pub fn $getter(&self) -> bool {
+                self.has_property(PropertyId::$id)
+            })*
+        }
+        impl NodePatch {
+            $(#[inline]
+            pub fn $setter(&mut self, value: $id) {
+                self.set_property(PropertyId::$id, PropertyValue::$id(value));
+            }
+            #[inline]
+            pub fn $clearer(&mut self) {
+                self.clear_property(PropertyId::$id);
+            })*
+        }
     }
 }
 
@@ -1379,6 +1510,34 @@ impl NodeBuilder {
     }
 }
 
+impl Node {
+    /// Creates a builder with the same role, actions, flags, and properties
+    /// as this node, so a modified version of the node can be built.
+    /// If nothing is changed, building the result with the class set
+    /// that contains this node's class reuses that class.
+    pub fn to_builder(&self) -> NodeBuilder {
+        NodeBuilder {
+            class: *self.class,
+            flags: self.flags,
+            props: self.props.to_vec(),
+        }
+    }
+}
+
+impl From<Node> for NodeBuilder {
+    #[inline]
+    fn from(node: Node) -> Self {
+        node.to_builder()
+    }
+}
+
+impl From<&Node> for NodeBuilder {
+    #[inline]
+    fn from(node: &Node) -> Self {
+        node.to_builder()
+    }
+}
+
 impl Node {
     #[inline]
     pub fn role(&self) -> Role {
@@ -1404,6 +1563,102 @@ impl Node {
     }
 }
 
+impl Node {
+    /// Returns the parts of this node that differ from `other`.
+    pub fn diff(&self, other: &Node) -> NodeDiff {
+        let mut props = 0;
+        if !Arc::ptr_eq(&self.class, &other.class) || !Arc::ptr_eq(&self.props, &other.props) {
+            for id in 0..(PropertyId::Unset as usize) {
+                if self.class.get_property_at(&self.props, id)
+                    != other.class.get_property_at(&other.props, id)
+                {
+                    props |= 1 << id;
+                }
+            }
+        }
+        NodeDiff {
+            role: self.class.role != other.class.role,
+            actions: self.class.actions.0 ^ other.class.actions.0,
+            flags: self.flags ^ other.flags,
+            props,
+        }
+    }
+}
+
+impl NodeDiff {
+    /// Returns true if the two versions of the node are identical.
+    pub fn is_empty(&self) -> bool {
+        !self.role && self.actions == 0 && self.flags == 0 && self.props == 0
+    }
+
+    #[inline]
+    pub fn role(&self) -> bool {
+        self.role
+    }
+
+    /// Returns true if only one of the versions supports the given action.
+    #[inline]
+    pub fn supports_action(&self, action: Action) -> bool {
+        (self.actions & action.mask()) != 0
+    }
+
+    #[inline]
+    fn has_property(&self, id: PropertyId) -> bool {
+        (self.props & (1 << (id as u8))) != 0
+    }
+}
+
+impl NodePatch {
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns true if applying this patch wouldn't change anything.
+    pub fn is_empty(&self) -> bool {
+        self.role.is_none()
+            && self.added_actions == 0
+            && self.removed_actions == 0
+            && self.set_flags == 0
+            && self.cleared_flags == 0
+            && self.props.is_empty()
+    }
+
+    #[inline]
+    pub fn set_role(&mut self, value: Role) {
+        self.role = Some(value);
+    }
+    #[inline]
+    pub fn add_action(&mut self, action: Action) {
+        self.added_actions |= action.mask();
+        self.removed_actions &= !(action.mask());
+    }
+    #[inline]
+    pub fn remove_action(&mut self, action: Action) {
+        self.removed_actions |= action.mask();
+        self.added_actions &= !(action.mask());
+    }
+
+    /// Creates a new node by applying this patch to an existing node.
+    pub fn apply(&self, node: &Node, classes: &mut NodeClassSet) -> Node {
+        let mut builder = node.to_builder();
+        if let Some(role) = self.role {
+            builder.class.role = role;
+        }
+        builder.class.actions.0 |= self.added_actions;
+        builder.class.actions.0 &= !self.removed_actions;
+        builder.flags |= self.set_flags;
+        builder.flags &= !self.cleared_flags;
+        for (id, value) in &self.props {
+            match value {
+                PropertyValue::None => builder.clear_property(*id),
+                value => builder.set_property(*id, value.clone()),
+            }
+        }
+        builder.build(classes)
+    }
+}
+
 impl NodeBuilder {
     #[inline]
     pub fn supports_action(&self, action: Action) -> bool {
@@ -1483,7 +1738,8 @@ option_ref_type_getters! {
     (get_affine_property, Affine, Affine),
     (get_string_property, str, String),
     (get_coord_slice_property, [f32], CoordSlice),
-    (get_text_selection_property, TextSelection, TextSelection)
+    (get_text_selection_property, TextSelection, TextSelection),
+    (get_key_shortcuts_property, KeyShortcuts, KeyShortcuts)
 }
 
 slice_type_getters! {
@@ -1505,7 +1761,8 @@ box_type_setters! {
     (set_string_property, str, String),
     (set_length_slice_property, [u8], LengthSlice),
     (set_coord_slice_property, [f32], CoordSlice),
-    (set_text_selection_property, TextSelection, TextSelection)
+    (set_text_selection_property, TextSelection, TextSelection),
+    (set_key_shortcuts_property, KeyShortcuts, KeyShortcuts)
 }
 
 copy_type_setters! {
@@ -1571,7 +1828,6 @@ string_property_methods! {
     /// to support third-party math accessibility products that parse MathML.
     (InnerHtml, inner_html, set_inner_html, clear_inner_html),
     (InputType, input_type, set_input_type, clear_input_type),
-    (KeyShortcuts, key_shortcuts, set_key_shortcuts, clear_key_shortcuts),
     /// Only present when different from parent.
     (Language, language, set_language, clear_language),
     (LiveRelevant, live_relevant, set_live_relevant, clear_live_relevant),
@@ -1790,7 +2046,16 @@ property_methods! {
     /// [`transform`]: Node::transform
     (Bounds, bounds, get_rect_property, Option<Rect>, set_bounds, set_rect_property, Rect, clear_bounds),
 
-    (TextSelection, text_selection, get_text_selection_property, Option<&TextSelection>, set_text_selection, set_text_selection_property, impl Into<Box<TextSelection>>, clear_text_selection)
+    (TextSelection, text_selection, get_text_selection_property, Option<&TextSelection>, set_text_selection, set_text_selection_property, impl Into<Box<TextSelection>>, clear_text_selection),
+
+    /// The keyboard shortcuts that activate or focus this node. This is
+    /// the structured form of the ARIA `aria-keyshortcuts` attribute,
+    /// optionally with a mnemonic. The setter also accepts that attribute
+    /// as a string. [`access_key`] remains available for the free-form
+    /// HTML `accesskey` attribute.
+    ///
+    /// [`access_key`]: Node::access_key
+    (KeyShortcuts, key_shortcuts, get_key_shortcuts_property, Option<&KeyShortcuts>, set_key_shortcuts, set_key_shortcuts_property, impl Into<Box<KeyShortcuts>>, clear_key_shortcuts)
 }
 
 vec_property_methods! {
@@ -1824,12 +2089,10 @@ macro_rules! serialize_class_fields {
 
 #[cfg(feature = "serde")]
 macro_rules! serialize_property {
-    ($self:ident, $map:ident, $index:ident, $id:ident, { $($variant:ident),+ }) => {
-        match &$self.props[$index as usize] {
-            PropertyValue::None => (),
-            $(PropertyValue::$variant(value) => {
-                $map.serialize_entry(&$id, &Some(value))?;
-            })*
+    ($map:ident, $id:ident, $value:ident, { $($variant:ident),+ }) => {
+        match $value {
+            PropertyValue::None => $map.serialize_entry(&$id, &None::<()>),
+            $(PropertyValue::$variant(value) => $map.serialize_entry(&$id, &Some(value)),)*
         }
     }
 }
@@ -1847,22 +2110,185 @@ macro_rules! deserialize_class_field {
 
 #[cfg(feature = "serde")]
 macro_rules! deserialize_property {
-    ($builder:ident, $map:ident, $key:ident, { $($type:ident { $($id:ident),+ }),+ }) => {
+    ($map:ident, $key:ident, { $($type:ident { $($id:ident),+ }),+ }) => {
         match $key {
             $($(PropertyId::$id => {
-                if let Some(value) = $map.next_value()? {
-                    $builder.set_property(PropertyId::$id, PropertyValue::$type(value));
-                } else {
-                    $builder.clear_property(PropertyId::$id);
-                }
+                Some($map.next_value::<Option<_>>()?.map_or(PropertyValue::None, PropertyValue::$type))
             })*)*
             PropertyId::Unset => {
                 let _ = $map.next_value::<IgnoredAny>()?;
+                None
             }
         }
     }
 }
 
+#[cfg(feature = "serde")]
+fn serialize_property_value<M: SerializeMap>(
+    map: &mut M,
+    id: PropertyId,
+    value: &PropertyValue,
+) -> Result<(), M::Error> {
+    serialize_property!(map, id, value, {
+        NodeIdVec,
+        NodeId,
+        String,
+        F64,
+        Usize,
+        Color,
+        TextDecoration,
+        LengthSlice,
+        CoordSlice,
+        Bool,
+        NameFrom,
+        DescriptionFrom,
+        Invalid,
+        CheckedState,
+        Live,
+        DefaultActionVerb,
+        TextDirection,
+        Orientation,
+        SortDirection,
+        AriaCurrent,
+        HasPopup,
+        ListStyle,
+        TextAlign,
+        VerticalOffset,
+        Affine,
+        Rect,
+        TextSelection,
+        CustomActionVec,
+        KeyShortcuts
+    })
+}
+
+/// Deserializes the next value in the map as the value of the given
+/// property. Returns `None` if the value should be ignored,
+/// or [`PropertyValue::None`] if the property should be cleared.
+#[cfg(feature = "serde")]
+fn next_property_value<'de, V: MapAccess<'de>>(
+    map: &mut V,
+    id: PropertyId,
+) -> Result<Option<PropertyValue>, V::Error> {
+    Ok(deserialize_property!(map, id, {
+        NodeIdVec {
+            Children,
+            IndirectChildren,
+            Controls,
+            Details,
+            DescribedBy,
+            FlowTo,
+            LabelledBy,
+            RadioGroup
+        },
+        NodeId {
+            ActiveDescendant,
+            ErrorMessage,
+            InPageLinkTarget,
+            MemberOf,
+            NextOnLine,
+            PreviousOnLine,
+            PopupFor,
+            TableHeader,
+            TableRowHeader,
+            TableColumnHeader,
+            NextFocus,
+            PreviousFocus
+        },
+        String {
+            Name,
+            Description,
+            Value,
+            AccessKey,
+            AutoComplete,
+            CheckedStateDescription,
+            ClassName,
+            CssDisplay,
+            FontFamily,
+            HtmlTag,
+            InnerHtml,
+            InputType,
+            Language,
+            LiveRelevant,
+            Placeholder,
+            AriaRole,
+            RoleDescription,
+            Tooltip,
+            Url
+        },
+        F64 {
+            ScrollX,
+            ScrollXMin,
+            ScrollXMax,
+            ScrollY,
+            ScrollYMin,
+            ScrollYMax,
+            NumericValue,
+            MinNumericValue,
+            MaxNumericValue,
+            NumericValueStep,
+            NumericValueJump,
+            FontSize,
+            FontWeight,
+            TextIndent
+        },
+        Usize {
+            TableRowCount,
+            TableColumnCount,
+            TableRowIndex,
+            TableColumnIndex,
+            TableCellColumnIndex,
+            TableCellColumnSpan,
+            TableCellRowIndex,
+            TableCellRowSpan,
+            HierarchicalLevel,
+            SizeOfSet,
+            PositionInSet
+        },
+        Color {
+            ColorValue,
+            BackgroundColor,
+            ForegroundColor
+        },
+        TextDecoration {
+            Overline,
+            Strikethrough,
+            Underline
+        },
+        LengthSlice {
+            CharacterLengths,
+            WordLengths
+        },
+        CoordSlice {
+            CharacterPositions,
+            CharacterWidths
+        },
+        Bool {
+            Expanded,
+            Selected
+        },
+        NameFrom { NameFrom },
+        DescriptionFrom { DescriptionFrom },
+        Invalid { Invalid },
+        CheckedState { CheckedState },
+        Live { Live },
+        DefaultActionVerb { DefaultActionVerb },
+        TextDirection { TextDirection },
+        Orientation { Orientation },
+        SortDirection { SortDirection },
+        AriaCurrent { AriaCurrent },
+        HasPopup { HasPopup },
+        ListStyle { ListStyle },
+        TextAlign { TextAlign },
+        VerticalOffset { VerticalOffset },
+        Affine { Transform },
+        Rect { Bounds },
+        TextSelection { TextSelection },
+        CustomActionVec { CustomActions },
+        KeyShortcuts { KeyShortcuts }
+    }))
+}
+
 #[cfg(feature = "serde")]
 impl Serialize for Node {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -1885,37 +2311,10 @@ impl Serialize for Node {
             if index == PropertyId::Unset as u8 {
                 continue;
             }
-            let id = PropertyId::n(id as _).unwrap();
-            serialize_property!(self, map, index, id, {
-                NodeIdVec,
-                NodeId,
-                String,
-                F64,
-                Usize,
-                Color,
-                TextDecoration,
-                LengthSlice,
-                CoordSlice,
-                Bool,
-                NameFrom,
-                DescriptionFrom,
-                Invalid,
-                CheckedState,
-                Live,
-                DefaultActionVerb,
-                TextDirection,
-                Orientation,
-                SortDirection,
-                AriaCurrent,
-                HasPopup,
-                ListStyle,
-                TextAlign,
-                VerticalOffset,
-                Affine,
-                Rect,
-                TextSelection,
-                CustomActionVec
-            });
+            let value = &self.props[index as usize];
+            if !matches!(value, PropertyValue::None) {
+                serialize_property_value(&mut map, PropertyId::n(id as _).unwrap(), value)?;
+            }
         }
         map.end()
     }
@@ -1953,125 +2352,11 @@ impl<'de> Visitor<'de> for NodeVisitor {
                         builder.flags &= !(flag.mask());
                     }
                 }
-                DeserializeKey::Property(id) => {
-                    deserialize_property!(builder, map, id, {
-                        NodeIdVec {
-                            Children,
-                            IndirectChildren,
-                            Controls,
-                            Details,
-                            DescribedBy,
-                            FlowTo,
-                            LabelledBy,
-                            RadioGroup
-                        },
-                        NodeId {
-                            ActiveDescendant,
-                            ErrorMessage,
-                            InPageLinkTarget,
-                            MemberOf,
-                            NextOnLine,
-                            PreviousOnLine,
-                            PopupFor,
-                            TableHeader,
-                            TableRowHeader,
-                            TableColumnHeader,
-                            NextFocus,
-                            PreviousFocus
-                        },
-                        String {
-                            Name,
-                            Description,
-                            Value,
-                            AccessKey,
-                            AutoComplete,
-                            CheckedStateDescription,
-                            ClassName,
-                            CssDisplay,
-                            FontFamily,
-                            HtmlTag,
-                            InnerHtml,
-                            InputType,
-                            KeyShortcuts,
-                            Language,
-                            LiveRelevant,
-                            Placeholder,
-                            AriaRole,
-                            RoleDescription,
-                            Tooltip,
-                            Url
-                        },
-                        F64 {
-                            ScrollX,
-                            ScrollXMin,
-                            ScrollXMax,
-                            ScrollY,
-                            ScrollYMin,
-                            ScrollYMax,
-                            NumericValue,
-                            MinNumericValue,
-                            MaxNumericValue,
-                            NumericValueStep,
-                            NumericValueJump,
-                            FontSize,
-                            FontWeight,
-                            TextIndent
-                        },
-                        Usize {
-                            TableRowCount,
-                            TableColumnCount,
-                            TableRowIndex,
-                            TableColumnIndex,
-                            TableCellColumnIndex,
-                            TableCellColumnSpan,
-                            TableCellRowIndex,
-                            TableCellRowSpan,
-                            HierarchicalLevel,
-                            SizeOfSet,
-                            PositionInSet
-                        },
-                        Color {
-                            ColorValue,
-                            BackgroundColor,
-                            ForegroundColor
-                        },
-                        TextDecoration {
-                            Overline,
-                            Strikethrough,
-                            Underline
-                        },
-                        LengthSlice {
-                            CharacterLengths,
-                            WordLengths
-                        },
-                        CoordSlice {
-                            CharacterPositions,
-                            CharacterWidths
-                        },
-                        Bool {
-                            Expanded,
-                            Selected
-                        },
-                        NameFrom { NameFrom },
-                        DescriptionFrom { DescriptionFrom },
-                        Invalid { Invalid },
-                        CheckedState { CheckedState },
-                        Live { Live },
-                        DefaultActionVerb { DefaultActionVerb },
-                        TextDirection { TextDirection },
-                        Orientation { Orientation },
-                        SortDirection { SortDirection },
-                        AriaCurrent { AriaCurrent },
-                        HasPopup { HasPopup },
-                        ListStyle { ListStyle },
-                        TextAlign { TextAlign },
-                        VerticalOffset { VerticalOffset },
-                        Affine { Transform },
-                        Rect { Bounds },
-                        TextSelection { TextSelection },
-                        CustomActionVec { CustomActions }
-                    });
-                }
+                DeserializeKey::Property(id) => match next_property_value(&mut map, id)? {
+                    Some(PropertyValue::None) => builder.clear_property(id),
+                    Some(value) => builder.set_property(id, value),
+                    None => (),
+                },
                 DeserializeKey::Unknown(_) => {
                     let _ = map.next_value::<IgnoredAny>()?;
                 }
@@ -2092,6 +2377,130 @@ impl<'de> Deserialize<'de> for Node {
     }
 }
 
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+enum PatchFieldId {
+    Role,
+    AddedActions,
+    RemovedActions,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+enum DeserializePatchKey {
+    Field(PatchFieldId),
+    Flag(Flag),
+    Property(PropertyId),
+    Unknown(String),
+}
+
+/// The serialized form of a patch is a map with the same keys as
+/// a serialized [`Node`], except that the actions are split into
+/// `addedActions` and `removedActions`. Flags that the patch clears
+/// are `false`, and properties that it clears are `null`.
+#[cfg(feature = "serde")]
+impl Serialize for NodePatch {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(role) = self.role {
+            map.serialize_entry(&PatchFieldId::Role, &role)?;
+        }
+        if self.added_actions != 0 {
+            map.serialize_entry(&PatchFieldId::AddedActions, &Actions(self.added_actions))?;
+        }
+        if self.removed_actions != 0 {
+            map.serialize_entry(
+                &PatchFieldId::RemovedActions,
+                &Actions(self.removed_actions),
+            )?;
+        }
+        for i in 0..((size_of_val(&self.set_flags) as u8) * 8) {
+            if let Some(flag) = Flag::n(i) {
+                if (self.set_flags & flag.mask()) != 0 {
+                    map.serialize_entry(&flag, &true)?;
+                } else if (self.cleared_flags & flag.mask()) != 0 {
+                    map.serialize_entry(&flag, &false)?;
+                }
+            }
+        }
+        for (id, value) in &self.props {
+            serialize_property_value(&mut map, *id, value)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct NodePatchVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for NodePatchVisitor {
+    type Value = NodePatch;
+
+    #[inline]
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct NodePatch")
+    }
+
+    fn visit_map<V>(self, mut map: V) -> Result<NodePatch, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut patch = NodePatch::new();
+        while let Some(key) = map.next_key()? {
+            match key {
+                DeserializePatchKey::Field(PatchFieldId::Role) => {
+                    patch.role = Some(map.next_value()?);
+                }
+                DeserializePatchKey::Field(PatchFieldId::AddedActions) => {
+                    let actions: Actions = map.next_value()?;
+                    patch.added_actions |= actions.0;
+                    patch.removed_actions &= !actions.0;
+                }
+                DeserializePatchKey::Field(PatchFieldId::RemovedActions) => {
+                    let actions: Actions = map.next_value()?;
+                    patch.removed_actions |= actions.0;
+                    patch.added_actions &= !actions.0;
+                }
+                DeserializePatchKey::Flag(flag) => {
+                    if map.next_value()? {
+                        patch.set_flags |= flag.mask();
+                        patch.cleared_flags &= !(flag.mask());
+                    } else {
+                        patch.cleared_flags |= flag.mask();
+                        patch.set_flags &= !(flag.mask());
+                    }
+                }
+                DeserializePatchKey::Property(id) => {
+                    if let Some(value) = next_property_value(&mut map, id)? {
+                        patch.set_property(id, value);
+                    }
+                }
+                DeserializePatchKey::Unknown(_) => {
+                    let _ = map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(patch)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for NodePatch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(NodePatchVisitor)
+    }
+}
+
 #[cfg(feature = "schemars")]
 macro_rules! add_schema_property {
     ($gen:ident, $properties:ident, $enum_value:expr, $type:ty) => {{
@@ -2116,6 +2525,162 @@ macro_rules! add_properties_to_schema {
     }
 }
 
+#[cfg(feature = "schemars")]
+fn add_node_fields_to_schema(
+    gen: &mut SchemaGenerator,
+    properties: &mut SchemaMap<String, Schema>,
+) {
+    add_flags_to_schema!(gen, properties, {
+        AutofillAvailable,
+        Default,
+        Editable,
+        Hovered,
+        Hidden,
+        Linked,
+        Multiline,
+        Multiselectable,
+        Protected,
+        Required,
+        Visited,
+        Busy,
+        LiveAtomic,
+        Modal,
+        Scrollable,
+        SelectedFromFocus,
+        TouchPassThrough,
+        ReadOnly,
+        Disabled,
+        Bold,
+        Italic,
+        CanvasHasFallback,
+        ClipsChildren,
+        IsLineBreakingObject,
+        IsPageBreakingObject,
+        IsSpellingError,
+        IsGrammarError,
+        IsSearchMatch,
+        IsSuggestion,
+        IsNonatomicTextFieldRoot
+    });
+    add_properties_to_schema!(gen, properties, {
+        Vec<NodeId> {
+            Children,
+            IndirectChildren,
+            Controls,
+            Details,
+            DescribedBy,
+            FlowTo,
+            LabelledBy,
+            RadioGroup
+        },
+        NodeId {
+            ActiveDescendant,
+            ErrorMessage,
+            InPageLinkTarget,
+            MemberOf,
+            NextOnLine,
+            PreviousOnLine,
+            PopupFor,
+            TableHeader,
+            TableRowHeader,
+            TableColumnHeader,
+            NextFocus,
+            PreviousFocus
+        },
+        Box<str> {
+            Name,
+            Description,
+            Value,
+            AccessKey,
+            AutoComplete,
+            CheckedStateDescription,
+            ClassName,
+            CssDisplay,
+            FontFamily,
+            HtmlTag,
+            InnerHtml,
+            InputType,
+            Language,
+            LiveRelevant,
+            Placeholder,
+            AriaRole,
+            RoleDescription,
+            Tooltip,
+            Url
+        },
+        f64 {
+            ScrollX,
+            ScrollXMin,
+            ScrollXMax,
+            ScrollY,
+            ScrollYMin,
+            ScrollYMax,
+            NumericValue,
+            MinNumericValue,
+            MaxNumericValue,
+            NumericValueStep,
+            NumericValueJump,
+            FontSize,
+            FontWeight,
+            TextIndent
+        },
+        usize {
+            TableRowCount,
+            TableColumnCount,
+            TableRowIndex,
+            TableColumnIndex,
+            TableCellColumnIndex,
+            TableCellColumnSpan,
+            TableCellRowIndex,
+            TableCellRowSpan,
+            HierarchicalLevel,
+            SizeOfSet,
+            PositionInSet
+        },
+        u32 {
+            ColorValue,
+            BackgroundColor,
+            ForegroundColor
+        },
+        TextDecoration {
+            Overline,
+            Strikethrough,
+            Underline
+        },
+        Box<[u8]> {
+            CharacterLengths,
+            WordLengths
+        },
+        Box<[f32]> {
+            CharacterPositions,
+            CharacterWidths
+        },
+        bool {
+            Expanded,
+            Selected
+        },
+        NameFrom { NameFrom },
+        DescriptionFrom { DescriptionFrom },
+        Invalid { Invalid },
+        CheckedState { CheckedState },
+        Live { Live },
+        DefaultActionVerb { DefaultActionVerb },
+        TextDirection { TextDirection },
+        Orientation { Orientation },
+        SortDirection { SortDirection },
+        AriaCurrent { AriaCurrent },
+        HasPopup { HasPopup },
+        ListStyle { ListStyle },
+        TextAlign { TextAlign },
+        VerticalOffset { VerticalOffset },
+        Affine { Transform },
+        Rect { Bounds },
+        TextSelection { TextSelection },
+        Vec<CustomAction> { CustomActions },
+        KeyShortcuts { KeyShortcuts }
+    });
+}
+
 #[cfg(feature = "schemars")]
 impl JsonSchema for Node {
     #[inline]
@@ -2127,155 +2692,35 @@ impl JsonSchema for Node {
         let mut properties = SchemaMap::<String, Schema>::new();
         add_schema_property!(gen, properties, ClassFieldId::Role, Role);
         add_schema_property!(gen, properties, ClassFieldId::Actions, Actions);
-        add_flags_to_schema!(gen, properties, {
-            AutofillAvailable,
-            Default,
-            Editable,
-            Hovered,
-            Hidden,
-            Linked,
-            Multiline,
-            Multiselectable,
-            Protected,
-            Required,
-            Visited,
-            Busy,
-            LiveAtomic,
-            Modal,
-            Scrollable,
-            SelectedFromFocus,
-            TouchPassThrough,
-            ReadOnly,
-            Disabled,
-            Bold,
-            Italic,
-            CanvasHasFallback,
-            ClipsChildren,
-            IsLineBreakingObject,
-            IsPageBreakingObject,
-            IsSpellingError,
-            IsGrammarError,
-            IsSearchMatch,
-            IsSuggestion,
-            IsNonatomicTextFieldRoot
-        });
-        add_properties_to_schema!(gen, properties, {
-            Vec<NodeId> {
-                Children,
-                IndirectChildren,
-                Controls,
-                Details,
-                DescribedBy,
-                FlowTo,
-                LabelledBy,
-                RadioGroup
-            },
-            NodeId {
-                ActiveDescendant,
-                ErrorMessage,
-                InPageLinkTarget,
-                MemberOf,
-                NextOnLine,
-                PreviousOnLine,
-                PopupFor,
-                TableHeader,
-                TableRowHeader,
-                TableColumnHeader,
-                NextFocus,
-                PreviousFocus
-            },
-            Box<str> {
-                Name,
-                Description,
-                Value,
-                AccessKey,
-                AutoComplete,
-                CheckedStateDescription,
-                ClassName,
-                CssDisplay,
-                FontFamily,
-                HtmlTag,
-                InnerHtml,
-                InputType,
-                KeyShortcuts,
-                Language,
-                LiveRelevant,
-                Placeholder,
-                AriaRole,
-                RoleDescription,
-                Tooltip,
-                Url
-            },
-            f64 {
-                ScrollX,
-                ScrollXMin,
-                ScrollXMax,
-                ScrollY,
-                ScrollYMin,
-                ScrollYMax,
-                NumericValue,
-                MinNumericValue,
-                MaxNumericValue,
-                NumericValueStep,
-                NumericValueJump,
-                FontSize,
-                FontWeight,
-                TextIndent
-            },
-            usize {
-                TableRowCount,
-                TableColumnCount,
-                TableRowIndex,
-                TableColumnIndex,
-                TableCellColumnIndex,
-                TableCellColumnSpan,
-                TableCellRowIndex,
-                TableCellRowSpan,
-                HierarchicalLevel,
-                SizeOfSet,
-                PositionInSet
-            },
-            u32 {
-                ColorValue,
-                BackgroundColor,
-                ForegroundColor
-            },
-            TextDecoration {
-                Overline,
-                Strikethrough,
-                Underline
-            },
-            Box<[u8]> {
-                CharacterLengths,
-                WordLengths
-            },
-            Box<[f32]> {
-                CharacterPositions,
-                CharacterWidths
-            },
-            bool {
-                Expanded,
-                Selected
-            },
-            NameFrom { NameFrom },
-            DescriptionFrom { DescriptionFrom },
-            Invalid { Invalid },
-            CheckedState { CheckedState },
-            Live { Live },
-            DefaultActionVerb { DefaultActionVerb },
-            TextDirection { TextDirection },
-            Orientation { Orientation },
-            SortDirection { SortDirection },
-            AriaCurrent { AriaCurrent },
-            HasPopup { HasPopup },
-            ListStyle { ListStyle },
-            TextAlign { TextAlign },
-            VerticalOffset { VerticalOffset },
-            Affine { Transform },
-            Rect { Bounds },
-            TextSelection { TextSelection },
-            Vec<CustomAction> { CustomActions }
-        });
+        add_node_fields_to_schema(gen, &mut properties);
+        SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            object: Some(
+                ObjectValidation {
+                    properties,
+                    ..Default::default()
+                }
+                .into(),
+            ),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for NodePatch {
+    #[inline]
+    fn schema_name() -> String {
+        "NodePatch".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut properties = SchemaMap::<String, Schema>::new();
+        add_schema_property!(gen, properties, PatchFieldId::Role, Role);
+        add_schema_property!(gen, properties, PatchFieldId::AddedActions, Actions);
+        add_schema_property!(gen, properties, PatchFieldId::RemovedActions, Actions);
+        add_node_fields_to_schema(gen, &mut properties);
         SchemaObject {
             instance_type: Some(InstanceType::Object.into()),
             object: Some(
@@ -2427,3 +2872,544 @@ pub trait ActionHandler {
     /// the request to another thread.
     fn do_action(&self, request: ActionRequest);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+
+    fn node_with_every_property_kind(classes: &mut NodeClassSet) -> Node {
+        let mut builder = NodeBuilder::new(Role::TextField);
+        builder.add_action(Action::Focus);
+        builder.add_action(Action::SetValue);
+        builder.set_editable();
+        builder.set_required();
+        builder.set_children(vec![NODE_ID_1, NODE_ID_2]);
+        builder.set_active_descendant(NODE_ID_2);
+        builder.set_name("Name");
+        builder.set_numeric_value(1.5);
+        builder.set_size_of_set(3);
+        builder.set_background_color(0xff0000ff);
+        builder.set_underline(TextDecoration::Dotted);
+        builder.set_character_lengths([1, 2]);
+        builder.set_character_positions([0.0, 5.5]);
+        builder.set_expanded(false);
+        builder.set_live(Live::Polite);
+        builder.set_transform(Affine::scale(2.0));
+        builder.set_bounds(Rect::new(0.0, 0.0, 10.0, 20.0));
+        builder.set_text_selection(TextSelection {
+            anchor: TextPosition {
+                node: NODE_ID_1,
+                character_index: 0,
+            },
+            focus: TextPosition {
+                node: NODE_ID_2,
+                character_index: 1,
+            },
+        });
+        builder.push_custom_action(CustomAction {
+            id: 1,
+            description: "Archive".into(),
+        });
+        builder.set_key_shortcuts(KeyShortcuts {
+            mnemonic: None,
+            accelerators: vec![KeyCombination::new(
+                KeyModifiers {
+                    control: true,
+                    ..Default::default()
+                },
+                "n",
+            )],
+        });
+        builder.build(classes)
+    }
+
+    #[test]
+    fn to_builder_round_trip() {
+        let mut classes = NodeClassSet::new();
+        let node = node_with_every_property_kind(&mut classes);
+        let rebuilt = node.to_builder().build(&mut classes);
+        assert_eq!(node, rebuilt);
+        assert!(Arc::ptr_eq(&node.class, &rebuilt.class));
+        assert_eq!(node, NodeBuilder::from(&node).build(&mut classes));
+        assert_eq!(node, NodeBuilder::from(node.clone()).build(&mut classes));
+    }
+
+    #[test]
+    fn to_builder_allows_modification() {
+        let mut classes = NodeClassSet::new();
+        let node = node_with_every_property_kind(&mut classes);
+        let mut builder = node.to_builder();
+        assert_eq!(node.name(), builder.name());
+        builder.set_name("Other name");
+        builder.clear_editable();
+        let modified = builder.build(&mut classes);
+        assert_eq!(Some("Other name"), modified.name());
+        assert!(!modified.is_editable());
+        assert_eq!(node.children(), modified.children());
+        assert_eq!(node.key_shortcuts(), modified.key_shortcuts());
+        assert_eq!(Some("Name"), node.name());
+        assert!(node.is_editable());
+    }
+
+    #[test]
+    fn diff() {
+        let mut classes = NodeClassSet::new();
+        let node = node_with_every_property_kind(&mut classes);
+        assert!(node.diff(&node).is_empty());
+        assert!(node.diff(&node.to_builder().build(&mut classes)).is_empty());
+
+        let mut builder = node.to_builder();
+        builder.set_role(Role::SearchBox);
+        builder.remove_action(Action::Focus);
+        builder.clear_editable();
+        builder.set_name("Other name");
+        builder.clear_bounds();
+        builder.set_class_name("search");
+        builder.clear_key_shortcuts();
+        let diff = node.diff(&builder.build(&mut classes));
+        assert!(!diff.is_empty());
+        assert!(diff.role());
+        assert!(diff.supports_action(Action::Focus));
+        assert!(!diff.supports_action(Action::SetValue));
+        assert!(diff.is_editable());
+        assert!(!diff.is_required());
+        assert!(diff.name());
+        assert!(diff.bounds());
+        assert!(diff.class_name());
+        assert!(diff.key_shortcuts());
+        assert!(!diff.children());
+        assert!(!diff.custom_actions());
+    }
+
+    /// A minimal self-describing data format, so that the hand-written
+    /// serialization code can be round-tripped without a format crate.
+    #[cfg(feature = "serde")]
+    mod value {
+        use serde::{
+            de::{self, value::Error, IntoDeserializer},
+            forward_to_deserialize_any,
+            ser::{self, Error as _, Impossible},
+            Serialize,
+        };
+
+        #[derive(Clone, Debug, PartialEq)]
+        pub(super) enum Value {
+            Null,
+            Bool(bool),
+            I64(i64),
+            U64(u64),
+            U128(u128),
+            F64(f64),
+            String(String),
+            Seq(Vec<Value>),
+            Map(Vec<(Value, Value)>),
+        }
+
+        impl Value {
+            pub(super) fn get(&self, key: &str) -> Option<&Value> {
+                match self {
+                    Value::Map(entries) => entries
+                        .iter()
+                        .find(|(entry_key, _)| *entry_key == Value::String(key.into()))
+                        .map(|(_, value)| value),
+                    _ => None,
+                }
+            }
+        }
+
+        pub(super) struct Serializer;
+
+        pub(super) struct SeqSerializer(Vec<Value>);
+
+        pub(super) struct MapSerializer {
+            entries: Vec<(Value, Value)>,
+            key: Option<Value>,
+        }
+
+        impl ser::Serializer for Serializer {
+            type Ok = Value;
+            type Error = Error;
+            type SerializeSeq = SeqSerializer;
+            type SerializeTuple = SeqSerializer;
+            type SerializeTupleStruct = SeqSerializer;
+            type SerializeTupleVariant = Impossible<Value, Error>;
+            type SerializeMap = MapSerializer;
+            type SerializeStruct = MapSerializer;
+            type SerializeStructVariant = Impossible<Value, Error>;
+
+            fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+                Ok(Value::Bool(v))
+            }
+            fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+                self.serialize_i64(v.into())
+            }
+            fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+                self.serialize_i64(v.into())
+            }
+            fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+                self.serialize_i64(v.into())
+            }
+            fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+                Ok(Value::I64(v))
+            }
+            fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+                self.serialize_u64(v.into())
+            }
+            fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+                self.serialize_u64(v.into())
+            }
+            fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+                self.serialize_u64(v.into())
+            }
+            fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+                Ok(Value::U64(v))
+            }
+            fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+                Ok(Value::U128(v))
+            }
+            fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+                self.serialize_f64(v.into())
+            }
+            fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+                Ok(Value::F64(v))
+            }
+            fn serialize_char(self, v: char) -> Result<Value, Error> {
+                Ok(Value::String(v.into()))
+            }
+            fn serialize_str(self, v: &str) -> Result<Value, Error> {
+                Ok(Value::String(v.into()))
+            }
+            fn serialize_bytes(self, _: &[u8]) -> Result<Value, Error> {
+                Err(Error::custom("bytes aren't supported"))
+            }
+            fn serialize_none(self) -> Result<Value, Error> {
+                Ok(Value::Null)
+            }
+            fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, Error> {
+                value.serialize(self)
+            }
+            fn serialize_unit(self) -> Result<Value, Error> {
+                Ok(Value::Null)
+            }
+            fn serialize_unit_struct(self, _: &'static str) -> Result<Value, Error> {
+                Ok(Value::Null)
+            }
+            fn serialize_unit_variant(
+                self,
+                _: &'static str,
+                _: u32,
+                variant: &'static str,
+            ) -> Result<Value, Error> {
+                Ok(Value::String(variant.into()))
+            }
+            fn serialize_newtype_struct<T: ?Sized + Serialize>(
+                self,
+                _: &'static str,
+                value: &T,
+            ) -> Result<Value, Error> {
+                value.serialize(self)
+            }
+            fn serialize_newtype_variant<T: ?Sized + Serialize>(
+                self,
+                _: &'static str,
+                _: u32,
+                variant: &'static str,
+                value: &T,
+            ) -> Result<Value, Error> {
+                Ok(Value::Map(vec![(
+                    Value::String(variant.into()),
+                    value.serialize(self)?,
+                )]))
+            }
+            fn serialize_seq(self, _: Option<usize>) -> Result<SeqSerializer, Error> {
+                Ok(SeqSerializer(Vec::new()))
+            }
+            fn serialize_tuple(self, _: usize) -> Result<SeqSerializer, Error> {
+                Ok(SeqSerializer(Vec::new()))
+            }
+            fn serialize_tuple_struct(
+                self,
+                _: &'static str,
+                _: usize,
+            ) -> Result<SeqSerializer, Error> {
+                Ok(SeqSerializer(Vec::new()))
+            }
+            fn serialize_tuple_variant(
+                self,
+                _: &'static str,
+                _: u32,
+                _: &'static str,
+                _: usize,
+            ) -> Result<Self::SerializeTupleVariant, Error> {
+                Err(Error::custom("tuple variants aren't supported"))
+            }
+            fn serialize_map(self, _: Option<usize>) -> Result<MapSerializer, Error> {
+                Ok(MapSerializer {
+                    entries: Vec::new(),
+                    key: None,
+                })
+            }
+            fn serialize_struct(self, _: &'static str, _: usize) -> Result<MapSerializer, Error> {
+                self.serialize_map(None)
+            }
+            fn serialize_struct_variant(
+                self,
+                _: &'static str,
+                _: u32,
+                _: &'static str,
+                _: usize,
+            ) -> Result<Self::SerializeStructVariant, Error> {
+                Err(Error::custom("struct variants aren't supported"))
+            }
+        }
+
+        impl ser::SerializeSeq for SeqSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+                self.0.push(value.serialize(Serializer)?);
+                Ok(())
+            }
+            fn end(self) -> Result<Value, Error> {
+                Ok(Value::Seq(self.0))
+            }
+        }
+
+        impl ser::SerializeTuple for SeqSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+                ser::SerializeSeq::serialize_element(self, value)
+            }
+            fn end(self) -> Result<Value, Error> {
+                ser::SerializeSeq::end(self)
+            }
+        }
+
+        impl ser::SerializeTupleStruct for SeqSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+                ser::SerializeSeq::serialize_element(self, value)
+            }
+            fn end(self) -> Result<Value, Error> {
+                ser::SerializeSeq::end(self)
+            }
+        }
+
+        impl ser::SerializeMap for MapSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+                self.key = Some(key.serialize(Serializer)?);
+                Ok(())
+            }
+            fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+                let key = self.key.take().unwrap();
+                self.entries.push((key, value.serialize(Serializer)?));
+                Ok(())
+            }
+            fn end(self) -> Result<Value, Error> {
+                Ok(Value::Map(self.entries))
+            }
+        }
+
+        impl ser::SerializeStruct for MapSerializer {
+            type Ok = Value;
+            type Error = Error;
+
+            fn serialize_field<T: ?Sized + Serialize>(
+                &mut self,
+                key: &'static str,
+                value: &T,
+            ) -> Result<(), Error> {
+                ser::SerializeMap::serialize_entry(self, key, value)
+            }
+            fn end(self) -> Result<Value, Error> {
+                ser::SerializeMap::end(self)
+            }
+        }
+
+        impl<'de> IntoDeserializer<'de, Error> for Value {
+            type Deserializer = Self;
+
+            fn into_deserializer(self) -> Self {
+                self
+            }
+        }
+
+        impl<'de> de::Deserializer<'de> for Value {
+            type Error = Error;
+
+            fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self {
+                    Value::Null => visitor.visit_unit(),
+                    Value::Bool(v) => visitor.visit_bool(v),
+                    Value::I64(v) => visitor.visit_i64(v),
+                    Value::U64(v) => visitor.visit_u64(v),
+                    Value::U128(v) => visitor.visit_u128(v),
+                    Value::F64(v) => visitor.visit_f64(v),
+                    Value::String(v) => visitor.visit_string(v),
+                    Value::Seq(v) => {
+                        visitor.visit_seq(de::value::SeqDeserializer::new(v.into_iter()))
+                    }
+                    Value::Map(v) => {
+                        visitor.visit_map(de::value::MapDeserializer::new(v.into_iter()))
+                    }
+                }
+            }
+
+            fn deserialize_option<V: de::Visitor<'de>>(
+                self,
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                match self {
+                    Value::Null => visitor.visit_none(),
+                    value => visitor.visit_some(value),
+                }
+            }
+
+            fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+                self,
+                _: &'static str,
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                visitor.visit_newtype_struct(self)
+            }
+
+            fn deserialize_enum<V: de::Visitor<'de>>(
+                self,
+                name: &'static str,
+                variants: &'static [&'static str],
+                visitor: V,
+            ) -> Result<V::Value, Error> {
+                match self {
+                    Value::String(variant) => IntoDeserializer::<Error>::into_deserializer(variant)
+                        .deserialize_enum(name, variants, visitor),
+                    value => value.deserialize_any(visitor),
+                }
+            }
+
+            forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+                bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+                identifier ignored_any
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn node_patch_serde_round_trip() {
+        use value::{Serializer, Value};
+
+        let mut patch = NodePatch::new();
+        patch.set_role(Role::SearchBox);
+        patch.add_action(Action::Focus);
+        patch.remove_action(Action::SetValue);
+        patch.set_required();
+        patch.clear_editable();
+        patch.set_name("Search");
+        patch.set_numeric_value(2.5);
+        patch.set_children(vec![NODE_ID_2]);
+        patch.clear_description();
+        let value = patch.serialize(Serializer).unwrap();
+        assert_eq!(Some(&Value::String("searchBox".into())), value.get("role"));
+        assert_eq!(
+            Some(&Value::Seq(vec![Value::String("focus".into())])),
+            value.get("addedActions")
+        );
+        assert_eq!(
+            Some(&Value::Seq(vec![Value::String("setValue".into())])),
+            value.get("removedActions")
+        );
+        assert_eq!(Some(&Value::Bool(true)), value.get("required"));
+        assert_eq!(Some(&Value::Bool(false)), value.get("editable"));
+        assert_eq!(Some(&Value::String("Search".into())), value.get("name"));
+        assert_eq!(Some(&Value::Null), value.get("description"));
+        assert_eq!(None, value.get("value"));
+        assert_eq!(patch, NodePatch::deserialize(value).unwrap());
+
+        let value = NodePatch::new().serialize(Serializer).unwrap();
+        assert_eq!(Value::Map(Vec::new()), value);
+        assert!(NodePatch::deserialize(value).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn node_patch_deserialization_ignores_unknown_keys() {
+        use value::Value;
+
+        let value = Value::Map(vec![
+            (Value::String("unknownKey".into()), Value::Bool(true)),
+            (Value::String("hidden".into()), Value::Bool(true)),
+            (Value::String("name".into()), Value::Null),
+            (
+                Value::String("anotherUnknownKey".into()),
+                Value::Seq(vec![Value::U64(1)]),
+            ),
+        ]);
+        let mut expected = NodePatch::new();
+        expected.set_hidden();
+        expected.clear_name();
+        assert_eq!(expected, NodePatch::deserialize(value).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialized_node_patch_applies() {
+        use value::Serializer;
+
+        let mut classes = NodeClassSet::new();
+        let node = node_with_every_property_kind(&mut classes);
+        let mut patch = NodePatch::new();
+        patch.remove_action(Action::Focus);
+        patch.clear_editable();
+        patch.set_hidden();
+        patch.clear_name();
+        patch.set_numeric_value(2.0);
+        let value = patch.serialize(Serializer).unwrap();
+        let patched = NodePatch::deserialize(value)
+            .unwrap()
+            .apply(&node, &mut classes);
+        assert_eq!(patch.apply(&node, &mut classes), patched);
+        assert!(!patched.supports_action(Action::Focus));
+        assert!(patched.supports_action(Action::SetValue));
+        assert!(!patched.is_editable());
+        assert!(patched.is_hidden());
+        assert!(patched.is_required());
+        assert_eq!(None, patched.name());
+        assert_eq!(Some(2.0), patched.numeric_value());
+        assert_eq!(node.children(), patched.children());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn key_shortcuts_serialize_as_aria_string() {
+        use value::{Serializer, Value};
+
+        let mut classes = NodeClassSet::new();
+        let node = node_with_every_property_kind(&mut classes);
+        let value = node.serialize(Serializer).unwrap();
+        assert_eq!(
+            Some(&Value::String("Control+n".into())),
+            value.get("keyShortcuts")
+        );
+        assert_eq!(node, Node::deserialize(value).unwrap());
+        let value = Value::Map(vec![
+            (Value::String("role".into()), Value::String("button".into())),
+            (
+                Value::String("keyShortcuts".into()),
+                Value::String("Control+".into()),
+            ),
+        ]);
+        assert!(Node::deserialize(value).is_err());
+    }
+}
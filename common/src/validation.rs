@@ -0,0 +1,607 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+use crate::{Node, NodeId, Role, TextPosition, TreeUpdate};
+
+/// A problem found by [`TreeUpdate::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// Neither the update nor the previous tree has [`TreeUpdate::tree`].
+    MissingTree,
+    /// The root node is neither in the update nor in the previous tree.
+    UnknownRoot(NodeId),
+    /// A node lists the same child more than once.
+    DuplicateChild { parent: NodeId, child: NodeId },
+    /// A node lists a child that is neither in the update nor in
+    /// the previous tree.
+    UnknownChild { parent: NodeId, child: NodeId },
+    /// More than one node lists the same child.
+    MultipleParents {
+        node: NodeId,
+        first_parent: NodeId,
+        second_parent: NodeId,
+    },
+    /// A node is its own ancestor.
+    Cycle(NodeId),
+    /// A node in the update isn't reachable from the root.
+    Unreachable(NodeId),
+    /// A relation property refers to a node that isn't in the tree.
+    UnknownRelationTarget {
+        node: NodeId,
+        relation: &'static str,
+        target: NodeId,
+    },
+    /// A text selection endpoint doesn't refer to an inline text box
+    /// in the tree.
+    TextPositionNotInlineTextBox {
+        node: NodeId,
+        position: TextPosition,
+    },
+    /// A text selection endpoint's character index is greater than
+    /// the number of characters in its inline text box.
+    TextPositionOutOfRange {
+        node: NodeId,
+        position: TextPosition,
+        character_count: usize,
+    },
+    /// The focused node isn't in the tree.
+    UnknownFocus(NodeId),
+    /// The root scroller isn't in the tree.
+    UnknownRootScroller(NodeId),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTree => write!(f, "no tree data was provided"),
+            Self::UnknownRoot(id) => write!(f, "root node {} doesn't exist", id.0),
+            Self::DuplicateChild { parent, child } => write!(
+                f,
+                "node {} lists child {} more than once",
+                parent.0, child.0
+            ),
+            Self::UnknownChild { parent, child } => write!(
+                f,
+                "node {} lists child {}, which doesn't exist",
+                parent.0, child.0
+            ),
+            Self::MultipleParents {
+                node,
+                first_parent,
+                second_parent,
+            } => write!(
+                f,
+                "node {} is listed as a child of both {} and {}",
+                node.0, first_parent.0, second_parent.0
+            ),
+            Self::Cycle(id) => write!(f, "node {} is its own ancestor", id.0),
+            Self::Unreachable(id) => {
+                write!(f, "node {} isn't reachable from the root", id.0)
+            }
+            Self::UnknownRelationTarget {
+                node,
+                relation,
+                target,
+            } => write!(
+                f,
+                "{} of node {} refers to node {}, which isn't in the tree",
+                relation, node.0, target.0
+            ),
+            Self::TextPositionNotInlineTextBox { node, position } => write!(
+                f,
+                "text selection of node {} refers to node {}, which isn't an inline text box in the tree",
+                node.0, position.node.0
+            ),
+            Self::TextPositionOutOfRange {
+                node,
+                position,
+                character_count,
+            } => write!(
+                f,
+                "text selection of node {} refers to character {} of node {}, which only has {} characters",
+                node.0, position.character_index, position.node.0, character_count
+            ),
+            Self::UnknownFocus(id) => {
+                write!(f, "focused node {} isn't in the tree", id.0)
+            }
+            Self::UnknownRootScroller(id) => {
+                write!(f, "root scroller {} isn't in the tree", id.0)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+fn relations(node: &Node) -> impl Iterator<Item = (&'static str, NodeId)> + '_ {
+    let vec_relations = [
+        ("indirect_children", node.indirect_children()),
+        ("controls", node.controls()),
+        ("details", node.details()),
+        ("described_by", node.described_by()),
+        ("flow_to", node.flow_to()),
+        ("labelled_by", node.labelled_by()),
+        ("radio_group", node.radio_group()),
+    ];
+    let single_relations = [
+        ("active_descendant", node.active_descendant()),
+        ("error_message", node.error_message()),
+        ("in_page_link_target", node.in_page_link_target()),
+        ("member_of", node.member_of()),
+        ("next_on_line", node.next_on_line()),
+        ("previous_on_line", node.previous_on_line()),
+        ("popup_for", node.popup_for()),
+        ("table_header", node.table_header()),
+        ("table_row_header", node.table_row_header()),
+        ("table_column_header", node.table_column_header()),
+        ("next_focus", node.next_focus()),
+        ("previous_focus", node.previous_focus()),
+    ];
+    vec_relations
+        .into_iter()
+        .flat_map(|(name, ids)| ids.iter().map(move |id| (name, *id)))
+        .chain(
+            single_relations
+                .into_iter()
+                .filter_map(|(name, id)| id.map(|id| (name, id))),
+        )
+}
+
+impl TreeUpdate {
+    /// Checks that applying this update would result in a well-formed tree,
+    /// reporting every problem found.
+    ///
+    /// `previous` is the complete state of the tree that this update will be
+    /// applied to, with every node in the tree, as in the initial update.
+    /// It should be `None` when validating an initial update.
+    ///
+    /// This is meant to catch mistakes on the provider side, before an update
+    /// is sent to a platform adapter, which would otherwise panic.
+    pub fn validate(&self, previous: Option<&TreeUpdate>) -> Result<(), Vec<ValidationError>> {
+        let mut nodes: HashMap<NodeId, &Node> = HashMap::new();
+        if let Some(previous) = previous {
+            nodes.extend(previous.nodes.iter().map(|(id, node)| (*id, node)));
+        }
+        nodes.extend(self.nodes.iter().map(|(id, node)| (*id, node)));
+
+        let tree = match self
+            .tree
+            .as_ref()
+            .or_else(|| previous.and_then(|previous| previous.tree.as_ref()))
+        {
+            Some(tree) => tree,
+            None => return Err(vec![ValidationError::MissingTree]),
+        };
+        if !nodes.contains_key(&tree.root) {
+            return Err(vec![ValidationError::UnknownRoot(tree.root)]);
+        }
+
+        let mut errors = Vec::new();
+        let mut parents: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut reachable = vec![tree.root];
+        let mut stack = vec![tree.root];
+        while let Some(parent) = stack.pop() {
+            let mut seen_child_ids = HashSet::new();
+            for child in nodes[&parent].children().iter() {
+                if !seen_child_ids.insert(*child) {
+                    errors.push(ValidationError::DuplicateChild {
+                        parent,
+                        child: *child,
+                    });
+                    continue;
+                }
+                if !nodes.contains_key(child) {
+                    errors.push(ValidationError::UnknownChild {
+                        parent,
+                        child: *child,
+                    });
+                    continue;
+                }
+                if *child == tree.root {
+                    errors.push(ValidationError::Cycle(*child));
+                    continue;
+                }
+                if let Some(first_parent) = parents.get(child) {
+                    let mut ancestor = Some(parent);
+                    while let Some(id) = ancestor {
+                        if id == *child {
+                            break;
+                        }
+                        ancestor = parents.get(&id).copied();
+                    }
+                    errors.push(if ancestor.is_some() {
+                        ValidationError::Cycle(*child)
+                    } else {
+                        ValidationError::MultipleParents {
+                            node: *child,
+                            first_parent: *first_parent,
+                            second_parent: parent,
+                        }
+                    });
+                    continue;
+                }
+                parents.insert(*child, parent);
+                reachable.push(*child);
+                stack.push(*child);
+            }
+        }
+        let is_reachable = |id: &NodeId| *id == tree.root || parents.contains_key(id);
+
+        for (id, _) in &self.nodes {
+            if !is_reachable(id) {
+                errors.push(ValidationError::Unreachable(*id));
+            }
+        }
+
+        // The walk from the root can't find cycles among nodes that aren't
+        // reachable from it, so look for those separately.
+        let mut finished = HashSet::new();
+        for (id, _) in &self.nodes {
+            if is_reachable(id) || finished.contains(id) {
+                continue;
+            }
+            let mut path = vec![(*id, 0)];
+            let mut on_path = HashSet::from([*id]);
+            while let Some((node, next_child_index)) = path.pop() {
+                let child = match nodes[&node].children().get(next_child_index) {
+                    Some(child) => *child,
+                    None => {
+                        on_path.remove(&node);
+                        finished.insert(node);
+                        continue;
+                    }
+                };
+                path.push((node, next_child_index + 1));
+                if !nodes.contains_key(&child) || is_reachable(&child) || finished.contains(&child)
+                {
+                    continue;
+                }
+                if !on_path.insert(child) {
+                    errors.push(ValidationError::Cycle(child));
+                    continue;
+                }
+                path.push((child, 0));
+            }
+        }
+
+        for id in &reachable {
+            let node = nodes[id];
+            for (relation, target) in relations(node) {
+                if !is_reachable(&target) {
+                    errors.push(ValidationError::UnknownRelationTarget {
+                        node: *id,
+                        relation,
+                        target,
+                    });
+                }
+            }
+            if let Some(selection) = node.text_selection() {
+                for position in [selection.anchor, selection.focus] {
+                    let text_box = nodes.get(&position.node).filter(|text_box| {
+                        is_reachable(&position.node) && text_box.role() == Role::InlineTextBox
+                    });
+                    match text_box {
+                        Some(text_box) => {
+                            let character_count = text_box.character_lengths().len();
+                            if position.character_index > character_count {
+                                errors.push(ValidationError::TextPositionOutOfRange {
+                                    node: *id,
+                                    position,
+                                    character_count,
+                                });
+                            }
+                        }
+                        None => errors.push(ValidationError::TextPositionNotInlineTextBox {
+                            node: *id,
+                            position,
+                        }),
+                    }
+                }
+            }
+        }
+
+        if let Some(id) = self.focus {
+            if !is_reachable(&id) {
+                errors.push(ValidationError::UnknownFocus(id));
+            }
+        }
+        if let Some(id) = tree.root_scroller {
+            if !is_reachable(&id) {
+                errors.push(ValidationError::UnknownRootScroller(id));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU128;
+
+    use crate::{
+        NodeBuilder, NodeClassSet, NodeId, Role, TextPosition, TextSelection, Tree, TreeUpdate,
+        ValidationError,
+    };
+
+    const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+    const NODE_ID_3: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const NODE_ID_4: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+
+    fn update(nodes: Vec<(NodeId, NodeBuilder)>) -> TreeUpdate {
+        let mut classes = NodeClassSet::new();
+        TreeUpdate {
+            nodes: nodes
+                .into_iter()
+                .map(|(id, builder)| (id, builder.build(&mut classes)))
+                .collect(),
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        }
+    }
+
+    fn node(role: Role, children: &[NodeId]) -> NodeBuilder {
+        let mut builder = NodeBuilder::new(role);
+        builder.set_children(children);
+        builder
+    }
+
+    fn text_field_with_selection(
+        anchor: TextPosition,
+        focus: TextPosition,
+    ) -> (NodeId, NodeBuilder) {
+        let mut builder = node(Role::TextField, &[NODE_ID_3]);
+        builder.set_text_selection(TextSelection { anchor, focus });
+        (NODE_ID_2, builder)
+    }
+
+    fn text_box() -> (NodeId, NodeBuilder) {
+        let mut builder = NodeBuilder::new(Role::InlineTextBox);
+        builder.set_value("ab");
+        builder.set_character_lengths([1, 1]);
+        (NODE_ID_3, builder)
+    }
+
+    #[test]
+    fn valid_initial_update() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            (NODE_ID_2, node(Role::Button, &[])),
+        ]);
+        assert_eq!(Ok(()), update.validate(None));
+    }
+
+    #[test]
+    fn valid_incremental_update() {
+        let previous = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            (NODE_ID_2, node(Role::Button, &[])),
+        ]);
+        let mut update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2, NODE_ID_3])),
+            (NODE_ID_3, node(Role::Button, &[])),
+        ]);
+        update.tree = None;
+        update.focus = Some(NODE_ID_2);
+        assert_eq!(Ok(()), update.validate(Some(&previous)));
+    }
+
+    #[test]
+    fn missing_tree() {
+        let mut update = update(vec![(NODE_ID_1, node(Role::Window, &[]))]);
+        update.tree = None;
+        assert_eq!(
+            Err(vec![ValidationError::MissingTree]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unknown_root() {
+        let update = update(vec![(NODE_ID_2, node(Role::Window, &[]))]);
+        assert_eq!(
+            Err(vec![ValidationError::UnknownRoot(NODE_ID_1)]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn duplicate_child() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2, NODE_ID_2])),
+            (NODE_ID_2, node(Role::Button, &[])),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::DuplicateChild {
+                parent: NODE_ID_1,
+                child: NODE_ID_2
+            }]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unknown_child() {
+        let update = update(vec![(NODE_ID_1, node(Role::Window, &[NODE_ID_2]))]);
+        assert_eq!(
+            Err(vec![ValidationError::UnknownChild {
+                parent: NODE_ID_1,
+                child: NODE_ID_2
+            }]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn multiple_parents() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2, NODE_ID_3])),
+            (NODE_ID_2, node(Role::Group, &[NODE_ID_4])),
+            (NODE_ID_3, node(Role::Group, &[NODE_ID_4])),
+            (NODE_ID_4, node(Role::Button, &[])),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::MultipleParents {
+                node: NODE_ID_4,
+                first_parent: NODE_ID_3,
+                second_parent: NODE_ID_2
+            }]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn cycle_through_root() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            (NODE_ID_2, node(Role::Group, &[NODE_ID_1])),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::Cycle(NODE_ID_1)]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn cycle_below_root() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            (NODE_ID_2, node(Role::Group, &[NODE_ID_3])),
+            (NODE_ID_3, node(Role::Group, &[NODE_ID_2])),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::Cycle(NODE_ID_2)]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unreachable_cycle() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[])),
+            (NODE_ID_2, node(Role::Group, &[NODE_ID_3])),
+            (NODE_ID_3, node(Role::Group, &[NODE_ID_4])),
+            (NODE_ID_4, node(Role::Group, &[NODE_ID_2])),
+        ]);
+        assert_eq!(
+            Err(vec![
+                ValidationError::Unreachable(NODE_ID_2),
+                ValidationError::Unreachable(NODE_ID_3),
+                ValidationError::Unreachable(NODE_ID_4),
+                ValidationError::Cycle(NODE_ID_2),
+            ]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unreachable() {
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[])),
+            (NODE_ID_2, node(Role::Button, &[])),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::Unreachable(NODE_ID_2)]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unknown_relation_target() {
+        let mut button = node(Role::Button, &[]);
+        button.push_labelled_by(NODE_ID_3);
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            (NODE_ID_2, button),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::UnknownRelationTarget {
+                node: NODE_ID_2,
+                relation: "labelled_by",
+                target: NODE_ID_3
+            }]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn text_position_not_inline_text_box() {
+        let position = TextPosition {
+            node: NODE_ID_2,
+            character_index: 0,
+        };
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            text_field_with_selection(position, position),
+            text_box(),
+        ]);
+        assert_eq!(
+            Err(vec![
+                ValidationError::TextPositionNotInlineTextBox {
+                    node: NODE_ID_2,
+                    position
+                };
+                2
+            ]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn text_position_out_of_range() {
+        let anchor = TextPosition {
+            node: NODE_ID_3,
+            character_index: 2,
+        };
+        let focus = TextPosition {
+            node: NODE_ID_3,
+            character_index: 3,
+        };
+        let update = update(vec![
+            (NODE_ID_1, node(Role::Window, &[NODE_ID_2])),
+            text_field_with_selection(anchor, focus),
+            text_box(),
+        ]);
+        assert_eq!(
+            Err(vec![ValidationError::TextPositionOutOfRange {
+                node: NODE_ID_2,
+                position: focus,
+                character_count: 2
+            }]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unknown_focus() {
+        let mut update = update(vec![(NODE_ID_1, node(Role::Window, &[]))]);
+        update.focus = Some(NODE_ID_2);
+        assert_eq!(
+            Err(vec![ValidationError::UnknownFocus(NODE_ID_2)]),
+            update.validate(None)
+        );
+    }
+
+    #[test]
+    fn unknown_root_scroller() {
+        let mut update = update(vec![(NODE_ID_1, node(Role::Window, &[]))]);
+        update.tree.as_mut().unwrap().root_scroller = Some(NODE_ID_2);
+        assert_eq!(
+            Err(vec![ValidationError::UnknownRootScroller(NODE_ID_2)]),
+            update.validate(None)
+        );
+    }
+}
@@ -4,7 +4,10 @@
 // the LICENSE-MIT file), at your option.
 
 pub(crate) mod tree;
-pub use tree::{ChangeHandler as TreeChangeHandler, State as TreeState, Tree};
+pub use tree::{
+    ChangeHandler as TreeChangeHandler, NodeChanges, State as TreeState, Tree,
+    UpdateError as TreeUpdateError,
+};
 
 pub(crate) mod node;
 pub use node::{DetachedNode, Node, NodeState};
@@ -12,6 +15,14 @@ pub use node::{DetachedNode, Node, NodeState};
 pub(crate) mod iterators;
 pub use iterators::FilterResult;
 
+pub(crate) mod name;
+
+pub(crate) mod relations;
+pub use relations::Relation;
+
+pub(crate) mod table;
+pub use table::{Table, TableCell};
+
 pub(crate) mod text;
 pub use text::{
     AttributeValue as TextAttributeValue, Position as TextPosition, Range as TextRange,
@@ -0,0 +1,606 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+// Implements the text alternative computation described in
+// https://www.w3.org/TR/accname-1.2/, adapted to the AccessKit schema.
+
+use accesskit::{DescriptionFrom, NameFrom, NodeId, Role};
+use std::collections::HashSet;
+
+use crate::{node::Node, tree::State as TreeState};
+
+#[derive(Clone, Copy, Default)]
+struct Traversal {
+    in_labelled_by: bool,
+    in_contents: bool,
+    include_hidden: bool,
+}
+
+struct NameComputation {
+    root: NodeId,
+    // Nodes on the current path, to guard against cycles introduced
+    // by relations.
+    path: HashSet<NodeId>,
+}
+
+fn allows_name_from_contents(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Button
+            | Role::Cell
+            | Role::CheckBox
+            | Role::ColumnHeader
+            | Role::DisclosureTriangle
+            | Role::DocBackLink
+            | Role::DocBiblioRef
+            | Role::DocGlossRef
+            | Role::DocNoteRef
+            | Role::Heading
+            | Role::LabelText
+            | Role::LayoutTableCell
+            | Role::Legend
+            | Role::Link
+            | Role::ListBoxOption
+            | Role::MenuItem
+            | Role::MenuItemCheckBox
+            | Role::MenuItemRadio
+            | Role::MenuListOption
+            | Role::PopupButton
+            | Role::RadioButton
+            | Role::Row
+            | Role::RowHeader
+            | Role::StaticText
+            | Role::Switch
+            | Role::Tab
+            | Role::ToggleButton
+            | Role::Tooltip
+            | Role::TreeItem
+    )
+}
+
+fn is_range(role: Role) -> bool {
+    matches!(
+        role,
+        Role::Meter | Role::ProgressIndicator | Role::ScrollBar | Role::Slider | Role::SpinButton
+    )
+}
+
+fn is_option(role: Role) -> bool {
+    matches!(role, Role::ListBoxOption | Role::MenuListOption)
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl NameComputation {
+    fn text_alternative(
+        &mut self,
+        node: &Node,
+        traversal: Traversal,
+    ) -> Option<(String, NameFrom)> {
+        let id = node.id();
+        if !self.path.insert(id) {
+            return None;
+        }
+        let result = self.text_alternative_for_new_node(node, traversal);
+        self.path.remove(&id);
+        result
+    }
+
+    fn text_alternative_for_new_node(
+        &mut self,
+        node: &Node,
+        traversal: Traversal,
+    ) -> Option<(String, NameFrom)> {
+        let data = node.data();
+        let is_root = node.id() == self.root;
+
+        // Step 2A: hidden nodes don't contribute, unless they were
+        // directly referenced by a relation.
+        if !is_root && node.is_hidden() && !traversal.include_hidden {
+            return None;
+        }
+
+        // Step 2B: labelled-by relations.
+        if !traversal.in_labelled_by && !data.labelled_by().is_empty() {
+            let names = data
+                .labelled_by()
+                .iter()
+                .filter_map(|id| node.tree_state.node_by_id(*id))
+                .filter_map(|label| {
+                    let traversal = Traversal {
+                        in_labelled_by: true,
+                        in_contents: false,
+                        include_hidden: label.is_hidden(),
+                    };
+                    self.text_alternative(&label, traversal)
+                        .map(|(name, _)| name)
+                })
+                .collect::<Vec<String>>();
+            if !names.is_empty() {
+                return Some((names.join(" "), NameFrom::RelatedElement));
+            }
+        }
+
+        // Step 2C: when recursing, embedded controls contribute their value
+        // rather than their name.
+        let is_recursion = traversal.in_labelled_by || traversal.in_contents;
+        if is_recursion && !is_root {
+            if let Some(value) = self.embedded_control_value(node) {
+                return (!value.is_empty()).then_some((value, NameFrom::Value));
+            }
+        }
+
+        // Step 2D: the name provided by the toolkit.
+        let name_from = data.name_from();
+        if let Some(name) = data.name() {
+            if !name.is_empty() {
+                return Some((name.into(), name_from.unwrap_or(NameFrom::Attribute)));
+            }
+        }
+        if is_root && name_from == Some(NameFrom::AttributeExplicitlyEmpty) {
+            return None;
+        }
+        if node.role() == Role::InlineTextBox {
+            return data
+                .value()
+                .filter(|value| !value.is_empty())
+                .map(|value| (value.into(), NameFrom::Contents));
+        }
+
+        // Step 2F: name from contents.
+        if is_recursion || allows_name_from_contents(node.role()) {
+            let traversal = Traversal {
+                in_contents: true,
+                ..traversal
+            };
+            let separator = if node.role() == Role::StaticText {
+                ""
+            } else {
+                " "
+            };
+            let contents = node
+                .children()
+                .filter_map(|child| self.text_alternative(&child, traversal))
+                .map(|(name, _)| name)
+                .collect::<Vec<String>>()
+                .join(separator);
+            let contents = normalize_whitespace(&contents);
+            if !contents.is_empty() {
+                return Some((contents, NameFrom::Contents));
+            }
+        }
+
+        // Step 2I: fall back to the tooltip, then the placeholder.
+        if let Some(tooltip) = data.tooltip().filter(|tooltip| !tooltip.is_empty()) {
+            return Some((tooltip.into(), NameFrom::Title));
+        }
+        if let Some(placeholder) = data
+            .placeholder()
+            .filter(|placeholder| !placeholder.is_empty())
+        {
+            return Some((placeholder.into(), NameFrom::Placeholder));
+        }
+
+        None
+    }
+
+    fn embedded_control_value(&mut self, node: &Node) -> Option<String> {
+        let role = node.role();
+        if node.is_text_field() {
+            return Some(node.value().unwrap_or_default().into());
+        }
+        if is_range(role) {
+            return Some(match (node.value(), node.numeric_value()) {
+                (Some(value), _) => value.into(),
+                (None, Some(value)) => value.to_string(),
+                (None, None) => String::new(),
+            });
+        }
+        if matches!(
+            role,
+            Role::ComboBoxGrouping
+                | Role::ComboBoxMenuButton
+                | Role::ListBox
+                | Role::TextFieldWithComboBox
+        ) {
+            if let Some(value) = node.value() {
+                return Some(value.into());
+            }
+            let mut selected = Vec::new();
+            self.collect_selected_options(node, &mut selected);
+            return Some(selected.join(" "));
+        }
+        None
+    }
+
+    fn collect_selected_options(&mut self, node: &Node, selected: &mut Vec<String>) {
+        for child in node.children() {
+            if child.is_hidden() {
+                continue;
+            }
+            if is_option(child.role()) && child.is_selected() == Some(true) {
+                let traversal = Traversal {
+                    in_contents: true,
+                    ..Default::default()
+                };
+                if let Some((name, _)) = self.text_alternative(&child, traversal) {
+                    selected.push(name);
+                }
+            } else {
+                self.collect_selected_options(&child, selected);
+            }
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    fn name_and_source(&self) -> Option<(String, NameFrom)> {
+        let mut computation = NameComputation {
+            root: self.id(),
+            path: HashSet::new(),
+        };
+        computation.text_alternative(self, Traversal::default())
+    }
+
+    /// The accessible name of this node, computed from the `name` property,
+    /// `labelled_by` relations, descendants, and other fallbacks
+    /// as described in the W3C accessible name computation specification.
+    pub fn name(&self) -> Option<String> {
+        self.name_and_source().map(|(name, _)| name)
+    }
+
+    /// Where the result of [`Node::name`] came from.
+    pub fn name_from(&self) -> Option<NameFrom> {
+        self.name_and_source().map(|(_, source)| source)
+    }
+
+    /// `name_from` is only called if the tooltip or placeholder
+    /// would be used, to avoid computing the name otherwise.
+    fn description_and_source(
+        &self,
+        name_from: impl FnOnce() -> Option<NameFrom>,
+    ) -> Option<(String, DescriptionFrom)> {
+        let data = self.data();
+        let mut computation = NameComputation {
+            root: self.id(),
+            path: HashSet::new(),
+        };
+        // A node can't contribute to its own description.
+        computation.path.insert(self.id());
+        let descriptions = data
+            .described_by()
+            .iter()
+            .filter_map(|id| self.tree_state.node_by_id(*id))
+            .filter_map(|node| {
+                let traversal = Traversal {
+                    in_labelled_by: true,
+                    in_contents: false,
+                    include_hidden: node.is_hidden(),
+                };
+                computation
+                    .text_alternative(&node, traversal)
+                    .map(|(description, _)| description)
+            })
+            .collect::<Vec<String>>();
+        if !descriptions.is_empty() {
+            return Some((descriptions.join(" "), DescriptionFrom::RelatedElement));
+        }
+
+        if let Some(description) = data.description().filter(|text| !text.is_empty()) {
+            return Some((
+                description.into(),
+                data.description_from()
+                    .unwrap_or(DescriptionFrom::AriaDescription),
+            ));
+        }
+
+        let tooltip = data.tooltip().filter(|text| !text.is_empty());
+        let placeholder = data.placeholder().filter(|text| !text.is_empty());
+        if tooltip.is_none() && placeholder.is_none() {
+            return None;
+        }
+        let name_from = name_from();
+        if let Some(tooltip) = tooltip {
+            if name_from != Some(NameFrom::Title) {
+                return Some((tooltip.into(), DescriptionFrom::Title));
+            }
+        }
+        if let Some(placeholder) = placeholder {
+            if name_from != Some(NameFrom::Placeholder) {
+                return Some((placeholder.into(), DescriptionFrom::Placeholder));
+            }
+        }
+
+        None
+    }
+
+    /// The accessible description of this node, computed from
+    /// `described_by` relations, then the `description` property,
+    /// then the tooltip or placeholder if they weren't used for the name.
+    pub fn description(&self) -> Option<String> {
+        self.description_and_source(|| self.name_from())
+            .map(|(description, _)| description)
+    }
+
+    /// Where the result of [`Node::description`] came from.
+    pub fn description_from(&self) -> Option<DescriptionFrom> {
+        self.description_and_source(|| self.name_from())
+            .map(|(_, source)| source)
+    }
+
+    /// Equivalent to calling [`Node::name`] and [`Node::description`],
+    /// but only runs the name computation once.
+    pub fn name_and_description(&self) -> (Option<String>, Option<String>) {
+        let (name, name_from) = match self.name_and_source() {
+            Some((name, source)) => (Some(name), Some(source)),
+            None => (None, None),
+        };
+        let description = self
+            .description_and_source(|| name_from)
+            .map(|(description, _)| description);
+        (name, description)
+    }
+}
+
+/// Returns the IDs of the nodes whose name or description may be derived
+/// from any of the given nodes, through their contents or through
+/// `labelled_by` or `described_by` relations, directly or transitively.
+/// The given nodes themselves are only included if they depend on
+/// each other.
+pub(crate) fn name_dependent_ids(
+    state: &TreeState,
+    ids: impl IntoIterator<Item = NodeId>,
+) -> HashSet<NodeId> {
+    let mut result = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut pending = ids.into_iter().collect::<Vec<_>>();
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let node = match state.node_by_id(id) {
+            Some(node) => node,
+            None => continue,
+        };
+        for referrer in node.labels_for().chain(node.descriptions_for()) {
+            result.insert(referrer.id());
+            pending.push(referrer.id());
+        }
+        // A parent with its own name uses neither this node's text nor
+        // its descendants', and neither do the parent's ancestors.
+        if let Some(parent) = node.parent() {
+            if !matches!(parent.data().name(), Some(name) if !name.is_empty()) {
+                if allows_name_from_contents(parent.role()) {
+                    result.insert(parent.id());
+                }
+                pending.push(parent.id());
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{
+        DescriptionFrom, NameFrom, NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate,
+    };
+    use std::num::NonZeroU128;
+
+    const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+    const NODE_ID_3: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const NODE_ID_4: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+    const NODE_ID_5: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(5) });
+    const NODE_ID_6: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(6) });
+
+    fn static_text(classes: &mut NodeClassSet, text: &str) -> accesskit::Node {
+        let mut builder = NodeBuilder::new(Role::StaticText);
+        builder.set_name(text);
+        builder.build(classes)
+    }
+
+    #[test]
+    fn name_from_contents() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_5]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Heading);
+                    builder.set_children(vec![NODE_ID_3, NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, static_text(&mut classes, "Chapter")),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::Emphasis);
+                    builder.push_child(NODE_ID_6);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_5, NodeBuilder::new(Role::List).build(&mut classes)),
+                (NODE_ID_6, static_text(&mut classes, "one")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let tree = crate::Tree::new(update);
+        let heading = tree.state().node_by_id(NODE_ID_2).unwrap();
+        assert_eq!(Some("Chapter one".into()), heading.name());
+        assert_eq!(Some(NameFrom::Contents), heading.name_from());
+        assert_eq!(None, tree.state().node_by_id(NODE_ID_5).unwrap().name());
+    }
+
+    #[test]
+    fn hidden_descendants_are_skipped() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.push_child(NODE_ID_2);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_children(vec![NODE_ID_3, NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, static_text(&mut classes, "Save")),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::StaticText);
+                    builder.set_name("(Ctrl+S)");
+                    builder.set_hidden();
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let tree = crate::Tree::new(update);
+        assert_eq!(
+            Some("Save".into()),
+            tree.state().node_by_id(NODE_ID_2).unwrap().name()
+        );
+    }
+
+    #[test]
+    fn embedded_control_value_in_label() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::CheckBox);
+                    builder.push_labelled_by(NODE_ID_3);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::LabelText);
+                    builder.set_children(vec![NODE_ID_4, NODE_ID_5, NODE_ID_6]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_4, static_text(&mut classes, "Check email every")),
+                (NODE_ID_5, {
+                    let mut builder = NodeBuilder::new(Role::SpinButton);
+                    builder.set_numeric_value(5.0);
+                    builder.push_labelled_by(NODE_ID_3);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_6, static_text(&mut classes, "minutes")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let tree = crate::Tree::new(update);
+        let check_box = tree.state().node_by_id(NODE_ID_2).unwrap();
+        assert_eq!(Some("Check email every 5 minutes".into()), check_box.name());
+        assert_eq!(Some(NameFrom::RelatedElement), check_box.name_from());
+        // The spin button is labelled by its own ancestor, so it must not
+        // include itself.
+        assert_eq!(
+            Some("Check email every minutes".into()),
+            tree.state().node_by_id(NODE_ID_5).unwrap().name()
+        );
+    }
+
+    #[test]
+    fn tooltip_and_placeholder_fallbacks() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Image);
+                    builder.set_tooltip("Logo");
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.set_placeholder("Search");
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let tree = crate::Tree::new(update);
+        let image = tree.state().node_by_id(NODE_ID_2).unwrap();
+        assert_eq!(Some("Logo".into()), image.name());
+        assert_eq!(Some(NameFrom::Title), image.name_from());
+        let text_field = tree.state().node_by_id(NODE_ID_3).unwrap();
+        assert_eq!(Some("Search".into()), text_field.name());
+        assert_eq!(Some(NameFrom::Placeholder), text_field.name_from());
+    }
+
+    #[test]
+    fn description_sources() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3, NODE_ID_4, NODE_ID_5]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.set_name("Password");
+                    builder.push_described_by(NODE_ID_3);
+                    builder.set_tooltip("Ignored");
+                    builder.build(&mut classes)
+                }),
+                (
+                    NODE_ID_3,
+                    static_text(&mut classes, "At least 8 characters"),
+                ),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_name("Save");
+                    builder.set_tooltip("Save the document");
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_5, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.set_placeholder("Search");
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let tree = crate::Tree::new(update);
+        let text_field = tree.state().node_by_id(NODE_ID_2).unwrap();
+        assert_eq!(
+            Some("At least 8 characters".into()),
+            text_field.description()
+        );
+        assert_eq!(
+            Some(DescriptionFrom::RelatedElement),
+            text_field.description_from()
+        );
+        let button = tree.state().node_by_id(NODE_ID_4).unwrap();
+        assert_eq!(Some("Save the document".into()), button.description());
+        assert_eq!(Some(DescriptionFrom::Title), button.description_from());
+        // The placeholder is already used as the name.
+        assert_eq!(
+            None,
+            tree.state().node_by_id(NODE_ID_5).unwrap().description()
+        );
+    }
+}
@@ -11,8 +11,8 @@
 use std::{iter::FusedIterator, ops::Deref};
 
 use accesskit::{
-    Action, Affine, CheckedState, DefaultActionVerb, Live, Node as NodeData, NodeId, Point, Rect,
-    Role, TextSelection,
+    Action, Affine, AriaCurrent, CheckedState, CustomAction, DefaultActionVerb, HasPopup,
+    KeyShortcuts, Live, Node as NodeData, NodeId, Point, Rect, Role, SortDirection, TextSelection,
 };
 
 use crate::iterators::{
@@ -45,13 +45,16 @@ impl NodeState {
 
 impl<'a> Node<'a> {
     pub fn detached(&self) -> DetachedNode {
+        let (name, description) = self.name_and_description();
         DetachedNode {
             state: self.state.clone(),
             is_focused: self.is_focused(),
             is_root: self.is_root(),
-            name: self.name(),
+            name,
+            description,
             live: self.live(),
             supports_text_ranges: self.supports_text_ranges(),
+            filtered_parent_id: self.parent_id(),
         }
     }
 
@@ -360,6 +363,10 @@ impl NodeState {
         self.data().is_disabled()
     }
 
+    pub fn is_editable(&self) -> bool {
+        self.data().is_editable()
+    }
+
     pub fn is_read_only(&self) -> bool {
         let data = self.data();
         if data.is_read_only() {
@@ -383,6 +390,10 @@ impl NodeState {
         self.data().value()
     }
 
+    pub fn url(&self) -> Option<&str> {
+        self.data().url()
+    }
+
     pub fn numeric_value(&self) -> Option<f64> {
         self.data().numeric_value()
     }
@@ -498,6 +509,46 @@ impl NodeState {
     pub fn supports_decrement(&self) -> bool {
         self.supports_action(Action::Decrement)
     }
+
+    pub fn supports_expand(&self) -> bool {
+        self.supports_action(Action::Expand)
+    }
+
+    pub fn supports_collapse(&self) -> bool {
+        self.supports_action(Action::Collapse)
+    }
+
+    pub fn supports_show_context_menu(&self) -> bool {
+        self.supports_action(Action::ShowContextMenu)
+    }
+
+    pub fn supports_scroll_into_view(&self) -> bool {
+        self.supports_action(Action::ScrollIntoView)
+    }
+
+    pub fn supports_set_value(&self) -> bool {
+        self.supports_action(Action::SetValue)
+    }
+
+    pub fn supports_replace_selected_text(&self) -> bool {
+        self.supports_action(Action::ReplaceSelectedText)
+    }
+
+    pub fn supports_copy(&self) -> bool {
+        self.supports_action(Action::Copy)
+    }
+
+    pub fn supports_cut(&self) -> bool {
+        self.supports_action(Action::Cut)
+    }
+
+    pub fn supports_paste(&self) -> bool {
+        self.supports_action(Action::Paste)
+    }
+
+    pub fn custom_actions(&self) -> &[CustomAction] {
+        self.data().custom_actions()
+    }
 }
 
 fn descendant_label_filter(node: &Node) -> FilterResult {
@@ -522,18 +573,6 @@ impl<'a> Node<'a> {
             }
         }
     }
-
-    pub fn name(&self) -> Option<String> {
-        if let Some(name) = &self.data().name() {
-            Some(name.to_string())
-        } else {
-            let names = self
-                .labelled_by()
-                .filter_map(|node| node.name())
-                .collect::<Vec<String>>();
-            (!names.is_empty()).then(move || names.join(" "))
-        }
-    }
 }
 
 impl NodeState {
@@ -603,9 +642,123 @@ impl NodeState {
         self.data().is_selected()
     }
 
+    pub fn is_multiselectable(&self) -> bool {
+        self.data().is_multiselectable()
+    }
+
+    pub fn table_row_count(&self) -> Option<usize> {
+        self.data().table_row_count()
+    }
+
+    pub fn table_column_count(&self) -> Option<usize> {
+        self.data().table_column_count()
+    }
+
+    pub fn table_row_index(&self) -> Option<usize> {
+        self.data().table_row_index()
+    }
+
+    pub fn table_cell_row_index(&self) -> Option<usize> {
+        self.data().table_cell_row_index()
+    }
+
+    pub fn table_cell_column_index(&self) -> Option<usize> {
+        self.data().table_cell_column_index()
+    }
+
+    pub fn table_cell_row_span(&self) -> Option<usize> {
+        self.data().table_cell_row_span()
+    }
+
+    pub fn table_cell_column_span(&self) -> Option<usize> {
+        self.data().table_cell_column_span()
+    }
+
+    pub fn table_column_index(&self) -> Option<usize> {
+        self.data().table_column_index()
+    }
+
+    pub fn table_header(&self) -> Option<NodeId> {
+        self.data().table_header()
+    }
+
+    pub fn table_row_header(&self) -> Option<NodeId> {
+        self.data().table_row_header()
+    }
+
+    pub fn table_column_header(&self) -> Option<NodeId> {
+        self.data().table_column_header()
+    }
+
     pub fn raw_text_selection(&self) -> Option<&TextSelection> {
         self.data().text_selection()
     }
+
+    pub fn hierarchical_level(&self) -> Option<usize> {
+        self.data().hierarchical_level()
+    }
+
+    pub fn size_of_set(&self) -> Option<usize> {
+        self.data().size_of_set()
+    }
+
+    pub fn position_in_set(&self) -> Option<usize> {
+        self.data().position_in_set()
+    }
+
+    /// The live setting of this node itself, without inheriting
+    /// from its ancestors as [`Node::live`] does.
+    pub fn explicit_live(&self) -> Option<Live> {
+        self.data().live()
+    }
+
+    pub fn is_live_atomic(&self) -> bool {
+        self.data().is_live_atomic()
+    }
+
+    pub fn live_relevant(&self) -> Option<&str> {
+        self.data().live_relevant()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.data().is_busy()
+    }
+
+    pub fn has_popup(&self) -> Option<HasPopup> {
+        self.data().has_popup()
+    }
+
+    pub fn role_description(&self) -> Option<&str> {
+        self.data().role_description()
+    }
+
+    pub fn placeholder(&self) -> Option<&str> {
+        self.data().placeholder()
+    }
+
+    pub fn key_shortcuts(&self) -> Option<&KeyShortcuts> {
+        self.data().key_shortcuts()
+    }
+
+    pub fn access_key(&self) -> Option<&str> {
+        self.data().access_key()
+    }
+
+    pub fn aria_current(&self) -> Option<AriaCurrent> {
+        self.data().aria_current()
+    }
+
+    pub fn sort_direction(&self) -> Option<SortDirection> {
+        self.data().sort_direction()
+    }
+
+    pub fn class_name(&self) -> Option<&str> {
+        self.data().class_name()
+    }
+
+    pub fn aria_role(&self) -> Option<&str> {
+        self.data().aria_role()
+    }
 }
 
 impl<'a> Node<'a> {
@@ -680,8 +833,10 @@ pub struct DetachedNode {
     pub(crate) is_focused: bool,
     pub(crate) is_root: bool,
     pub(crate) name: Option<String>,
+    pub(crate) description: Option<String>,
     pub(crate) live: Live,
     pub(crate) supports_text_ranges: bool,
+    pub(crate) filtered_parent_id: Option<NodeId>,
 }
 
 impl DetachedNode {
@@ -693,10 +848,26 @@ impl DetachedNode {
         self.is_root
     }
 
+    /// The name computed while the node was still attached, before any
+    /// part of the update that detached it was applied.
+    ///
+    /// For a node passed to [`ChangeHandler::node_removed`], this is only
+    /// computed if [`ChangeHandler::filter_detached`] includes the node.
+    /// Otherwise, it is always `None`.
+    ///
+    /// [`ChangeHandler::node_removed`]: crate::TreeChangeHandler::node_removed
+    /// [`ChangeHandler::filter_detached`]: crate::TreeChangeHandler::filter_detached
     pub fn name(&self) -> Option<String> {
         self.name.clone()
     }
 
+    /// The description computed while the node was still attached.
+    /// Like [`DetachedNode::name`], this is only computed for a removed
+    /// node if the handler's filter includes it.
+    pub fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
     pub fn live(&self) -> Live {
         self.live
     }
@@ -705,6 +876,17 @@ impl DetachedNode {
         self.supports_text_ranges
     }
 
+    /// For a node passed to [`ChangeHandler::node_removed`], the ID of
+    /// the nearest ancestor that [`ChangeHandler::filter_detached`]
+    /// includes, as of before the node was removed. For any other
+    /// detached node, this is the same as [`NodeState::parent_id`].
+    ///
+    /// [`ChangeHandler::node_removed`]: crate::TreeChangeHandler::node_removed
+    /// [`ChangeHandler::filter_detached`]: crate::TreeChangeHandler::filter_detached
+    pub fn filtered_parent_id(&self) -> Option<NodeId> {
+        self.filtered_parent_id
+    }
+
     pub fn state(&self) -> &NodeState {
         &self.state
     }
@@ -0,0 +1,303 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{Node as NodeData, NodeDiff, NodeId};
+use std::{
+    collections::{HashMap, HashSet},
+    iter::FusedIterator,
+};
+
+use crate::node::Node;
+
+/// A relation from one node to one or more other nodes, as expressed
+/// by a property of the source node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Relation {
+    LabelledBy,
+    Controls,
+    DescribedBy,
+    FlowTo,
+    Details,
+    ErrorMessage,
+    MemberOf,
+    PopupFor,
+    RadioGroup,
+}
+
+impl Relation {
+    pub(crate) const ALL: [Self; 9] = [
+        Self::LabelledBy,
+        Self::Controls,
+        Self::DescribedBy,
+        Self::FlowTo,
+        Self::Details,
+        Self::ErrorMessage,
+        Self::MemberOf,
+        Self::PopupFor,
+        Self::RadioGroup,
+    ];
+
+    /// Returns true if the property that expresses this relation differs
+    /// between the two versions of a node.
+    pub fn is_changed(self, diff: &NodeDiff) -> bool {
+        match self {
+            Self::LabelledBy => diff.labelled_by(),
+            Self::Controls => diff.controls(),
+            Self::DescribedBy => diff.described_by(),
+            Self::FlowTo => diff.flow_to(),
+            Self::Details => diff.details(),
+            Self::ErrorMessage => diff.error_message(),
+            Self::MemberOf => diff.member_of(),
+            Self::PopupFor => diff.popup_for(),
+            Self::RadioGroup => diff.radio_group(),
+        }
+    }
+
+    fn targets(self, data: &NodeData) -> Vec<NodeId> {
+        match self {
+            Self::LabelledBy => data.labelled_by().to_vec(),
+            Self::Controls => data.controls().to_vec(),
+            Self::DescribedBy => data.described_by().to_vec(),
+            Self::FlowTo => data.flow_to().to_vec(),
+            Self::Details => data.details().to_vec(),
+            Self::ErrorMessage => data.error_message().into_iter().collect(),
+            Self::MemberOf => data.member_of().into_iter().collect(),
+            Self::PopupFor => data.popup_for().into_iter().collect(),
+            Self::RadioGroup => data.radio_group().to_vec(),
+        }
+    }
+}
+
+/// For each target node and relation, the nodes that have that relation
+/// to the target.
+#[derive(Clone, Default)]
+pub(crate) struct ReverseRelations(HashMap<(NodeId, Relation), Vec<NodeId>>);
+
+impl ReverseRelations {
+    /// Updates the index to reflect a change in the data of the node
+    /// with the given ID, adding the IDs of any nodes whose reverse
+    /// relations changed to `changed`.
+    pub(crate) fn update_node(
+        &mut self,
+        id: NodeId,
+        old_data: Option<&NodeData>,
+        new_data: Option<&NodeData>,
+        changed: &mut HashSet<NodeId>,
+    ) {
+        for relation in Relation::ALL {
+            let old_targets = old_data.map_or_else(Vec::new, |data| relation.targets(data));
+            let new_targets = new_data.map_or_else(Vec::new, |data| relation.targets(data));
+            if old_targets == new_targets {
+                continue;
+            }
+            // Only the targets that were added or removed have a different
+            // set of sources, even if the order of the targets changed.
+            for target in &old_targets {
+                if new_targets.contains(target) {
+                    continue;
+                }
+                if let Some(sources) = self.0.get_mut(&(*target, relation)) {
+                    sources.retain(|source| *source != id);
+                    if sources.is_empty() {
+                        self.0.remove(&(*target, relation));
+                    }
+                    changed.insert(*target);
+                }
+            }
+            for target in &new_targets {
+                if old_targets.contains(target) {
+                    continue;
+                }
+                let sources = self.0.entry((*target, relation)).or_default();
+                if !sources.contains(&id) {
+                    sources.push(id);
+                    changed.insert(*target);
+                }
+            }
+        }
+    }
+
+    fn sources(&self, target: NodeId, relation: Relation) -> &[NodeId] {
+        self.0
+            .get(&(target, relation))
+            .map_or(&[], |sources| &sources[..])
+    }
+}
+
+impl<'a> Node<'a> {
+    /// The nodes to which this node has the given relation.
+    pub fn relations(
+        &self,
+        relation: Relation,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        let tree_state = self.tree_state;
+        relation
+            .targets(self.state.data())
+            .into_iter()
+            .filter_map(move |id| tree_state.node_by_id(id))
+    }
+
+    /// The nodes that have the given relation to this node.
+    pub fn reverse_relations(
+        &self,
+        relation: Relation,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        let tree_state = self.tree_state;
+        tree_state
+            .reverse_relations
+            .sources(self.id(), relation)
+            .iter()
+            .filter_map(move |id| tree_state.node_by_id(*id))
+    }
+
+    /// The nodes that are labelled by this node.
+    pub fn labels_for(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::LabelledBy)
+    }
+
+    /// The nodes that control this node.
+    pub fn controlled_by(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::Controls)
+    }
+
+    /// The nodes that are described by this node.
+    pub fn descriptions_for(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::DescribedBy)
+    }
+
+    /// The nodes whose reading order flows to this node.
+    pub fn flows_from(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::FlowTo)
+    }
+
+    /// The nodes for which this node provides details.
+    pub fn details_for(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::Details)
+    }
+
+    /// The nodes for which this node is the error message.
+    pub fn error_message_for(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Node<'a>> + FusedIterator<Item = Node<'a>> + 'a {
+        self.reverse_relations(Relation::ErrorMessage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate};
+    use std::num::NonZeroU128;
+
+    use crate::Relation;
+
+    const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+    const NODE_ID_3: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const NODE_ID_4: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+
+    fn ids<'a>(nodes: impl Iterator<Item = crate::Node<'a>>) -> Vec<NodeId> {
+        nodes.map(|node| node.id()).collect()
+    }
+
+    #[test]
+    fn reverse_relations_are_updated() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3, NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.push_labelled_by(NODE_ID_3);
+                    builder.set_error_message(NODE_ID_4);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::StaticText);
+                    builder.set_name("Email");
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::StaticText);
+                    builder.set_name("Invalid address");
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = crate::Tree::new(update);
+        let state = tree.state();
+        let label = state.node_by_id(NODE_ID_3).unwrap();
+        assert_eq!(vec![NODE_ID_2], ids(label.labels_for()));
+        assert!(label.error_message_for().next().is_none());
+        let field = state.node_by_id(NODE_ID_2).unwrap();
+        assert_eq!(vec![NODE_ID_3], ids(field.relations(Relation::LabelledBy)));
+        assert_eq!(
+            vec![NODE_ID_4],
+            ids(field.relations(Relation::ErrorMessage))
+        );
+        assert!(field.relations(Relation::Controls).next().is_none());
+        let error = state.node_by_id(NODE_ID_4).unwrap();
+        assert_eq!(vec![NODE_ID_2], ids(error.error_message_for()));
+        assert_eq!(
+            vec![NODE_ID_2],
+            ids(error.reverse_relations(Relation::ErrorMessage))
+        );
+
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_2, {
+                let mut builder = NodeBuilder::new(Role::TextField);
+                builder.push_labelled_by(NODE_ID_3);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: None,
+        };
+        tree.update(update);
+        let state = tree.state();
+        assert_eq!(
+            vec![NODE_ID_2],
+            ids(state.node_by_id(NODE_ID_3).unwrap().labels_for())
+        );
+        assert!(state
+            .node_by_id(NODE_ID_4)
+            .unwrap()
+            .error_message_for()
+            .next()
+            .is_none());
+
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_1, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NODE_ID_3, NODE_ID_4]);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: None,
+        };
+        tree.update(update);
+        assert!(tree
+            .state()
+            .node_by_id(NODE_ID_3)
+            .unwrap()
+            .labels_for()
+            .next()
+            .is_none());
+    }
+}
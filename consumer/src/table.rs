@@ -0,0 +1,722 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use accesskit::{NodeId, Role};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use crate::node::{Node, NodeState};
+
+struct CellInfo {
+    id: NodeId,
+    row_index: usize,
+    column_index: usize,
+    row_span: usize,
+    column_span: usize,
+}
+
+pub(crate) struct Grid {
+    rows: HashMap<usize, NodeId>,
+    columns: HashMap<usize, NodeId>,
+    cells: Vec<CellInfo>,
+    cell_indices: HashMap<NodeId, usize>,
+    slots: HashMap<(usize, usize), usize>,
+    row_count: usize,
+    column_count: usize,
+}
+
+impl Grid {
+    fn new(table: Node) -> Self {
+        let mut grid = Self {
+            rows: HashMap::new(),
+            columns: HashMap::new(),
+            cells: Vec::new(),
+            cell_indices: HashMap::new(),
+            slots: HashMap::new(),
+            row_count: 0,
+            column_count: 0,
+        };
+        let mut row_nodes = Vec::new();
+        collect_rows(table, &mut row_nodes);
+        let mut next_row_index = 0;
+        for row in row_nodes {
+            let row_index = row.table_row_index().unwrap_or(next_row_index);
+            next_row_index = row_index + 1;
+            grid.rows.insert(row_index, row.id());
+            grid.row_count = grid.row_count.max(next_row_index);
+            let mut cell_nodes = Vec::new();
+            collect_cells(row, &mut cell_nodes);
+            let mut next_column_index = 0;
+            for cell in cell_nodes {
+                let cell_row_index = cell.table_cell_row_index().unwrap_or(row_index);
+                let row_span = cell.table_cell_row_span().unwrap_or(1).max(1);
+                let column_span = cell.table_cell_column_span().unwrap_or(1).max(1);
+                let column_index = match cell.table_cell_column_index() {
+                    Some(index) => index,
+                    None => {
+                        // Skip slots that are covered by cells spanning
+                        // from previous rows.
+                        let mut index = next_column_index;
+                        while grid.slots.contains_key(&(cell_row_index, index)) {
+                            index += 1;
+                        }
+                        index
+                    }
+                };
+                next_column_index = column_index + column_span;
+                let cell_index = grid.cells.len();
+                grid.cells.push(CellInfo {
+                    id: cell.id(),
+                    row_index: cell_row_index,
+                    column_index,
+                    row_span,
+                    column_span,
+                });
+                grid.cell_indices.insert(cell.id(), cell_index);
+                for row in cell_row_index..(cell_row_index + row_span) {
+                    for column in column_index..(column_index + column_span) {
+                        grid.slots.entry((row, column)).or_insert(cell_index);
+                    }
+                }
+                grid.row_count = grid.row_count.max(cell_row_index + row_span);
+                grid.column_count = grid.column_count.max(column_index + column_span);
+            }
+        }
+        let mut next_column_index = 0;
+        for column in table
+            .children()
+            .filter(|child| child.role() == Role::Column && !child.is_hidden())
+        {
+            let column_index = column.table_column_index().unwrap_or(next_column_index);
+            next_column_index = column_index + 1;
+            grid.columns.insert(column_index, column.id());
+        }
+        grid.row_count = grid.row_count.max(table.table_row_count().unwrap_or(0));
+        grid.column_count = grid
+            .column_count
+            .max(table.table_column_count().unwrap_or(0));
+        grid
+    }
+}
+
+/// The grids of the tables in a tree, built on first use and discarded
+/// whenever the tree is updated.
+#[derive(Default)]
+pub(crate) struct GridCache(Mutex<HashMap<NodeId, Arc<Grid>>>);
+
+impl GridCache {
+    fn get_or_build(&self, table: Node) -> Arc<Grid> {
+        let mut grids = self.0.lock().unwrap();
+        Arc::clone(
+            grids
+                .entry(table.id())
+                .or_insert_with(|| Arc::new(Grid::new(table))),
+        )
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.0.get_mut().unwrap().clear();
+    }
+}
+
+impl Clone for GridCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+fn collect_rows<'a>(node: Node<'a>, rows: &mut Vec<Node<'a>>) {
+    for child in node.children() {
+        if child.is_hidden() {
+            continue;
+        }
+        match child.role() {
+            Role::Row => rows.push(child),
+            Role::RowGroup | Role::LayoutTableRow | Role::GenericContainer => {
+                collect_rows(child, rows)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_cells<'a>(node: Node<'a>, cells: &mut Vec<Node<'a>>) {
+    for child in node.children() {
+        if child.is_hidden() {
+            continue;
+        }
+        if child.is_table_cell() {
+            cells.push(child);
+        } else if child.role() == Role::GenericContainer {
+            collect_cells(child, cells);
+        }
+    }
+}
+
+/// A view of a table, grid or tree grid as a grid of cells, with
+/// implicit row and column indices resolved and each spanning cell
+/// occupying every slot that it covers.
+///
+/// The grid is built the first time a table is viewed and is reused
+/// until the tree is updated.
+#[derive(Clone)]
+pub struct Table<'a> {
+    node: Node<'a>,
+    grid: Arc<Grid>,
+}
+
+impl<'a> Table<'a> {
+    fn new(node: Node<'a>) -> Self {
+        Self {
+            node,
+            grid: node.tree_state.table_grids.get_or_build(node),
+        }
+    }
+
+    fn node_by_id(&self, id: NodeId) -> Node<'a> {
+        self.node.tree_state.node_by_id(id).unwrap()
+    }
+
+    fn cell(&self, index: usize) -> TableCell<'a> {
+        TableCell {
+            table: self.clone(),
+            index,
+        }
+    }
+
+    pub fn node(&self) -> Node<'a> {
+        self.node
+    }
+
+    /// The number of rows, which is at least
+    /// [`accesskit::Node::table_row_count`] if that is set.
+    pub fn row_count(&self) -> usize {
+        self.grid.row_count
+    }
+
+    /// The number of columns, which is at least
+    /// [`accesskit::Node::table_column_count`] if that is set.
+    pub fn column_count(&self) -> usize {
+        self.grid.column_count
+    }
+
+    /// The row node at the given row index, if any.
+    pub fn row(&self, row_index: usize) -> Option<Node<'a>> {
+        self.grid
+            .rows
+            .get(&row_index)
+            .map(|id| self.node_by_id(*id))
+    }
+
+    /// The column node at the given column index, if the table has
+    /// children with the [`Role::Column`] role.
+    pub fn column(&self, column_index: usize) -> Option<Node<'a>> {
+        self.grid
+            .columns
+            .get(&column_index)
+            .map(|id| self.node_by_id(*id))
+    }
+
+    /// The cell that covers the given slot, which may start in
+    /// a previous row or column if it spans multiple slots.
+    pub fn cell_at(&self, row_index: usize, column_index: usize) -> Option<TableCell<'a>> {
+        self.grid
+            .slots
+            .get(&(row_index, column_index))
+            .map(|index| self.cell(*index))
+    }
+
+    /// The cells that cover a slot in the given row, in column order.
+    pub fn cells_in_row(&self, row_index: usize) -> Vec<TableCell<'a>> {
+        self.distinct_cells((0..self.column_count()).map(|column_index| (row_index, column_index)))
+    }
+
+    /// The cells that cover a slot in the given column, in row order.
+    pub fn cells_in_column(&self, column_index: usize) -> Vec<TableCell<'a>> {
+        self.distinct_cells((0..self.row_count()).map(|row_index| (row_index, column_index)))
+    }
+
+    pub fn caption(&self) -> Option<Node<'a>> {
+        self.node
+            .children()
+            .find(|child| child.role() == Role::Caption)
+    }
+
+    /// The node that contains the table's column headers, from
+    /// [`accesskit::Node::table_header`].
+    pub fn header(&self) -> Option<Node<'a>> {
+        self.node
+            .table_header()
+            .and_then(|id| self.node.tree_state.node_by_id(id))
+    }
+
+    fn distinct_cells(&self, slots: impl Iterator<Item = (usize, usize)>) -> Vec<TableCell<'a>> {
+        let mut seen = HashSet::new();
+        slots
+            .filter_map(|slot| self.grid.slots.get(&slot))
+            .filter(|index| seen.insert(**index))
+            .map(|index| self.cell(*index))
+            .collect()
+    }
+
+    fn headers(
+        &self,
+        related: Option<Node<'a>>,
+        cells: Vec<TableCell<'a>>,
+        role: Role,
+    ) -> Vec<Node<'a>> {
+        let mut headers = related.into_iter().collect::<Vec<_>>();
+        for cell in cells {
+            let node = cell.node();
+            if node.role() == role && !headers.iter().any(|header| header.id() == node.id()) {
+                headers.push(node);
+            }
+        }
+        headers
+    }
+
+    /// The row header cells that cover a slot in the given row, starting
+    /// with the row's [`accesskit::Node::table_row_header`] if it is set.
+    pub fn row_headers(&self, row_index: usize) -> Vec<Node<'a>> {
+        let related = self
+            .row(row_index)
+            .and_then(|row| row.table_row_header())
+            .and_then(|id| self.node.tree_state.node_by_id(id));
+        self.headers(related, self.cells_in_row(row_index), Role::RowHeader)
+    }
+
+    /// The column header cells that cover a slot in the given column,
+    /// starting with the column's [`accesskit::Node::table_column_header`]
+    /// if it is set.
+    pub fn column_headers(&self, column_index: usize) -> Vec<Node<'a>> {
+        let related = self
+            .column(column_index)
+            .and_then(|column| column.table_column_header())
+            .and_then(|id| self.node.tree_state.node_by_id(id));
+        self.headers(
+            related,
+            self.cells_in_column(column_index),
+            Role::ColumnHeader,
+        )
+    }
+
+    pub fn is_row_selected(&self, row_index: usize) -> bool {
+        self.row(row_index)
+            .and_then(|row| row.is_selected())
+            .unwrap_or(false)
+    }
+
+    /// Returns true if every cell in the column is selected.
+    pub fn is_column_selected(&self, column_index: usize) -> bool {
+        let cells = self.cells_in_column(column_index);
+        !cells.is_empty()
+            && cells
+                .iter()
+                .all(|cell| cell.node().is_selected() == Some(true))
+    }
+}
+
+/// A cell of a [`Table`], with its resolved position in the grid.
+#[derive(Clone)]
+pub struct TableCell<'a> {
+    table: Table<'a>,
+    index: usize,
+}
+
+impl<'a> TableCell<'a> {
+    fn info(&self) -> &CellInfo {
+        &self.table.grid.cells[self.index]
+    }
+
+    pub fn node(&self) -> Node<'a> {
+        self.table.node_by_id(self.info().id)
+    }
+
+    pub fn table(&self) -> &Table<'a> {
+        &self.table
+    }
+
+    /// The index of the first row that the cell covers.
+    pub fn row_index(&self) -> usize {
+        self.info().row_index
+    }
+
+    /// The index of the first column that the cell covers.
+    pub fn column_index(&self) -> usize {
+        self.info().column_index
+    }
+
+    pub fn row_span(&self) -> usize {
+        self.info().row_span
+    }
+
+    pub fn column_span(&self) -> usize {
+        self.info().column_span
+    }
+
+    fn other_headers(&self, headers: impl Iterator<Item = Node<'a>>) -> Vec<Node<'a>> {
+        let id = self.info().id;
+        let mut seen = HashSet::new();
+        headers
+            .filter(|header| header.id() != id && seen.insert(header.id()))
+            .collect()
+    }
+
+    /// The row header cells for the rows that this cell covers,
+    /// not including this cell.
+    pub fn row_header_cells(&self) -> Vec<Node<'a>> {
+        let rows = self.row_index()..(self.row_index() + self.row_span());
+        self.other_headers(rows.flat_map(|row_index| self.table.row_headers(row_index)))
+    }
+
+    /// The column header cells for the columns that this cell covers,
+    /// not including this cell.
+    pub fn column_header_cells(&self) -> Vec<Node<'a>> {
+        let columns = self.column_index()..(self.column_index() + self.column_span());
+        self.other_headers(columns.flat_map(|column_index| self.table.column_headers(column_index)))
+    }
+
+    /// Returns true if the cell, or any row that it covers, is selected.
+    pub fn is_selected(&self) -> bool {
+        self.node().is_selected() == Some(true)
+            || (self.row_index()..(self.row_index() + self.row_span()))
+                .any(|row_index| self.table.is_row_selected(row_index))
+    }
+}
+
+impl NodeState {
+    pub fn is_table(&self) -> bool {
+        matches!(
+            self.role(),
+            Role::Table | Role::Grid | Role::TreeGrid | Role::ListGrid
+        )
+    }
+
+    pub fn is_table_cell(&self) -> bool {
+        matches!(
+            self.role(),
+            Role::Cell | Role::ColumnHeader | Role::RowHeader
+        )
+    }
+}
+
+impl<'a> Node<'a> {
+    /// Returns a grid view of this node if it is a table.
+    pub fn as_table(&self) -> Option<Table<'a>> {
+        self.is_table().then(|| Table::new(*self))
+    }
+
+    /// Returns this node's position in the nearest ancestor table,
+    /// if this node is a cell of that table.
+    pub fn as_table_cell(&self) -> Option<TableCell<'a>> {
+        if !self.is_table_cell() {
+            return None;
+        }
+        let mut ancestor = self.parent();
+        while let Some(node) = ancestor {
+            if node.is_table() {
+                let table = Table::new(node);
+                let index = *table.grid.cell_indices.get(&self.id())?;
+                return Some(table.cell(index));
+            }
+            ancestor = node.parent();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate};
+    use std::num::NonZeroU128;
+
+    const TABLE_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const CAPTION_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+    const HEADER_GROUP_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const HEADER_ROW_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+    const NAME_HEADER_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(5) });
+    const DETAILS_HEADER_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(6) });
+    const BODY_GROUP_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(7) });
+    const ROW_1_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(8) });
+    const ALICE_HEADER_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(9) });
+    const CELL_1_1_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(10) });
+    const CELL_1_2_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(11) });
+    const ROW_2_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(12) });
+    const CELL_2_1_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(13) });
+    const CELL_2_2_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(14) });
+    const LAYOUT_ROW_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(15) });
+    const ROW_3_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(16) });
+    const CELL_3_2_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(17) });
+    const COLUMN_ID: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(18) });
+
+    // | Name      | Details             |
+    // | Alice     | cell 1,1 | cell 1,2 |
+    // | (spanned) | cell 2,1 | cell 2,2 |
+    // |           |          | cell 3,2 |
+    fn table_tree() -> crate::Tree {
+        let mut classes = NodeClassSet::new();
+        let node = |role: Role, children: Vec<NodeId>| {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_children(children);
+            builder
+        };
+        let table = {
+            let mut builder = node(
+                Role::Table,
+                vec![CAPTION_ID, HEADER_GROUP_ID, BODY_GROUP_ID],
+            );
+            builder.set_table_row_count(5);
+            builder.build(&mut classes)
+        };
+        let details_header = {
+            let mut builder = node(Role::ColumnHeader, vec![]);
+            builder.set_table_cell_column_span(2);
+            builder.build(&mut classes)
+        };
+        let alice_header = {
+            let mut builder = node(Role::RowHeader, vec![]);
+            builder.set_table_cell_row_span(2);
+            builder.build(&mut classes)
+        };
+        let cell_3_2 = {
+            let mut builder = node(Role::Cell, vec![]);
+            builder.set_table_cell_column_index(2);
+            builder.build(&mut classes)
+        };
+        let update = TreeUpdate {
+            nodes: vec![
+                (TABLE_ID, table),
+                (CAPTION_ID, node(Role::Caption, vec![]).build(&mut classes)),
+                (
+                    HEADER_GROUP_ID,
+                    node(Role::RowGroup, vec![HEADER_ROW_ID]).build(&mut classes),
+                ),
+                (
+                    HEADER_ROW_ID,
+                    node(Role::Row, vec![NAME_HEADER_ID, DETAILS_HEADER_ID]).build(&mut classes),
+                ),
+                (
+                    NAME_HEADER_ID,
+                    node(Role::ColumnHeader, vec![]).build(&mut classes),
+                ),
+                (DETAILS_HEADER_ID, details_header),
+                (
+                    BODY_GROUP_ID,
+                    node(
+                        Role::GenericContainer,
+                        vec![ROW_1_ID, ROW_2_ID, LAYOUT_ROW_ID],
+                    )
+                    .build(&mut classes),
+                ),
+                (
+                    ROW_1_ID,
+                    node(Role::Row, vec![ALICE_HEADER_ID, CELL_1_1_ID, CELL_1_2_ID])
+                        .build(&mut classes),
+                ),
+                (ALICE_HEADER_ID, alice_header),
+                (CELL_1_1_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (CELL_1_2_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (
+                    ROW_2_ID,
+                    node(Role::Row, vec![CELL_2_1_ID, CELL_2_2_ID]).build(&mut classes),
+                ),
+                (CELL_2_1_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (CELL_2_2_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (
+                    LAYOUT_ROW_ID,
+                    node(Role::LayoutTableRow, vec![ROW_3_ID]).build(&mut classes),
+                ),
+                (
+                    ROW_3_ID,
+                    node(Role::Row, vec![CELL_3_2_ID]).build(&mut classes),
+                ),
+                (CELL_3_2_ID, cell_3_2),
+            ],
+            tree: Some(Tree::new(TABLE_ID)),
+            focus: None,
+        };
+        crate::Tree::new(update)
+    }
+
+    #[test]
+    fn cell_grid() {
+        let tree = table_tree();
+        let table = tree.state().root().as_table().unwrap();
+        assert_eq!(5, table.row_count());
+        assert_eq!(3, table.column_count());
+        let expected = [
+            [
+                Some(NAME_HEADER_ID),
+                Some(DETAILS_HEADER_ID),
+                Some(DETAILS_HEADER_ID),
+            ],
+            [Some(ALICE_HEADER_ID), Some(CELL_1_1_ID), Some(CELL_1_2_ID)],
+            [Some(ALICE_HEADER_ID), Some(CELL_2_1_ID), Some(CELL_2_2_ID)],
+            [None, None, Some(CELL_3_2_ID)],
+            [None, None, None],
+        ];
+        for (row_index, row) in expected.iter().enumerate() {
+            for (column_index, id) in row.iter().enumerate() {
+                assert_eq!(
+                    *id,
+                    table
+                        .cell_at(row_index, column_index)
+                        .map(|cell| cell.node().id())
+                );
+            }
+        }
+        assert_eq!(Some(ROW_3_ID), table.row(3).map(|row| row.id()));
+        assert_eq!(
+            Some(CAPTION_ID),
+            table.caption().map(|caption| caption.id())
+        );
+    }
+
+    #[test]
+    fn cell_positions() {
+        let tree = table_tree();
+        let state = tree.state();
+        let cell = |id| state.node_by_id(id).unwrap().as_table_cell().unwrap();
+        let details = cell(DETAILS_HEADER_ID);
+        assert_eq!((0, 1), (details.row_index(), details.column_index()));
+        assert_eq!((1, 2), (details.row_span(), details.column_span()));
+        let alice = cell(ALICE_HEADER_ID);
+        assert_eq!((1, 0), (alice.row_index(), alice.column_index()));
+        assert_eq!((2, 1), (alice.row_span(), alice.column_span()));
+        let cell_2_1 = cell(CELL_2_1_ID);
+        assert_eq!((2, 1), (cell_2_1.row_index(), cell_2_1.column_index()));
+        let cell_3_2 = cell(CELL_3_2_ID);
+        assert_eq!((3, 2), (cell_3_2.row_index(), cell_3_2.column_index()));
+        assert_eq!(TABLE_ID, cell_3_2.table().node().id());
+        assert!(state
+            .node_by_id(ROW_1_ID)
+            .unwrap()
+            .as_table_cell()
+            .is_none());
+        assert!(state.node_by_id(ROW_1_ID).unwrap().as_table().is_none());
+    }
+
+    #[test]
+    fn headers() {
+        let tree = table_tree();
+        let state = tree.state();
+        let table = state.root().as_table().unwrap();
+        let ids = |nodes: Vec<crate::Node>| nodes.iter().map(|node| node.id()).collect::<Vec<_>>();
+        assert_eq!(vec![NAME_HEADER_ID], ids(table.column_headers(0)));
+        assert_eq!(vec![DETAILS_HEADER_ID], ids(table.column_headers(2)));
+        assert_eq!(vec![ALICE_HEADER_ID], ids(table.row_headers(2)));
+        assert!(table.row_headers(3).is_empty());
+        let cell = |id| state.node_by_id(id).unwrap().as_table_cell().unwrap();
+        let cell_2_2 = cell(CELL_2_2_ID);
+        assert_eq!(vec![ALICE_HEADER_ID], ids(cell_2_2.row_header_cells()));
+        assert_eq!(vec![DETAILS_HEADER_ID], ids(cell_2_2.column_header_cells()));
+        let alice = cell(ALICE_HEADER_ID);
+        assert!(alice.row_header_cells().is_empty());
+        assert_eq!(vec![NAME_HEADER_ID], ids(alice.column_header_cells()));
+    }
+
+    // | Name label | (column 1) |
+    // | (row 0)    | Bob        |
+    fn related_headers_tree() -> crate::Tree {
+        let mut classes = NodeClassSet::new();
+        let node = |role: Role, children: Vec<NodeId>| {
+            let mut builder = NodeBuilder::new(role);
+            builder.set_children(children);
+            builder
+        };
+        let table = {
+            let mut builder = node(Role::Grid, vec![HEADER_GROUP_ID, ROW_1_ID, COLUMN_ID]);
+            builder.set_table_header(HEADER_GROUP_ID);
+            builder.build(&mut classes)
+        };
+        let column = {
+            let mut builder = node(Role::Column, vec![]);
+            builder.set_table_column_index(1);
+            builder.set_table_column_header(NAME_HEADER_ID);
+            builder.build(&mut classes)
+        };
+        let row = {
+            let mut builder = node(Role::Row, vec![CELL_1_1_ID, CELL_1_2_ID]);
+            builder.set_table_row_header(ALICE_HEADER_ID);
+            builder.build(&mut classes)
+        };
+        let update = TreeUpdate {
+            nodes: vec![
+                (TABLE_ID, table),
+                (
+                    HEADER_GROUP_ID,
+                    node(
+                        Role::GenericContainer,
+                        vec![NAME_HEADER_ID, ALICE_HEADER_ID],
+                    )
+                    .build(&mut classes),
+                ),
+                (
+                    NAME_HEADER_ID,
+                    node(Role::StaticText, vec![]).build(&mut classes),
+                ),
+                (
+                    ALICE_HEADER_ID,
+                    node(Role::StaticText, vec![]).build(&mut classes),
+                ),
+                (ROW_1_ID, row),
+                (CELL_1_1_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (CELL_1_2_ID, node(Role::Cell, vec![]).build(&mut classes)),
+                (COLUMN_ID, column),
+            ],
+            tree: Some(Tree::new(TABLE_ID)),
+            focus: None,
+        };
+        crate::Tree::new(update)
+    }
+
+    #[test]
+    fn related_headers() {
+        let tree = related_headers_tree();
+        let state = tree.state();
+        let table = state.root().as_table().unwrap();
+        let ids = |nodes: Vec<crate::Node>| nodes.iter().map(|node| node.id()).collect::<Vec<_>>();
+        assert_eq!(Some(HEADER_GROUP_ID), table.header().map(|node| node.id()));
+        assert_eq!(Some(COLUMN_ID), table.column(1).map(|node| node.id()));
+        assert!(table.column(0).is_none());
+        assert_eq!(vec![NAME_HEADER_ID], ids(table.column_headers(1)));
+        assert!(table.column_headers(0).is_empty());
+        assert_eq!(vec![ALICE_HEADER_ID], ids(table.row_headers(0)));
+        let cell = state
+            .node_by_id(CELL_1_2_ID)
+            .unwrap()
+            .as_table_cell()
+            .unwrap();
+        assert_eq!(vec![ALICE_HEADER_ID], ids(cell.row_header_cells()));
+        assert_eq!(vec![NAME_HEADER_ID], ids(cell.column_header_cells()));
+    }
+
+    #[test]
+    fn grid_is_rebuilt_after_update() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = table_tree();
+        assert_eq!(3, tree.state().root().as_table().unwrap().column_count());
+        let cell_3_2 = {
+            let mut builder = NodeBuilder::new(Role::Cell);
+            builder.set_table_cell_column_index(4);
+            builder.build(&mut classes)
+        };
+        tree.update(TreeUpdate {
+            nodes: vec![(CELL_3_2_ID, cell_3_2)],
+            tree: None,
+            focus: None,
+        });
+        let state = tree.state();
+        assert_eq!(5, state.root().as_table().unwrap().column_count());
+        let cell = state
+            .node_by_id(CELL_3_2_ID)
+            .unwrap()
+            .as_table_cell()
+            .unwrap();
+        assert_eq!((3, 4), (cell.row_index(), cell.column_index()));
+    }
+}
@@ -234,7 +234,7 @@ impl<'a> Position<'a> {
         Range::new(self.root_node, self.inner, self.inner)
     }
 
-    pub fn to_global_utf16_index(&self) -> usize {
+    fn global_index(&self, char_len: impl Fn(char) -> usize) -> usize {
         let mut total_length = 0usize;
         for node in self.root_node.inline_text_boxes() {
             let node_text = node.value().unwrap();
@@ -245,17 +245,23 @@ impl<'a> Position<'a> {
                     .copied()
                     .map(usize::from)
                     .sum::<usize>();
-                return total_length
-                    + node_text[..slice_end]
-                        .chars()
-                        .map(char::len_utf16)
-                        .sum::<usize>();
+                return total_length + node_text[..slice_end].chars().map(&char_len).sum::<usize>();
             }
-            total_length += node_text.chars().map(char::len_utf16).sum::<usize>();
+            total_length += node_text.chars().map(&char_len).sum::<usize>();
         }
         panic!("invalid position")
     }
 
+    pub fn to_global_utf16_index(&self) -> usize {
+        self.global_index(char::len_utf16)
+    }
+
+    /// Returns the index of this position in the document's text,
+    /// counted in Unicode scalar values (code points).
+    pub fn to_global_usv_index(&self) -> usize {
+        self.global_index(|_| 1)
+    }
+
     pub fn to_line_index(&self) -> usize {
         let mut pos = *self;
         if !pos.is_line_start() {
@@ -980,24 +986,28 @@ impl<'a> Node<'a> {
         Some(Range::new(*self, pos.inner, end.inner))
     }
 
-    pub fn text_position_from_global_utf16_index(&self, index: usize) -> Option<Position> {
+    fn text_position_from_global_index(
+        &self,
+        index: usize,
+        char_len: impl Fn(char) -> usize,
+    ) -> Option<Position<'_>> {
         let mut total_length = 0usize;
         for node in self.inline_text_boxes() {
             let node_text = node.value().unwrap();
-            let node_text_length = node_text.chars().map(char::len_utf16).sum::<usize>();
+            let node_text_length = node_text.chars().map(&char_len).sum::<usize>();
             let new_total_length = total_length + node_text_length;
             if index >= total_length && index < new_total_length {
                 let index = index - total_length;
                 let mut utf8_length = 0usize;
-                let mut utf16_length = 0usize;
+                let mut length = 0usize;
                 for (character_index, utf8_char_length) in
                     node.data().character_lengths().iter().enumerate()
                 {
                     let new_utf8_length = utf8_length + (*utf8_char_length as usize);
                     let char_str = &node_text[utf8_length..new_utf8_length];
-                    let utf16_char_length = char_str.chars().map(char::len_utf16).sum::<usize>();
-                    let new_utf16_length = utf16_length + utf16_char_length;
-                    if index >= utf16_length && index < new_utf16_length {
+                    let char_length = char_str.chars().map(&char_len).sum::<usize>();
+                    let new_length = length + char_length;
+                    if index >= length && index < new_length {
                         return Some(Position {
                             root_node: *self,
                             inner: InnerPosition {
@@ -1007,7 +1017,7 @@ impl<'a> Node<'a> {
                         });
                     }
                     utf8_length = new_utf8_length;
-                    utf16_length = new_utf16_length;
+                    length = new_length;
                 }
                 panic!("index out of range");
             }
@@ -1021,6 +1031,46 @@ impl<'a> Node<'a> {
         }
         None
     }
+
+    pub fn text_position_from_global_utf16_index(&self, index: usize) -> Option<Position<'_>> {
+        self.text_position_from_global_index(index, char::len_utf16)
+    }
+
+    /// Returns the position at the given index in the document's text,
+    /// counted in Unicode scalar values (code points). An index that falls
+    /// inside a multi-code-point character refers to the start of that
+    /// character.
+    pub fn text_position_from_global_usv_index(&self, index: usize) -> Option<Position<'_>> {
+        self.text_position_from_global_index(index, |_| 1)
+    }
+
+    /// Returns the range of this node's text that comes from the inline
+    /// text boxes of the given descendant, or `None` if the descendant
+    /// doesn't contribute any text to this node.
+    pub fn text_range_for_descendant(&self, descendant: &Node) -> Option<Range<'_>> {
+        if !descendant.is_descendant_of(self) || descendant.id() == self.id() {
+            return None;
+        }
+        let (first, last) = if descendant.role() == Role::InlineTextBox {
+            let node = self.tree_state.node_by_id(descendant.id()).unwrap();
+            (node, node)
+        } else {
+            let mut boxes = descendant
+                .inline_text_boxes()
+                .map(|node| self.tree_state.node_by_id(node.id()).unwrap());
+            let first = boxes.next()?;
+            (first, boxes.next_back().unwrap_or(first))
+        };
+        let start = InnerPosition {
+            node: first,
+            character_index: 0,
+        };
+        let end = InnerPosition {
+            node: last,
+            character_index: last.data().character_lengths().len(),
+        };
+        Some(Range::new(*self, start, end))
+    }
 }
 
 #[cfg(test)]
@@ -1284,6 +1334,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn text_range_for_descendant() {
+        let tree = main_multiline_tree(None);
+        let state = tree.state();
+        let node = state.node_by_id(NODE_ID_2).unwrap();
+        let text_box = state.node_by_id(NODE_ID_4).unwrap();
+        let range = node.text_range_for_descendant(&text_box).unwrap();
+        assert_eq!(range.text(), "to another line.\n");
+        assert_eq!(range.start().to_global_usv_index(), 38);
+        assert_eq!(range.end().to_global_usv_index(), 55);
+        let root = state.node_by_id(NODE_ID_1).unwrap();
+        assert!(node.text_range_for_descendant(&root).is_none());
+        assert!(node.text_range_for_descendant(&node).is_none());
+    }
+
     #[test]
     fn supports_text_ranges() {
         let tree = main_multiline_tree(None);
@@ -1692,6 +1757,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn to_global_usv_index() {
+        let tree = main_multiline_tree(None);
+        let state = tree.state();
+        let node = state.node_by_id(NODE_ID_2).unwrap();
+
+        {
+            let range = node.document_range();
+            assert_eq!(range.start().to_global_usv_index(), 0);
+            assert_eq!(range.end().to_global_usv_index(), 96);
+        }
+
+        {
+            let range = node.document_range();
+            let pos = range.start().forward_to_line_start();
+            assert_eq!(pos.to_global_usv_index(), 38);
+            let pos = pos.forward_to_line_start().forward_to_line_start();
+            let pos = pos.forward_to_line_start();
+            assert_eq!(pos.to_global_usv_index(), 75);
+            let pos = pos.forward_to_line_end();
+            assert_eq!(pos.to_global_usv_index(), 96);
+            assert_eq!(pos.to_global_utf16_index(), 97);
+        }
+    }
+
     #[test]
     fn to_line_index() {
         let tree = main_multiline_tree(None);
@@ -1842,4 +1932,44 @@ mod tests {
 
         assert!(node.text_position_from_global_utf16_index(98).is_none());
     }
+
+    #[test]
+    fn text_position_from_global_usv_index() {
+        let tree = main_multiline_tree(None);
+        let state = tree.state();
+        let node = state.node_by_id(NODE_ID_2).unwrap();
+
+        {
+            let pos = node.text_position_from_global_usv_index(0).unwrap();
+            assert!(pos.is_document_start());
+        }
+
+        {
+            let pos = node.text_position_from_global_usv_index(17).unwrap();
+            let mut range = pos.to_degenerate_range();
+            range.set_end(pos.forward_to_character_end());
+            assert_eq!(range.text(), "\u{a0}");
+        }
+
+        {
+            let pos = node.text_position_from_global_usv_index(94).unwrap();
+            let mut range = pos.to_degenerate_range();
+            range.set_end(pos.forward_to_character_end());
+            assert_eq!(range.text(), "\u{1f60a}");
+        }
+
+        {
+            let pos = node.text_position_from_global_usv_index(95).unwrap();
+            let mut range = pos.to_degenerate_range();
+            range.set_end(pos.forward_to_character_end());
+            assert_eq!(range.text(), "\n");
+        }
+
+        {
+            let pos = node.text_position_from_global_usv_index(96).unwrap();
+            assert!(pos.is_document_end());
+        }
+
+        assert!(node.text_position_from_global_usv_index(97).is_none());
+    }
 }
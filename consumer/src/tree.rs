@@ -3,16 +3,103 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::{Live, Node as NodeData, NodeId, Tree as TreeData, TreeUpdate};
-use std::collections::{HashMap, HashSet};
+use accesskit::{
+    Node as NodeData, NodeClassSet, NodeDiff, NodeId, NodePatch, Role, Tree as TreeData, TreeUpdate,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+};
 
+use crate::iterators::FilterResult;
+use crate::name::name_dependent_ids;
 use crate::node::{DetachedNode, Node, NodeState, ParentAndIndex};
+use crate::relations::ReverseRelations;
+use crate::table::GridCache;
+
+/// The reason a [`TreeUpdate`] couldn't be applied to a tree.
+///
+/// When an update is rejected, the tree is left in the state it was in
+/// before the update.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateError {
+    /// The initial update didn't include the tree data.
+    MissingTree,
+    /// The root node is neither in the tree nor in the update.
+    UnknownRoot(NodeId),
+    /// A node lists the same child more than once.
+    DuplicateChild { parent: NodeId, child: NodeId },
+    /// A node lists a child that is neither in the tree nor in the update.
+    UnknownChild { parent: NodeId, child: NodeId },
+    /// More than one node claims the same child.
+    ReparentedTwice {
+        node: NodeId,
+        first_parent: NodeId,
+        second_parent: NodeId,
+    },
+    /// A node in the update can't be reached from the root.
+    OrphanedNode(NodeId),
+    /// A node would be its own ancestor.
+    Cycle(NodeId),
+    /// The focused node isn't in the resulting tree.
+    UnknownFocus(NodeId),
+    /// The root scroller isn't in the resulting tree.
+    UnreachableRootScroller(NodeId),
+    /// A patch targets a node that isn't in the tree.
+    UnknownPatchTarget(NodeId),
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTree => write!(f, "the initial update has no tree data"),
+            Self::UnknownRoot(id) => write!(f, "root node {} doesn't exist", id.0),
+            Self::DuplicateChild { parent, child } => write!(
+                f,
+                "node {} lists child {} more than once",
+                parent.0, child.0
+            ),
+            Self::UnknownChild { parent, child } => write!(
+                f,
+                "node {} lists child {}, which doesn't exist",
+                parent.0, child.0
+            ),
+            Self::ReparentedTwice {
+                node,
+                first_parent,
+                second_parent,
+            } => write!(
+                f,
+                "node {} is claimed as a child by both {} and {}",
+                node.0, first_parent.0, second_parent.0
+            ),
+            Self::OrphanedNode(id) => {
+                write!(f, "node {} isn't reachable from the root", id.0)
+            }
+            Self::Cycle(id) => write!(f, "node {} is its own ancestor", id.0),
+            Self::UnknownFocus(id) => {
+                write!(f, "focused node {} isn't in the tree", id.0)
+            }
+            Self::UnreachableRootScroller(id) => {
+                write!(f, "root scroller {} isn't in the tree", id.0)
+            }
+            Self::UnknownPatchTarget(id) => {
+                write!(f, "patched node {} isn't in the tree", id.0)
+            }
+        }
+    }
+}
+
+impl Error for UpdateError {}
 
 #[derive(Clone)]
 pub struct State {
     pub(crate) nodes: HashMap<NodeId, NodeState>,
     pub(crate) data: TreeData,
     pub(crate) focus: Option<NodeId>,
+    pub(crate) reverse_relations: ReverseRelations,
+    pub(crate) table_grids: GridCache,
 }
 
 struct InternalFocusChange {
@@ -26,6 +113,10 @@ struct InternalChanges {
     updated_nodes: HashMap<NodeId, DetachedNode>,
     focus_change: Option<InternalFocusChange>,
     removed_nodes: HashMap<NodeId, DetachedNode>,
+    /// The IDs of the removed nodes, with each node preceding
+    /// its descendants.
+    removed_node_ids: Vec<NodeId>,
+    reverse_relations_changed: HashSet<NodeId>,
 }
 
 impl State {
@@ -39,30 +130,289 @@ impl State {
         }
     }
 
-    fn update(&mut self, update: TreeUpdate, mut changes: Option<&mut InternalChanges>) {
+    /// Checks that applying the update would result in a well-formed tree,
+    /// without modifying the current state.
+    fn validate_update(&self, update: &TreeUpdate) -> Result<(), UpdateError> {
+        let tree = update.tree.as_ref().unwrap_or(&self.data);
+        let root = tree.root;
+        let updated_nodes: HashMap<NodeId, &NodeData> =
+            update.nodes.iter().map(|(id, data)| (*id, data)).collect();
+        let exists = |id: &NodeId| updated_nodes.contains_key(id) || self.nodes.contains_key(id);
+
+        if !exists(&root) {
+            return Err(UpdateError::UnknownRoot(root));
+        }
+
+        let mut new_parents: HashMap<NodeId, NodeId> = HashMap::new();
+        for (parent, data) in &update.nodes {
+            let mut seen_child_ids = HashSet::new();
+            for child in data.children().iter() {
+                if !seen_child_ids.insert(*child) {
+                    return Err(UpdateError::DuplicateChild {
+                        parent: *parent,
+                        child: *child,
+                    });
+                }
+                if !exists(child) {
+                    return Err(UpdateError::UnknownChild {
+                        parent: *parent,
+                        child: *child,
+                    });
+                }
+                if let Some(first_parent) = new_parents.insert(*child, *parent) {
+                    if first_parent != *parent {
+                        return Err(UpdateError::ReparentedTwice {
+                            node: *child,
+                            first_parent,
+                            second_parent: *parent,
+                        });
+                    }
+                }
+            }
+        }
+
+        // The parent of a node once the update is applied, if any.
+        let parent_of = |id: NodeId| -> Option<NodeId> {
+            if let Some(parent) = new_parents.get(&id) {
+                return Some(*parent);
+            }
+            match self.nodes.get(&id)?.parent_and_index {
+                Some(ParentAndIndex(parent, _)) if !updated_nodes.contains_key(&parent) => {
+                    Some(parent)
+                }
+                _ => None,
+            }
+        };
+        let mut reachable: HashMap<NodeId, bool> = HashMap::new();
+        let mut is_reachable = |id: NodeId| -> Result<bool, UpdateError> {
+            let mut path = Vec::new();
+            let mut current = id;
+            let result = loop {
+                if current == root {
+                    break true;
+                }
+                if let Some(result) = reachable.get(&current) {
+                    break *result;
+                }
+                if path.contains(&current) {
+                    return Err(UpdateError::Cycle(current));
+                }
+                path.push(current);
+                match parent_of(current) {
+                    Some(parent) => current = parent,
+                    None => break false,
+                }
+            };
+            for id in path {
+                reachable.insert(id, result);
+            }
+            Ok(result)
+        };
+
+        if let Some(parent) = new_parents.get(&root) {
+            if is_reachable(*parent)? {
+                return Err(UpdateError::Cycle(root));
+            }
+        }
+        for (id, _) in &update.nodes {
+            if !is_reachable(*id)? {
+                return Err(UpdateError::OrphanedNode(*id));
+            }
+        }
+        for (parent, data) in &update.nodes {
+            for child in data.children().iter() {
+                let old_parent = match self.nodes.get(child).and_then(|n| n.parent_and_index) {
+                    Some(ParentAndIndex(old_parent, _)) => old_parent,
+                    None => continue,
+                };
+                if old_parent != *parent
+                    && !updated_nodes.contains_key(&old_parent)
+                    && is_reachable(old_parent)?
+                {
+                    return Err(UpdateError::ReparentedTwice {
+                        node: *child,
+                        first_parent: old_parent,
+                        second_parent: *parent,
+                    });
+                }
+            }
+        }
+        if let Some(id) = update.focus {
+            if !exists(&id) || !is_reachable(id)? {
+                return Err(UpdateError::UnknownFocus(id));
+            }
+        }
+        if let Some(id) = tree.root_scroller {
+            if !exists(&id) || !is_reachable(id)? {
+                return Err(UpdateError::UnreachableRootScroller(id));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_update(
+        &mut self,
+        update: TreeUpdate,
+        changes: Option<(&mut InternalChanges, &dyn ChangeHandler)>,
+    ) -> Result<(), UpdateError> {
+        self.validate_update(&update)?;
+        self.update(update, changes);
+        Ok(())
+    }
+
+    /// Converts patches into an update that replaces the patched nodes,
+    /// without changing the focus.
+    fn update_from_patches(
+        &self,
+        patches: Vec<(NodeId, NodePatch)>,
+        classes: &mut NodeClassSet,
+    ) -> Result<TreeUpdate, UpdateError> {
+        let mut nodes: Vec<(NodeId, NodeData)> = Vec::new();
+        let mut indices = HashMap::new();
+        for (id, patch) in patches {
+            // Multiple patches to the same node are applied in order.
+            if let Some(index) = indices.get(&id) {
+                let entry: &mut (NodeId, NodeData) = &mut nodes[*index];
+                entry.1 = patch.apply(&entry.1, classes);
+                continue;
+            }
+            let old_data = match self.nodes.get(&id) {
+                Some(old_state) => &old_state.data,
+                None => return Err(UpdateError::UnknownPatchTarget(id)),
+            };
+            indices.insert(id, nodes.len());
+            nodes.push((id, patch.apply(old_data, classes)));
+        }
+        Ok(TreeUpdate {
+            nodes,
+            tree: None,
+            focus: self.focus,
+        })
+    }
+
+    /// The handler is only used to filter removed nodes.
+    fn update(
+        &mut self,
+        update: TreeUpdate,
+        changes: Option<(&mut InternalChanges, &dyn ChangeHandler)>,
+    ) {
+        let (mut changes, handler) = match changes {
+            Some((changes, handler)) => (Some(changes), Some(handler)),
+            None => (None, None),
+        };
+
         // First, if we're collecting changes, get the accurate state
         // of any updated nodes.
+        // The name or description of a node that isn't in the update may
+        // still change, if it's computed from an updated node.
         if let Some(changes) = &mut changes {
-            for (node_id, _) in &update.nodes {
-                if let Some(old_node) = self.node_by_id(*node_id) {
+            let updated_ids = update.nodes.iter().map(|(node_id, _)| *node_id);
+            let dependent_ids = name_dependent_ids(self, updated_ids.clone());
+            for node_id in updated_ids.chain(dependent_ids) {
+                if changes.updated_nodes.contains_key(&node_id) {
+                    continue;
+                }
+                if let Some(old_node) = self.node_by_id(node_id) {
                     let old_node = old_node.detached();
-                    changes.updated_nodes.insert(*node_id, old_node);
+                    changes.updated_nodes.insert(node_id, old_node);
+                }
+            }
+        }
+
+        // Find the removed nodes while the old tree is still intact.
+        // Validation guarantees that every node in the update stays in the
+        // tree, so the removed subtrees consist only of nodes that aren't
+        // in the update, whose children don't change.
+        let new_root = update
+            .tree
+            .as_ref()
+            .map_or(self.data.root, |tree| tree.root);
+        let new_child_ids = update
+            .nodes
+            .iter()
+            .flat_map(|(_, node_data)| node_data.children().iter().copied())
+            .collect::<HashSet<NodeId>>();
+        let is_kept = |id: &NodeId| *id == new_root || new_child_ids.contains(id);
+        let mut removal_roots = Vec::new();
+        if !is_kept(&self.data.root) {
+            removal_roots.push(self.data.root);
+        }
+        for (node_id, _) in &update.nodes {
+            if let Some(old_state) = self.nodes.get(node_id) {
+                for child_id in old_state.data.children().iter() {
+                    if !is_kept(child_id) {
+                        removal_roots.push(*child_id);
+                    }
+                }
+            }
+        }
+        // A removal root may be a descendant of another one in the old tree,
+        // and each removed node must come before its descendants.
+        removal_roots.sort_by_cached_key(|id| self.index_path(*id));
+
+        fn traverse_removed(
+            nodes: &HashMap<NodeId, NodeState>,
+            is_kept: &impl Fn(&NodeId) -> bool,
+            removed_ids: &mut Vec<NodeId>,
+            id: NodeId,
+        ) {
+            removed_ids.push(id);
+            for child_id in nodes.get(&id).unwrap().data.children().iter() {
+                // Skip children that have been moved elsewhere.
+                if !is_kept(child_id) {
+                    traverse_removed(nodes, is_kept, removed_ids, *child_id);
                 }
             }
         }
 
-        let mut orphans = HashSet::new();
-        let old_focus_id = self.focus;
+        let mut removed_ids = Vec::new();
+        for id in removal_roots {
+            traverse_removed(&self.nodes, &is_kept, &mut removed_ids, id);
+        }
+
+        if let (Some(changes), Some(handler)) = (&mut changes, handler) {
+            for id in &removed_ids {
+                let node = self.node_by_id(*id).unwrap();
+                let mut old_node = DetachedNode {
+                    state: node.state.clone(),
+                    is_focused: node.is_focused(),
+                    is_root: node.is_root(),
+                    name: None,
+                    description: None,
+                    live: node.live(),
+                    supports_text_ranges: node.supports_text_ranges(),
+                    filtered_parent_id: node.parent_id(),
+                };
+                // Computing the name is expensive for large subtrees,
+                // so skip it for nodes that the handler ignores anyway.
+                if handler.filter_detached(&old_node) == FilterResult::Include {
+                    let (name, description) = node.name_and_description();
+                    old_node.name = name;
+                    old_node.description = description;
+                }
+                changes.removed_nodes.insert(*id, old_node);
+                changes.removed_node_ids.push(*id);
+            }
+        }
+
         let old_focus = self.focus.map(|id| self.node_by_id(id).unwrap().detached());
-        let old_root_id = self.data.root;
 
         if let Some(tree) = update.tree {
-            if tree.root != self.data.root {
-                orphans.insert(self.data.root);
-            }
             self.data = tree;
         }
 
+        let mut reverse_relations_changed = HashSet::new();
+        for (node_id, node_data) in &update.nodes {
+            let old_data = self.nodes.get(node_id).map(|node_state| &node_state.data);
+            self.reverse_relations.update_node(
+                *node_id,
+                old_data,
+                Some(node_data),
+                &mut reverse_relations_changed,
+            );
+        }
+
         let root = self.data.root;
         let mut pending_nodes: HashMap<NodeId, _> = HashMap::new();
         let mut pending_children = HashMap::new();
@@ -86,12 +436,9 @@ impl State {
         }
 
         for (node_id, node_data) in update.nodes {
-            orphans.remove(&node_id);
-
             let mut seen_child_ids = HashSet::new();
             for (child_index, child_id) in node_data.children().iter().enumerate() {
                 assert!(!seen_child_ids.contains(child_id));
-                orphans.remove(child_id);
                 let parent_and_index = ParentAndIndex(node_id, child_index);
                 if let Some(child_state) = self.nodes.get_mut(child_id) {
                     if child_state.parent_and_index != Some(parent_and_index) {
@@ -115,11 +462,6 @@ impl State {
                 if node_id == root {
                     node_state.parent_and_index = None
                 }
-                for child_id in node_state.data.children().iter() {
-                    if !seen_child_ids.contains(child_id) {
-                        orphans.insert(*child_id);
-                    }
-                }
                 node_state.data = node_data;
             } else if let Some(parent_and_index) = pending_children.remove(&node_id) {
                 add_node(
@@ -155,43 +497,107 @@ impl State {
             self.focus = update.focus;
         }
 
-        if !orphans.is_empty() {
-            let mut to_remove = HashSet::new();
+        for id in removed_ids {
+            if let Some(old_node_state) = self.nodes.remove(&id) {
+                self.reverse_relations.update_node(
+                    id,
+                    Some(&old_node_state.data),
+                    None,
+                    &mut reverse_relations_changed,
+                );
+            }
+        }
+
+        if let Some(changes) = &mut changes {
+            changes.reverse_relations_changed = reverse_relations_changed;
+            // Nodes that depend on an updated node may have been removed.
+            let nodes = &self.nodes;
+            changes
+                .updated_nodes
+                .retain(|node_id, _| nodes.contains_key(node_id));
+        }
 
-            fn traverse_orphan(
-                nodes: &HashMap<NodeId, NodeState>,
-                to_remove: &mut HashSet<NodeId>,
-                id: NodeId,
-            ) {
-                to_remove.insert(id);
-                let node = nodes.get(&id).unwrap();
-                for child_id in node.data.children().iter() {
-                    traverse_orphan(nodes, to_remove, *child_id);
+        self.table_grids.clear();
+        self.validate_global();
+    }
+
+    /// Returns the index of each of the node's ancestors within its parent,
+    /// starting from the root, followed by the index of the node itself.
+    /// Sorting nodes by this path puts them in depth-first order.
+    fn index_path(&self, id: NodeId) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut current = self.nodes.get(&id);
+        while let Some(ParentAndIndex(parent, index)) =
+            current.and_then(|state| state.parent_and_index.as_ref())
+        {
+            path.push(*index);
+            current = self.nodes.get(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns the IDs of the nodes that support text ranges and contain
+    /// an inline text box that was added, removed, or had its value changed.
+    fn text_changed_node_ids(&self, changes: &InternalChanges) -> HashSet<NodeId> {
+        let mut result = HashSet::new();
+        let mut add_text_ancestor = |parent_id: Option<NodeId>| {
+            let mut node = parent_id.and_then(|id| self.node_by_id(id));
+            while let Some(current) = node {
+                if current.supports_text_ranges() {
+                    result.insert(current.id());
+                    break;
                 }
+                node = current.parent();
             }
-
-            for id in orphans {
-                traverse_orphan(&self.nodes, &mut to_remove, id);
+        };
+        for id in &changes.added_node_ids {
+            let node = self.node_by_id(*id).unwrap();
+            if node.role() == Role::InlineTextBox {
+                add_text_ancestor(node.parent_id());
+            }
+        }
+        for (id, old_node) in &changes.updated_nodes {
+            let node = self.node_by_id(*id).unwrap();
+            if node.role() == Role::InlineTextBox && node.value() != old_node.value() {
+                add_text_ancestor(node.parent_id());
             }
+        }
+        for old_node in changes.removed_nodes.values() {
+            if old_node.role() == Role::InlineTextBox {
+                add_text_ancestor(old_node.parent_id());
+            }
+        }
+        result
+    }
 
-            for id in to_remove {
-                if let Some(old_node_state) = self.nodes.remove(&id) {
-                    if let Some(changes) = &mut changes {
-                        let old_node = DetachedNode {
-                            state: old_node_state,
-                            is_focused: old_focus_id == Some(id),
-                            is_root: old_root_id == id,
-                            name: None,
-                            live: Live::Off,
-                            supports_text_ranges: false,
-                        };
-                        changes.removed_nodes.insert(id, old_node);
-                    }
+    /// Returns the ID of the nearest ancestor of a removed node that
+    /// the handler's filter includes, as of before the update.
+    fn removed_node_filtered_parent_id(
+        &self,
+        node: &DetachedNode,
+        changes: &InternalChanges,
+        handler: &impl ChangeHandler,
+    ) -> Option<NodeId> {
+        let mut parent_id = node.parent_id();
+        while let Some(id) = parent_id {
+            let (filter_result, next_parent_id) = match changes
+                .removed_nodes
+                .get(&id)
+                .or_else(|| changes.updated_nodes.get(&id))
+            {
+                Some(parent) => (handler.filter_detached(parent), parent.parent_id()),
+                None => {
+                    let parent = self.node_by_id(id)?.detached();
+                    (handler.filter_detached(&parent), parent.parent_id())
                 }
+            };
+            if filter_result == FilterResult::Include {
+                return Some(id);
             }
+            parent_id = next_parent_id;
         }
-
-        self.validate_global();
+        None
     }
 
     pub fn serialize(&self) -> TreeUpdate {
@@ -216,6 +622,35 @@ impl State {
         }
     }
 
+    /// Computes the minimal update that brings this tree to the state
+    /// described by `next`, which must be a complete snapshot of the tree,
+    /// including every node.
+    ///
+    /// The result includes only the nodes that are new or changed. Removed
+    /// subtrees don't need to be listed, since their parents' children
+    /// will have changed. The tree data is only included if it changed.
+    pub fn diff(&self, next: &TreeUpdate) -> TreeUpdate {
+        let nodes = next
+            .nodes
+            .iter()
+            .filter(|(id, data)| match self.nodes.get(id) {
+                Some(old_state) => old_state.data != *data,
+                None => true,
+            })
+            .cloned()
+            .collect();
+        let tree = next
+            .tree
+            .as_ref()
+            .filter(|tree| **tree != self.data)
+            .cloned();
+        TreeUpdate {
+            nodes,
+            tree,
+            focus: next.focus,
+        }
+    }
+
     pub fn has_node(&self, id: NodeId) -> bool {
         self.nodes.contains_key(&id)
     }
@@ -244,23 +679,76 @@ impl State {
     }
 }
 
+/// What changed between the old and new versions of an updated node,
+/// as passed to [`ChangeHandler::node_updated`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeChanges {
+    /// The differences between the old and new versions of the node's
+    /// role, actions, flags, and properties.
+    pub data: NodeDiff,
+    /// Whether the node was moved to a different parent or to a different
+    /// index within its parent.
+    pub parent_and_index: bool,
+    /// Whether the text of the node changed. For an inline text box,
+    /// this means its value. For a node that supports text ranges, this
+    /// means the text of any of its inline text boxes, including ones that
+    /// were added or removed in the same update.
+    pub text_content: bool,
+}
+
+impl NodeChanges {
+    fn new(old_node: &DetachedNode, new_node: &Node, text_changed: &HashSet<NodeId>) -> Self {
+        let data = old_node.data().diff(new_node.data());
+        let text_content = if new_node.role() == Role::InlineTextBox {
+            data.value()
+        } else {
+            text_changed.contains(&new_node.id())
+        };
+        Self {
+            data,
+            parent_and_index: old_node.parent_and_index != new_node.state.parent_and_index,
+            text_content,
+        }
+    }
+
+    /// Returns true if nothing observable changed.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty() && !self.parent_and_index && !self.text_content
+    }
+}
+
 pub trait ChangeHandler {
     fn node_added(&mut self, node: &Node);
-    fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node);
+    /// Called for each node that was in the update and existed before it,
+    /// with the state it had before the update. It's also called for
+    /// other nodes whose name or description may be computed from one
+    /// of those, such as an ancestor that takes its name from its contents
+    /// or a node that is labelled by one of them, in which case `changes`
+    /// may be empty.
+    fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node, changes: &NodeChanges);
     fn focus_moved(
         &mut self,
         old_node: Option<&DetachedNode>,
         new_node: Option<&Node>,
         current_state: &State,
     );
-    /// The tree update process doesn't currently collect all possible information
-    /// about removed nodes. The following methods don't accurately reflect
-    /// the full state of the old node:
-    ///
-    /// * [`DetachedNode::name`]
-    /// * [`DetachedNode::live`]
-    /// * [`DetachedNode::supports_text_ranges`]
+    /// Called for each removed node, with the state it had before
+    /// the update. A removed node is always reported before any of its
+    /// removed descendants. [`DetachedNode::filtered_parent_id`] is
+    /// computed using [`ChangeHandler::filter_detached`].
     fn node_removed(&mut self, node: &DetachedNode, current_state: &State);
+    /// Determines which ancestors of a removed node are skipped when
+    /// computing [`DetachedNode::filtered_parent_id`]. By default,
+    /// every ancestor is included. The name and description of a removed
+    /// node are only computed if it is included, so they aren't yet
+    /// available to this method.
+    fn filter_detached(&self, _node: &DetachedNode) -> FilterResult {
+        FilterResult::Include
+    }
+    /// Called when the set of nodes that have a relation to this node,
+    /// such as the nodes that it labels, has changed.
+    /// See [`Node::reverse_relations`].
+    fn reverse_relations_changed(&mut self, _node: &Node) {}
 }
 
 pub struct Tree {
@@ -268,18 +756,32 @@ pub struct Tree {
 }
 
 impl Tree {
-    pub fn new(mut initial_state: TreeUpdate) -> Self {
+    pub fn new(initial_state: TreeUpdate) -> Self {
+        Self::try_new(initial_state).unwrap()
+    }
+
+    /// Like [`Tree::new`], but returns an error instead of panicking
+    /// if the initial state isn't a well-formed tree.
+    pub fn try_new(initial_state: TreeUpdate) -> Result<Self, UpdateError> {
         let mut state = State {
             nodes: HashMap::new(),
-            data: initial_state.tree.take().unwrap(),
+            data: initial_state.tree.clone().ok_or(UpdateError::MissingTree)?,
             focus: None,
+            reverse_relations: ReverseRelations::default(),
+            table_grids: GridCache::default(),
         };
-        state.update(initial_state, None);
-        Self { state }
+        state.try_update(initial_state, None)?;
+        Ok(Self { state })
     }
 
     pub fn update(&mut self, update: TreeUpdate) {
-        self.state.update(update, None);
+        self.try_update(update).unwrap()
+    }
+
+    /// Like [`Tree::update`], but returns an error instead of panicking
+    /// if the update is malformed. The tree is unchanged in that case.
+    pub fn try_update(&mut self, update: TreeUpdate) -> Result<(), UpdateError> {
+        self.state.try_update(update, None)
     }
 
     pub fn update_and_process_changes(
@@ -287,24 +789,41 @@ impl Tree {
         update: TreeUpdate,
         handler: &mut impl ChangeHandler,
     ) {
+        self.try_update_and_process_changes(update, handler)
+            .unwrap()
+    }
+
+    /// Like [`Tree::update_and_process_changes`], but returns an error
+    /// instead of panicking if the update is malformed. The tree is
+    /// unchanged and the handler isn't called in that case.
+    pub fn try_update_and_process_changes(
+        &mut self,
+        update: TreeUpdate,
+        handler: &mut impl ChangeHandler,
+    ) -> Result<(), UpdateError> {
         let mut changes = InternalChanges::default();
-        self.state.update(update, Some(&mut changes));
+        self.state
+            .try_update(update, Some((&mut changes, &*handler)))?;
+        let text_changed = self.state.text_changed_node_ids(&changes);
         for id in &changes.added_node_ids {
             let node = self.state.node_by_id(*id).unwrap();
             handler.node_added(&node);
         }
         for (id, old_node) in &changes.updated_nodes {
             let new_node = self.state.node_by_id(*id).unwrap();
-            handler.node_updated(old_node, &new_node);
+            let node_changes = NodeChanges::new(old_node, &new_node, &text_changed);
+            handler.node_updated(old_node, &new_node, &node_changes);
         }
-        if let Some(focus_change) = changes.focus_change {
+        if let Some(focus_change) = changes.focus_change.take() {
             if let Some(old_node) = &focus_change.old_focus {
                 let id = old_node.id();
                 if !changes.updated_nodes.contains_key(&id)
                     && !changes.removed_nodes.contains_key(&id)
                 {
                     if let Some(old_node_new_version) = self.state.node_by_id(id) {
-                        handler.node_updated(old_node, &old_node_new_version);
+                        let node_changes =
+                            NodeChanges::new(old_node, &old_node_new_version, &text_changed);
+                        handler.node_updated(old_node, &old_node_new_version, &node_changes);
                     }
                 }
             }
@@ -314,7 +833,9 @@ impl Tree {
                 if !changes.added_node_ids.contains(&id) && !changes.updated_nodes.contains_key(&id)
                 {
                     if let Some(new_node_old_version) = focus_change.new_focus_old_node {
-                        handler.node_updated(&new_node_old_version, &new_node);
+                        let node_changes =
+                            NodeChanges::new(&new_node_old_version, &new_node, &text_changed);
+                        handler.node_updated(&new_node_old_version, &new_node, &node_changes);
                     }
                 }
             }
@@ -324,9 +845,69 @@ impl Tree {
                 &self.state,
             );
         }
-        for node in changes.removed_nodes.values() {
+        for id in &changes.reverse_relations_changed {
+            if let Some(node) = self.state.node_by_id(*id) {
+                handler.reverse_relations_changed(&node);
+            }
+        }
+        let filtered_parent_ids = changes
+            .removed_node_ids
+            .iter()
+            .map(|id| {
+                self.state.removed_node_filtered_parent_id(
+                    &changes.removed_nodes[id],
+                    &changes,
+                    handler,
+                )
+            })
+            .collect::<Vec<_>>();
+        for (id, filtered_parent_id) in changes.removed_node_ids.iter().zip(filtered_parent_ids) {
+            let node = changes.removed_nodes.get_mut(id).unwrap();
+            node.filtered_parent_id = filtered_parent_id;
             handler.node_removed(node, &self.state);
         }
+        Ok(())
+    }
+
+    /// Applies patches to existing nodes, leaving any properties that
+    /// the patches don't mention unchanged. The focus is also unchanged.
+    pub fn apply_patches(&mut self, patches: Vec<(NodeId, NodePatch)>, classes: &mut NodeClassSet) {
+        self.try_apply_patches(patches, classes).unwrap()
+    }
+
+    /// Like [`Tree::apply_patches`], but returns an error instead of
+    /// panicking if a patch targets an unknown node or would result in
+    /// a malformed tree. The tree is unchanged in that case.
+    pub fn try_apply_patches(
+        &mut self,
+        patches: Vec<(NodeId, NodePatch)>,
+        classes: &mut NodeClassSet,
+    ) -> Result<(), UpdateError> {
+        let update = self.state.update_from_patches(patches, classes)?;
+        self.try_update(update)
+    }
+
+    pub fn apply_patches_and_process_changes(
+        &mut self,
+        patches: Vec<(NodeId, NodePatch)>,
+        classes: &mut NodeClassSet,
+        handler: &mut impl ChangeHandler,
+    ) {
+        self.try_apply_patches_and_process_changes(patches, classes, handler)
+            .unwrap()
+    }
+
+    /// Like [`Tree::apply_patches_and_process_changes`], but returns
+    /// an error instead of panicking. The tree is unchanged and the handler
+    /// isn't called in that case.
+    pub fn try_apply_patches_and_process_changes(
+        &mut self,
+        patches: Vec<(NodeId, NodePatch)>,
+        classes: &mut NodeClassSet,
+        handler: &mut impl ChangeHandler,
+    ) -> Result<(), UpdateError> {
+        let update = self.state.update_from_patches(patches, classes)?;
+        self.try_update_and_process_changes(update, handler)
     }
 
     pub fn state(&self) -> &State {
@@ -336,12 +917,19 @@ impl Tree {
 
 #[cfg(test)]
 mod tests {
-    use accesskit::{NodeBuilder, NodeClassSet, NodeId, Role, Tree, TreeUpdate};
-    use std::num::NonZeroU128;
+    use accesskit::{
+        Action, Live, NodeBuilder, NodeClassSet, NodeId, NodePatch, Role, Tree, TreeUpdate,
+    };
+    use std::{collections::HashMap, num::NonZeroU128};
+
+    use super::UpdateError;
 
     const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
     const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
     const NODE_ID_3: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const NODE_ID_4: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+    const NODE_ID_5: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(5) });
+    const NODE_ID_6: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(6) });
 
     #[test]
     fn init_tree_with_root_node() {
@@ -436,7 +1024,12 @@ mod tests {
                 }
                 unexpected_change();
             }
-            fn node_updated(&mut self, old_node: &crate::DetachedNode, new_node: &crate::Node) {
+            fn node_updated(
+                &mut self,
+                old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
                 if new_node.id() == NODE_ID_1
                     && old_node.data().children().is_empty()
                     && new_node.data().children() == [NODE_ID_2]
@@ -515,7 +1108,12 @@ mod tests {
             fn node_added(&mut self, _node: &crate::Node) {
                 unexpected_change();
             }
-            fn node_updated(&mut self, old_node: &crate::DetachedNode, new_node: &crate::Node) {
+            fn node_updated(
+                &mut self,
+                old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
                 if new_node.id() == NODE_ID_1
                     && old_node.data().children() == [NODE_ID_2]
                     && new_node.data().children().is_empty()
@@ -597,7 +1195,12 @@ mod tests {
             fn node_added(&mut self, _node: &crate::Node) {
                 unexpected_change();
             }
-            fn node_updated(&mut self, old_node: &crate::DetachedNode, new_node: &crate::Node) {
+            fn node_updated(
+                &mut self,
+                old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
                 if old_node.id() == NODE_ID_2
                     && new_node.id() == NODE_ID_2
                     && old_node.is_focused()
@@ -695,7 +1298,12 @@ mod tests {
             fn node_added(&mut self, _node: &crate::Node) {
                 unexpected_change();
             }
-            fn node_updated(&mut self, old_node: &crate::DetachedNode, new_node: &crate::Node) {
+            fn node_updated(
+                &mut self,
+                old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
                 if new_node.id() == NODE_ID_2
                     && old_node.name() == Some("foo".into())
                     && new_node.name() == Some("bar".into())
@@ -731,4 +1339,931 @@ mod tests {
             tree.state().node_by_id(NODE_ID_2).unwrap().name()
         );
     }
+
+    /// Records the name of each updated node before and after the update.
+    #[derive(Default)]
+    struct NameChangeHandler {
+        updated: Vec<(NodeId, Option<String>, Option<String>)>,
+    }
+
+    impl super::ChangeHandler for NameChangeHandler {
+        fn node_added(&mut self, _node: &crate::Node) {
+            panic!("expected only updated nodes");
+        }
+        fn node_updated(
+            &mut self,
+            old_node: &crate::DetachedNode,
+            new_node: &crate::Node,
+            _changes: &crate::NodeChanges,
+        ) {
+            self.updated
+                .push((new_node.id(), old_node.name(), new_node.name()));
+        }
+        fn focus_moved(
+            &mut self,
+            _old_node: Option<&crate::DetachedNode>,
+            _new_node: Option<&crate::Node>,
+            _current_state: &crate::TreeState,
+        ) {
+            panic!("expected only updated nodes");
+        }
+        fn node_removed(&mut self, _node: &crate::DetachedNode, _current_state: &crate::TreeState) {
+            panic!("expected only updated nodes");
+        }
+    }
+
+    fn static_text(classes: &mut NodeClassSet, name: &str) -> accesskit::Node {
+        let mut builder = NodeBuilder::new(Role::StaticText);
+        builder.set_name(name);
+        builder.build(classes)
+    }
+
+    #[test]
+    fn name_changes_propagate_to_dependent_nodes() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_4, NODE_ID_5]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_children(vec![NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, static_text(&mut classes, "OK")),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.set_labelled_by(vec![NODE_ID_5]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_5, {
+                    let mut builder = NodeBuilder::new(Role::LabelText);
+                    builder.set_children(vec![NODE_ID_6]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_6, static_text(&mut classes, "Email")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_3, static_text(&mut classes, "Cancel")),
+                (NODE_ID_6, static_text(&mut classes, "E-mail")),
+            ],
+            tree: None,
+            focus: None,
+        };
+        let mut handler = NameChangeHandler::default();
+        tree.update_and_process_changes(update, &mut handler);
+        handler.updated.sort_by_key(|(id, _, _)| id.0);
+        assert_eq!(
+            vec![
+                (NODE_ID_2, Some("OK".into()), Some("Cancel".into())),
+                (NODE_ID_3, Some("OK".into()), Some("Cancel".into())),
+                (NODE_ID_4, Some("Email".into()), Some("E-mail".into())),
+                (NODE_ID_5, Some("Email".into()), Some("E-mail".into())),
+                (NODE_ID_6, Some("Email".into()), Some("E-mail".into())),
+            ],
+            handler.updated
+        );
+    }
+
+    #[test]
+    fn name_changes_propagate_through_labelled_by_cycle() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::CheckBox);
+                    builder.set_labelled_by(vec![NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::LabelText);
+                    builder.set_labelled_by(vec![NODE_ID_2]);
+                    builder.set_children(vec![NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_4, static_text(&mut classes, "Remember me")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let state = tree.state();
+        assert_eq!(
+            Some("Remember me".into()),
+            state.node_by_id(NODE_ID_2).unwrap().name()
+        );
+        assert_eq!(
+            Some("Remember me".into()),
+            state.node_by_id(NODE_ID_3).unwrap().name()
+        );
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_4, static_text(&mut classes, "Stay signed in"))],
+            tree: None,
+            focus: None,
+        };
+        let mut handler = NameChangeHandler::default();
+        tree.update_and_process_changes(update, &mut handler);
+        handler.updated.sort_by_key(|(id, _, _)| id.0);
+        assert_eq!(
+            vec![
+                (
+                    NODE_ID_2,
+                    Some("Remember me".into()),
+                    Some("Stay signed in".into())
+                ),
+                (
+                    NODE_ID_3,
+                    Some("Remember me".into()),
+                    Some("Stay signed in".into())
+                ),
+                (
+                    NODE_ID_4,
+                    Some("Remember me".into()),
+                    Some("Stay signed in".into())
+                ),
+            ],
+            handler.updated
+        );
+    }
+
+    #[test]
+    fn reverse_relations_changed_only_for_added_or_removed_targets() {
+        let mut classes = NodeClassSet::new();
+        let field = |classes: &mut NodeClassSet, labels: Vec<NodeId>, description: &str| {
+            let mut builder = NodeBuilder::new(Role::TextField);
+            builder.set_labelled_by(labels);
+            builder.set_description(description);
+            builder.build(classes)
+        };
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3, NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, field(&mut classes, vec![NODE_ID_3], "a")),
+                (NODE_ID_3, static_text(&mut classes, "First")),
+                (NODE_ID_4, static_text(&mut classes, "Last")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        #[derive(Default)]
+        struct Handler {
+            reverse_relations_changed: Vec<NodeId>,
+        }
+        impl super::ChangeHandler for Handler {
+            fn node_added(&mut self, _node: &crate::Node) {}
+            fn node_updated(
+                &mut self,
+                _old_node: &crate::DetachedNode,
+                _new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
+            }
+            fn focus_moved(
+                &mut self,
+                _old_node: Option<&crate::DetachedNode>,
+                _new_node: Option<&crate::Node>,
+                _current_state: &crate::TreeState,
+            ) {
+            }
+            fn node_removed(
+                &mut self,
+                _node: &crate::DetachedNode,
+                _current_state: &crate::TreeState,
+            ) {
+            }
+            fn reverse_relations_changed(&mut self, node: &crate::Node) {
+                self.reverse_relations_changed.push(node.id());
+            }
+        }
+        let update = |tree: &mut super::Tree, data| {
+            let update = TreeUpdate {
+                nodes: vec![(NODE_ID_2, data)],
+                tree: None,
+                focus: None,
+            };
+            let mut handler = Handler::default();
+            tree.update_and_process_changes(update, &mut handler);
+            handler.reverse_relations_changed.sort_by_key(|id| id.0);
+            handler.reverse_relations_changed
+        };
+
+        let data = field(&mut classes, vec![NODE_ID_3], "b");
+        assert!(update(&mut tree, data).is_empty());
+        let data = field(&mut classes, vec![NODE_ID_3, NODE_ID_4], "b");
+        assert_eq!(vec![NODE_ID_4], update(&mut tree, data));
+        let data = field(&mut classes, vec![NODE_ID_4, NODE_ID_3], "b");
+        assert!(update(&mut tree, data).is_empty());
+        let data = field(&mut classes, vec![], "b");
+        assert_eq!(vec![NODE_ID_3, NODE_ID_4], update(&mut tree, data));
+    }
+
+    fn tree_with_two_buttons(classes: &mut NodeClassSet) -> super::Tree {
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3]);
+                    builder.build(classes)
+                }),
+                (NODE_ID_2, NodeBuilder::new(Role::Button).build(classes)),
+                (NODE_ID_3, NodeBuilder::new(Role::Button).build(classes)),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: Some(NODE_ID_2),
+        };
+        super::Tree::new(update)
+    }
+
+    #[test]
+    fn try_new_without_tree_data() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![(
+                NODE_ID_1,
+                NodeBuilder::new(Role::Window).build(&mut classes),
+            )],
+            tree: None,
+            focus: None,
+        };
+        assert_eq!(
+            Some(UpdateError::MissingTree),
+            super::Tree::try_new(update).err()
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_child() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_1, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NODE_ID_2, NODE_ID_2]);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        assert_eq!(
+            Err(UpdateError::DuplicateChild {
+                parent: NODE_ID_1,
+                child: NODE_ID_2
+            }),
+            tree.try_update(update)
+        );
+        assert_eq!(2, tree.state().root().children().count());
+        assert!(tree.state().has_node(NODE_ID_3));
+    }
+
+    #[test]
+    fn reject_orphaned_node() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![(
+                NODE_ID_4,
+                NodeBuilder::new(Role::Button).build(&mut classes),
+            )],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        assert_eq!(
+            Err(UpdateError::OrphanedNode(NODE_ID_4)),
+            tree.try_update(update)
+        );
+        assert!(!tree.state().has_node(NODE_ID_4));
+    }
+
+    #[test]
+    fn reject_unknown_focus() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_1, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NODE_ID_2]);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: Some(NODE_ID_3),
+        };
+        assert_eq!(
+            Err(UpdateError::UnknownFocus(NODE_ID_3)),
+            tree.try_update(update)
+        );
+        assert_eq!(2, tree.state().root().children().count());
+        assert_eq!(Some(NODE_ID_2), tree.state().focus_id());
+    }
+
+    #[test]
+    fn reject_cycle() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_3, {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_children(vec![NODE_ID_1]);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        assert_eq!(Err(UpdateError::Cycle(NODE_ID_1)), tree.try_update(update));
+        assert!(tree.state().root().parent().is_none());
+    }
+
+    #[test]
+    fn reject_node_reparented_twice() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_3, {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_children(vec![NODE_ID_2]);
+                builder.build(&mut classes)
+            })],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        assert_eq!(
+            Err(UpdateError::ReparentedTwice {
+                node: NODE_ID_2,
+                first_parent: NODE_ID_1,
+                second_parent: NODE_ID_3
+            }),
+            tree.try_update(update)
+        );
+        assert_eq!(
+            NODE_ID_1,
+            tree.state()
+                .node_by_id(NODE_ID_2)
+                .unwrap()
+                .parent()
+                .unwrap()
+                .id()
+        );
+    }
+
+    #[test]
+    fn move_node_to_sibling() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::Group);
+                    builder.set_children(vec![NODE_ID_2]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        tree.try_update(update).unwrap();
+        let state = tree.state();
+        assert_eq!(1, state.root().children().count());
+        assert_eq!(
+            NODE_ID_3,
+            state.node_by_id(NODE_ID_2).unwrap().parent().unwrap().id()
+        );
+    }
+
+    #[test]
+    fn diff_snapshots() {
+        let mut classes = NodeClassSet::new();
+        let tree = tree_with_two_buttons(&mut classes);
+        let mut next = tree.state().serialize();
+        next.nodes = vec![
+            (NODE_ID_1, {
+                let mut builder = NodeBuilder::new(Role::Window);
+                builder.set_children(vec![NODE_ID_2, NODE_ID_4]);
+                builder.build(&mut classes)
+            }),
+            (
+                NODE_ID_2,
+                NodeBuilder::new(Role::Button).build(&mut classes),
+            ),
+            (NODE_ID_4, {
+                let mut builder = NodeBuilder::new(Role::Button);
+                builder.set_name("new");
+                builder.build(&mut classes)
+            }),
+        ];
+        let update = tree.state().diff(&next);
+        assert_eq!(
+            vec![NODE_ID_1, NODE_ID_4],
+            update.nodes.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        );
+        assert!(update.tree.is_none());
+        assert_eq!(Some(NODE_ID_2), update.focus);
+
+        let mut tree = tree;
+        tree.update(update);
+        assert_eq!(next, tree.state().serialize());
+    }
+
+    #[derive(Default)]
+    struct NodeChangesCollector {
+        changes: HashMap<NodeId, crate::NodeChanges>,
+    }
+
+    impl super::ChangeHandler for NodeChangesCollector {
+        fn node_added(&mut self, _node: &crate::Node) {
+            panic!("expected only updated nodes");
+        }
+        fn node_updated(
+            &mut self,
+            _old_node: &crate::DetachedNode,
+            new_node: &crate::Node,
+            changes: &crate::NodeChanges,
+        ) {
+            self.changes.insert(new_node.id(), changes.clone());
+        }
+        fn focus_moved(
+            &mut self,
+            _old_node: Option<&crate::DetachedNode>,
+            _new_node: Option<&crate::Node>,
+            _current_state: &crate::TreeState,
+        ) {
+            panic!("expected only updated nodes");
+        }
+        fn node_removed(&mut self, _node: &crate::DetachedNode, _current_state: &crate::TreeState) {
+            panic!("expected only updated nodes");
+        }
+    }
+
+    #[test]
+    fn node_changes() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_3, NODE_ID_2]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_name("foo");
+                    builder.add_action(Action::Focus);
+                    builder.set_disabled();
+                    builder.build(&mut classes)
+                }),
+                (
+                    NODE_ID_3,
+                    NodeBuilder::new(Role::CheckBox).build(&mut classes),
+                ),
+            ],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        let mut handler = NodeChangesCollector::default();
+        tree.update_and_process_changes(update, &mut handler);
+        let window_changes = &handler.changes[&NODE_ID_1];
+        assert!(window_changes.data.children());
+        assert!(!window_changes.data.role());
+        assert!(!window_changes.parent_and_index);
+        assert!(!window_changes.text_content);
+        let button_changes = &handler.changes[&NODE_ID_2];
+        assert!(!button_changes.data.role());
+        assert!(button_changes.data.supports_action(Action::Focus));
+        assert!(!button_changes.data.supports_action(Action::Default));
+        assert!(button_changes.data.is_disabled());
+        assert!(!button_changes.data.is_hidden());
+        assert!(button_changes.data.name());
+        assert!(!button_changes.data.children());
+        assert!(button_changes.parent_and_index);
+        let check_box_changes = &handler.changes[&NODE_ID_3];
+        assert!(check_box_changes.data.role());
+        assert!(!check_box_changes.data.name());
+        assert!(check_box_changes.parent_and_index);
+
+        let update = TreeUpdate {
+            nodes: vec![(
+                NODE_ID_3,
+                NodeBuilder::new(Role::CheckBox).build(&mut classes),
+            )],
+            tree: None,
+            focus: Some(NODE_ID_2),
+        };
+        let mut handler = NodeChangesCollector::default();
+        tree.update_and_process_changes(update, &mut handler);
+        assert!(handler.changes[&NODE_ID_3].is_empty());
+    }
+
+    #[test]
+    fn text_content_changes() {
+        let mut classes = NodeClassSet::new();
+        let text_field = {
+            let mut builder = NodeBuilder::new(Role::TextField);
+            builder.set_children(vec![NODE_ID_3]);
+            builder.build(&mut classes)
+        };
+        let text_box = |value: &str, classes: &mut NodeClassSet| {
+            let mut builder = NodeBuilder::new(Role::InlineTextBox);
+            builder.set_value(value);
+            builder.set_character_lengths(vec![1; value.len()]);
+            builder.build(classes)
+        };
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, text_field.clone()),
+                (NODE_ID_3, text_box("foo", &mut classes)),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_2, text_field),
+                (NODE_ID_3, text_box("quux", &mut classes)),
+            ],
+            tree: None,
+            focus: None,
+        };
+        let mut handler = NodeChangesCollector::default();
+        tree.update_and_process_changes(update, &mut handler);
+        let text_field_changes = &handler.changes[&NODE_ID_2];
+        assert!(text_field_changes.data.is_empty());
+        assert!(text_field_changes.text_content);
+        let text_box_changes = &handler.changes[&NODE_ID_3];
+        assert!(text_box_changes.data.value());
+        assert!(text_box_changes.data.character_lengths());
+        assert!(!text_box_changes.data.name());
+        assert!(text_box_changes.text_content);
+    }
+
+    #[test]
+    fn removed_nodes_are_detached_before_removal() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::GenericContainer);
+                    builder.set_live(Live::Polite);
+                    builder.set_children(vec![NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_children(vec![NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::StaticText);
+                    builder.set_name("OK");
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let update = TreeUpdate {
+            nodes: vec![(
+                NODE_ID_1,
+                NodeBuilder::new(Role::Window).build(&mut classes),
+            )],
+            tree: None,
+            focus: None,
+        };
+        struct Handler {
+            removed: Vec<(NodeId, Option<String>, Live, Option<NodeId>)>,
+        }
+        fn unexpected_change() {
+            panic!("expected only removed nodes and updated root");
+        }
+        impl super::ChangeHandler for Handler {
+            fn node_added(&mut self, _node: &crate::Node) {
+                unexpected_change();
+            }
+            fn node_updated(
+                &mut self,
+                _old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
+                if new_node.id() != NODE_ID_1 {
+                    unexpected_change();
+                }
+            }
+            fn focus_moved(
+                &mut self,
+                _old_node: Option<&crate::DetachedNode>,
+                _new_node: Option<&crate::Node>,
+                _current_state: &crate::TreeState,
+            ) {
+                unexpected_change();
+            }
+            fn node_removed(
+                &mut self,
+                node: &crate::DetachedNode,
+                _current_state: &crate::TreeState,
+            ) {
+                self.removed.push((
+                    node.id(),
+                    node.name(),
+                    node.live(),
+                    node.filtered_parent_id(),
+                ));
+            }
+            fn filter_detached(&self, node: &crate::DetachedNode) -> crate::FilterResult {
+                if node.role() == Role::GenericContainer {
+                    crate::FilterResult::ExcludeNode
+                } else {
+                    crate::FilterResult::Include
+                }
+            }
+        }
+        let mut handler = Handler {
+            removed: Vec::new(),
+        };
+        tree.update_and_process_changes(update, &mut handler);
+        assert_eq!(
+            vec![
+                (NODE_ID_2, None, Live::Polite, Some(NODE_ID_1)),
+                (NODE_ID_3, Some("OK".into()), Live::Polite, Some(NODE_ID_1)),
+                (NODE_ID_4, Some("OK".into()), Live::Polite, Some(NODE_ID_3)),
+            ],
+            handler.removed
+        );
+    }
+
+    #[test]
+    fn removed_nodes_are_named_unless_filtered() {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_3]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_name("OK");
+                    builder.set_description("Confirm");
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::GenericContainer);
+                    builder.set_name("Group");
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let update = TreeUpdate {
+            nodes: vec![(
+                NODE_ID_1,
+                NodeBuilder::new(Role::Window).build(&mut classes),
+            )],
+            tree: None,
+            focus: None,
+        };
+        struct Handler {
+            removed: Vec<(NodeId, Option<String>, Option<String>)>,
+        }
+        impl super::ChangeHandler for Handler {
+            fn node_added(&mut self, _node: &crate::Node) {}
+            fn node_updated(
+                &mut self,
+                _old_node: &crate::DetachedNode,
+                _new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
+            }
+            fn focus_moved(
+                &mut self,
+                _old_node: Option<&crate::DetachedNode>,
+                _new_node: Option<&crate::Node>,
+                _current_state: &crate::TreeState,
+            ) {
+            }
+            fn node_removed(
+                &mut self,
+                node: &crate::DetachedNode,
+                _current_state: &crate::TreeState,
+            ) {
+                self.removed
+                    .push((node.id(), node.name(), node.description()));
+            }
+            fn filter_detached(&self, node: &crate::DetachedNode) -> crate::FilterResult {
+                if node.role() == Role::GenericContainer {
+                    crate::FilterResult::ExcludeNode
+                } else {
+                    crate::FilterResult::Include
+                }
+            }
+        }
+        let mut handler = Handler {
+            removed: Vec::new(),
+        };
+        tree.update_and_process_changes(update, &mut handler);
+        assert_eq!(
+            vec![
+                (NODE_ID_2, Some("OK".into()), Some("Confirm".into())),
+                (NODE_ID_3, None, None),
+            ],
+            handler.removed
+        );
+    }
+
+    #[test]
+    fn nested_removed_nodes_are_reported_in_tree_order() {
+        let mut classes = NodeClassSet::new();
+        // The new root is a descendant of the old one, and one of its
+        // children is removed along with the rest of the old tree.
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2, NODE_ID_5]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::GenericContainer);
+                    builder.set_children(vec![NODE_ID_3, NODE_ID_4]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, static_text(&mut classes, "Kept")),
+                (NODE_ID_4, {
+                    let mut builder = NodeBuilder::new(Role::Button);
+                    builder.set_children(vec![NODE_ID_6]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_5, static_text(&mut classes, "Removed")),
+                (NODE_ID_6, static_text(&mut classes, "OK")),
+            ],
+            tree: Some(Tree::new(NODE_ID_1)),
+            focus: None,
+        };
+        let mut tree = super::Tree::new(update);
+        let update = TreeUpdate {
+            nodes: vec![(NODE_ID_2, {
+                let mut builder = NodeBuilder::new(Role::GenericContainer);
+                builder.set_children(vec![NODE_ID_3]);
+                builder.build(&mut classes)
+            })],
+            tree: Some(Tree::new(NODE_ID_2)),
+            focus: None,
+        };
+        struct Handler {
+            removed: Vec<(NodeId, Option<String>)>,
+        }
+        impl super::ChangeHandler for Handler {
+            fn node_added(&mut self, _node: &crate::Node) {
+                panic!("expected no added nodes");
+            }
+            fn node_updated(
+                &mut self,
+                _old_node: &crate::DetachedNode,
+                _new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
+            }
+            fn focus_moved(
+                &mut self,
+                _old_node: Option<&crate::DetachedNode>,
+                _new_node: Option<&crate::Node>,
+                _current_state: &crate::TreeState,
+            ) {
+            }
+            fn node_removed(
+                &mut self,
+                node: &crate::DetachedNode,
+                current_state: &crate::TreeState,
+            ) {
+                assert!(!current_state.has_node(node.id()));
+                self.removed.push((node.id(), node.name()));
+            }
+        }
+        let mut handler = Handler {
+            removed: Vec::new(),
+        };
+        tree.update_and_process_changes(update, &mut handler);
+        assert_eq!(
+            vec![
+                (NODE_ID_1, None),
+                (NODE_ID_5, Some("Removed".into())),
+                (NODE_ID_4, Some("OK".into())),
+                (NODE_ID_6, Some("OK".into())),
+            ],
+            handler.removed
+        );
+        assert_eq!(NODE_ID_2, tree.state().root_id());
+        assert!(tree.state().root().parent().is_none());
+    }
+
+    #[test]
+    fn patch_node() {
+        let mut classes = NodeClassSet::new();
+        let mut tree = tree_with_two_buttons(&mut classes);
+        let mut patch = NodePatch::new();
+        patch.set_name("foo");
+        patch.set_disabled();
+        struct Handler {
+            got_updated_node: bool,
+        }
+        fn unexpected_change() {
+            panic!("expected only updated node");
+        }
+        impl super::ChangeHandler for Handler {
+            fn node_added(&mut self, _node: &crate::Node) {
+                unexpected_change();
+            }
+            fn node_updated(
+                &mut self,
+                old_node: &crate::DetachedNode,
+                new_node: &crate::Node,
+                _changes: &crate::NodeChanges,
+            ) {
+                if new_node.id() == NODE_ID_3
+                    && old_node.name().is_none()
+                    && !old_node.is_disabled()
+                    && new_node.name() == Some("foo".into())
+                    && new_node.is_disabled()
+                    && new_node.role() == Role::Button
+                {
+                    self.got_updated_node = true;
+                    return;
+                }
+                unexpected_change();
+            }
+            fn focus_moved(
+                &mut self,
+                _old_node: Option<&crate::DetachedNode>,
+                _new_node: Option<&crate::Node>,
+                _current_state: &crate::TreeState,
+            ) {
+                unexpected_change();
+            }
+            fn node_removed(
+                &mut self,
+                _node: &crate::DetachedNode,
+                _current_state: &crate::TreeState,
+            ) {
+                unexpected_change();
+            }
+        }
+        let mut handler = Handler {
+            got_updated_node: false,
+        };
+        tree.apply_patches_and_process_changes(
+            vec![(NODE_ID_3, patch)],
+            &mut classes,
+            &mut handler,
+        );
+        assert!(handler.got_updated_node);
+        assert_eq!(Some(NODE_ID_2), tree.state().focus_id());
+
+        let mut patch = NodePatch::new();
+        patch.clear_name();
+        tree.apply_patches(vec![(NODE_ID_3, patch)], &mut classes);
+        let node = tree.state().node_by_id(NODE_ID_3).unwrap();
+        assert!(node.name().is_none());
+        assert!(node.is_disabled());
+
+        assert_eq!(
+            Err(UpdateError::UnknownPatchTarget(NODE_ID_4)),
+            tree.try_apply_patches(vec![(NODE_ID_4, NodePatch::new())], &mut classes)
+        );
+    }
 }
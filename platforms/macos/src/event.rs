@@ -4,7 +4,9 @@
 // the LICENSE-MIT file), at your option.
 
 use accesskit::{Live, NodeId};
-use accesskit_consumer::{DetachedNode, FilterResult, Node, TreeChangeHandler, TreeState};
+use accesskit_consumer::{
+    DetachedNode, FilterResult, Node, NodeChanges, TreeChangeHandler, TreeState,
+};
 use objc2::{
     foundation::{NSInteger, NSMutableDictionary, NSNumber, NSObject, NSString},
     msg_send, Message,
@@ -161,7 +163,7 @@ impl TreeChangeHandler for EventGenerator {
         }
     }
 
-    fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node) {
+    fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node, _changes: &NodeChanges) {
         // TODO: text changes, live regions
         if filter(new_node) != FilterResult::Include {
             return;
@@ -221,4 +223,8 @@ impl TreeChangeHandler for EventGenerator {
     fn node_removed(&mut self, node: &DetachedNode, _current_state: &TreeState) {
         self.events.push(QueuedEvent::NodeDestroyed(node.id()));
     }
+
+    fn filter_detached(&self, node: &DetachedNode) -> FilterResult {
+        filter_detached(node)
+    }
 }
@@ -5,41 +5,284 @@
 
 use crate::{
     atspi::{
+        a11y_status,
         interfaces::{
-            AccessibleInterface, ActionInterface, ComponentInterface, Event, ObjectEvent,
-            ValueInterface, WindowEvent,
+            AccessibleInterface, ActionInterface, ComponentInterface, EditableTextInterface, Event,
+            HyperlinkInterface, HypertextInterface, ObjectEvent, SelectionInterface,
+            TableCellInterface, TableInterface, TextInterface, ValueInterface, WindowEvent,
         },
-        Bus, ObjectId, ACCESSIBLE_PATH_PREFIX,
+        Bus, ObjectId, ObjectRef, ACCESSIBLE_PATH_PREFIX,
     },
     context::Context,
-    node::{filter, filter_detached, NodeWrapper, PlatformNode},
-    util::AppContext,
+    node::{
+        filter, filter_detached, notify_selection_changes, remove_window, text_container,
+        LiveRegionChanges, NodeWrapper, PlatformNode, TextBoxContent, TextChanges,
+    },
+    util::{AppContext, WindowBounds},
 };
 use accesskit::{ActionHandler, NodeId, Rect, Role, TreeUpdate};
-use accesskit_consumer::{DetachedNode, FilterResult, Node, Tree, TreeChangeHandler, TreeState};
+use accesskit_consumer::{
+    DetachedNode, FilterResult, Node, NodeChanges, Tree, TreeChangeHandler, TreeState,
+};
 use async_channel::{Receiver, Sender};
 use atspi::{Interface, InterfaceSet, State};
 use futures_lite::StreamExt;
-use std::sync::Arc;
-use zbus::Task;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
+};
+use zbus::{blocking::Connection, Task};
+
+/// The connection and application state shared by every adapter in the
+/// process, so that all of an application's windows appear under a single
+/// application root.
+static APP_STATE: Mutex<Option<(Bus, Arc<RwLock<AppContext>>)>> = Mutex::new(None);
+
+static NEXT_ADAPTER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the shared application state, registering the application root
+/// with the desktop if no other adapter is active.
+fn app_state(
+    app_state: &mut Option<(Bus, Arc<RwLock<AppContext>>)>,
+    app_info: &AppInfo,
+) -> Option<(Bus, Arc<RwLock<AppContext>>)> {
+    if let Some((bus, app_context)) = app_state.as_ref() {
+        return Some((bus.clone(), app_context.clone()));
+    }
+    let mut bus = Bus::a11y_bus()?;
+    let app_context = Arc::new(RwLock::new(AppContext::new(
+        app_info.name.clone(),
+        app_info.toolkit_name.clone(),
+        app_info.toolkit_version.clone(),
+    )));
+    bus.register_root_node(&app_context).ok()?;
+    *app_state = Some((bus.clone(), app_context.clone()));
+    Some((bus, app_context))
+}
+
+/// How the application root describes the application.
+struct AppInfo {
+    name: String,
+    toolkit_name: String,
+    toolkit_version: String,
+}
+
+/// What an adapter needs to activate itself once an assistive technology
+/// is running.
+struct Activation {
+    app_info: AppInfo,
+    initial_state: Box<dyn FnOnce() -> TreeUpdate + Send>,
+    action_handler: Box<dyn ActionHandler + Send + Sync>,
+    root_window_bounds: WindowBounds,
+}
+
+/// Exactly one of the fields is `Some`.
+struct AdapterState {
+    activation: Option<Activation>,
+    adapter: Option<AdapterImpl>,
+}
+
+impl AdapterState {
+    /// Activates the adapter if it isn't already active. Returns whether
+    /// the adapter is active afterward.
+    fn activate(&mut self) -> bool {
+        let activation = match &self.activation {
+            Some(activation) => activation,
+            None => return true,
+        };
+        // Make sure that the accessibility bus is reachable before
+        // consuming the initial state.
+        if app_state(&mut APP_STATE.lock().unwrap(), &activation.app_info).is_none() {
+            return false;
+        }
+        let mut activation = self.activation.take().unwrap();
+        let initial_state = (activation.initial_state)();
+        // The last other adapter may have been dropped in the meantime,
+        // so the shared state is fetched again, and kept locked until
+        // this adapter is counted among its users.
+        let mut app_state_guard = APP_STATE.lock().unwrap();
+        let (atspi_bus, app_context) = match app_state(&mut app_state_guard, &activation.app_info) {
+            Some(app_state) => app_state,
+            None => {
+                activation.initial_state = Box::new(move || initial_state);
+                self.activation = Some(activation);
+                return false;
+            }
+        };
+        self.adapter = Some(AdapterImpl::new(
+            atspi_bus,
+            app_context,
+            initial_state,
+            activation.action_handler,
+            activation.root_window_bounds,
+        ));
+        true
+    }
+}
 
 pub struct Adapter {
-    atspi_bus: Bus,
-    _event_task: Task<()>,
-    events: Sender<Event>,
-    context: Arc<Context>,
+    state: Arc<Mutex<AdapterState>>,
+    _status_task: Option<Task<()>>,
 }
 
 impl Adapter {
     /// Create a new Unix adapter.
+    ///
+    /// Each window of the application should have its own adapter. The
+    /// active ones share a single application root, whose name and toolkit
+    /// are taken from the first adapter to be activated. That root
+    /// is removed once all of them have been dropped.
+    ///
+    /// The adapter stays inactive, without connecting to the accessibility
+    /// bus or building the tree, until an assistive technology is running,
+    /// as reported by the `IsEnabled` and `ScreenReaderEnabled` properties
+    /// of `org.a11y.Status` on the session bus. `initial_state` is then
+    /// called, possibly on another thread, to get the complete tree as it
+    /// is at that time, since the updates provided until then are dropped.
+    /// It must not wait on anything that is held while calling
+    /// [`Adapter::update_if_active`]. Once active, the adapter stays active.
     pub fn new(
         app_name: String,
         toolkit_name: String,
         toolkit_version: String,
-        initial_state: impl 'static + FnOnce() -> TreeUpdate,
+        initial_state: impl 'static + FnOnce() -> TreeUpdate + Send,
         action_handler: Box<dyn ActionHandler + Send + Sync>,
     ) -> Option<Self> {
-        let mut atspi_bus = Bus::a11y_bus()?;
+        let state = Arc::new(Mutex::new(AdapterState {
+            activation: Some(Activation {
+                app_info: AppInfo {
+                    name: app_name,
+                    toolkit_name,
+                    toolkit_version,
+                },
+                initial_state: Box::new(initial_state),
+                action_handler,
+                root_window_bounds: WindowBounds::default(),
+            }),
+            adapter: None,
+        }));
+        let status_task = match Connection::session() {
+            Ok(session_bus) => {
+                let session_bus = session_bus.inner();
+                let watched_bus = session_bus.clone();
+                let weak_state = Arc::downgrade(&state);
+                Some(session_bus.executor().spawn(
+                    async move {
+                        watch_status(watched_bus, weak_state).await;
+                    },
+                    "accesskit_status_task",
+                ))
+            }
+            // Without a session bus, the status can't be known, so activate
+            // right away in case the accessibility bus is reachable anyway.
+            Err(_) => {
+                if !state.lock().unwrap().activate() {
+                    return None;
+                }
+                None
+            }
+        };
+        Some(Self {
+            state,
+            _status_task: status_task,
+        })
+    }
+
+    pub fn set_root_window_bounds(&self, outer: Rect, inner: Rect) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        if let Some(activation) = &mut state.activation {
+            activation.root_window_bounds = WindowBounds { outer, inner };
+        } else if let Some(adapter) = &state.adapter {
+            adapter.set_root_window_bounds(outer, inner);
+        }
+    }
+
+    /// Apply the provided update to the tree, if the adapter is active.
+    /// While it is inactive, the update is dropped, since the tree
+    /// is built from scratch upon activation.
+    pub fn update(&self, update: TreeUpdate) {
+        if let Some(adapter) = &self.state.lock().unwrap().adapter {
+            adapter.update(update);
+        }
+    }
+
+    /// If and only if the adapter is active, call the provided function
+    /// and apply the resulting update to the tree. This avoids building
+    /// updates that nothing would consume.
+    pub fn update_if_active(&self, update_factory: impl FnOnce() -> TreeUpdate) {
+        if let Some(adapter) = &self.state.lock().unwrap().adapter {
+            adapter.update(update_factory());
+        }
+    }
+}
+
+/// Activates the adapter as soon as the accessibility status indicates
+/// that an assistive technology is running.
+async fn watch_status(session_bus: zbus::Connection, state: Weak<Mutex<AdapterState>>) {
+    let status = match a11y_status(&session_bus).await {
+        Ok(status) => status,
+        Err(_) => {
+            activate(&state);
+            return;
+        }
+    };
+    // Subscribe before reading the current values, so that no change
+    // can be missed in between.
+    let changes = status
+        .receive_property_changed::<bool>("IsEnabled")
+        .await
+        .or(status
+            .receive_property_changed::<bool>("ScreenReaderEnabled")
+            .await);
+    futures_lite::pin!(changes);
+    // If the status can't be read, an assistive technology may well be
+    // running, so err on the side of activating.
+    let is_enabled = status
+        .get_property::<bool>("IsEnabled")
+        .await
+        .unwrap_or(true)
+        || status
+            .get_property::<bool>("ScreenReaderEnabled")
+            .await
+            .unwrap_or(true);
+    if is_enabled && activate(&state) {
+        return;
+    }
+    while let Some(change) = changes.next().await {
+        if change.get().await.unwrap_or(false) && activate(&state) {
+            return;
+        }
+    }
+}
+
+/// Returns whether the adapter is active, or no longer exists.
+fn activate(state: &Weak<Mutex<AdapterState>>) -> bool {
+    match state.upgrade() {
+        Some(state) => state.lock().unwrap().activate(),
+        None => true,
+    }
+}
+
+struct AdapterImpl {
+    id: usize,
+    atspi_bus: Bus,
+    _event_task: Task<()>,
+    events: Sender<Event>,
+    context: Arc<Context>,
+}
+
+impl AdapterImpl {
+    fn new(
+        atspi_bus: Bus,
+        app_context: Arc<RwLock<AppContext>>,
+        initial_state: TreeUpdate,
+        action_handler: Box<dyn ActionHandler + Send + Sync>,
+        root_window_bounds: WindowBounds,
+    ) -> Self {
         let (event_sender, event_receiver) = async_channel::unbounded();
         let atspi_bus_copy = atspi_bus.clone();
         let event_task = atspi_bus.connection().inner().executor().spawn(
@@ -48,42 +291,52 @@ impl Adapter {
             },
             "accesskit_event_task",
         );
-        let tree = Tree::new(initial_state());
-        let app_context = AppContext::new(app_name, toolkit_name, toolkit_version);
-        let context = Context::new(tree, action_handler, app_context);
-        atspi_bus.register_root_node(&context).ok()?;
-        let adapter = Adapter {
+        let id = NEXT_ADAPTER_ID.fetch_add(1, Ordering::Relaxed);
+        let tree = Tree::new(initial_state);
+        let context = Context::new(id, tree, action_handler, &app_context);
+        *context.root_window_bounds.write().unwrap() = root_window_bounds;
+        let adapter = AdapterImpl {
+            id,
             atspi_bus,
             _event_task: event_task,
             events: event_sender,
             context,
         };
         adapter.register_tree();
-        Some(adapter)
+        let index = {
+            let mut app_context = app_context.write().unwrap();
+            app_context.push_adapter(id, &adapter.context);
+            app_context.adapters.len() - 1
+        };
+        let root_id = adapter.context.read_tree().state().root().id();
+        adapter
+            .events
+            .send_blocking(Event::Object {
+                target: ObjectId::root(),
+                event: ObjectEvent::ChildAdded(
+                    index,
+                    ObjectRef::Managed(ObjectId::node(id, root_id)),
+                ),
+            })
+            .unwrap();
+        adapter
     }
 
     fn register_tree(&self) {
         let tree = self.context.read_tree();
         let tree_state = tree.state();
-        let mut objects_to_add = Vec::new();
-
-        fn add_children(node: Node<'_>, to_add: &mut Vec<NodeId>) {
-            for child in node.filtered_children(&filter) {
-                to_add.push(child.id());
-                add_children(child, to_add);
-            }
-        }
-
-        objects_to_add.push(tree_state.root().id());
-        add_children(tree_state.root(), &mut objects_to_add);
-        for id in objects_to_add {
+        for id in included_node_ids(tree_state) {
             let interfaces = NodeWrapper::Node(&tree_state.node_by_id(id).unwrap()).interfaces();
             self.register_interfaces(id, interfaces).unwrap();
         }
     }
 
     fn register_interfaces(&self, id: NodeId, new_interfaces: InterfaceSet) -> zbus::Result<bool> {
-        let path = format!("{}{}", ACCESSIBLE_PATH_PREFIX, ObjectId::from(id).as_str());
+        let path = format!(
+            "{}{}",
+            ACCESSIBLE_PATH_PREFIX,
+            ObjectId::node(self.id, id).as_str()
+        );
         if new_interfaces.contains(Interface::Accessible) {
             self.atspi_bus.register_interface(
                 &path,
@@ -105,6 +358,54 @@ impl Adapter {
                 ComponentInterface::new(PlatformNode::new(&self.context, id)),
             )?;
         }
+        if new_interfaces.contains(Interface::Hyperlink) {
+            self.atspi_bus.register_interface(
+                &path,
+                HyperlinkInterface::new(PlatformNode::new(&self.context, id)),
+            )?;
+        }
+        if new_interfaces.contains(Interface::Hypertext) {
+            self.atspi_bus.register_interface(
+                &path,
+                HypertextInterface::new(PlatformNode::new(&self.context, id)),
+            )?;
+        }
+        if new_interfaces.contains(Interface::Selection) {
+            self.atspi_bus.register_interface(
+                &path,
+                SelectionInterface::new(PlatformNode::new(&self.context, id)),
+            )?;
+        }
+        if new_interfaces.contains(Interface::Table) {
+            self.atspi_bus.register_interface(
+                &path,
+                TableInterface::new(
+                    self.atspi_bus.unique_name().to_owned(),
+                    PlatformNode::new(&self.context, id),
+                ),
+            )?;
+        }
+        if new_interfaces.contains(Interface::TableCell) {
+            self.atspi_bus.register_interface(
+                &path,
+                TableCellInterface::new(
+                    self.atspi_bus.unique_name().to_owned(),
+                    PlatformNode::new(&self.context, id),
+                ),
+            )?;
+        }
+        if new_interfaces.contains(Interface::Text) {
+            self.atspi_bus.register_interface(
+                &path,
+                TextInterface::new(PlatformNode::new(&self.context, id)),
+            )?;
+        }
+        if new_interfaces.contains(Interface::EditableText) {
+            self.atspi_bus.register_interface(
+                &path,
+                EditableTextInterface::new(PlatformNode::new(&self.context, id)),
+            )?;
+        }
         if new_interfaces.contains(Interface::Value) {
             self.atspi_bus.register_interface(
                 &path,
@@ -132,6 +433,34 @@ impl Adapter {
             self.atspi_bus
                 .unregister_interface::<ComponentInterface>(&path)?;
         }
+        if old_interfaces.contains(Interface::Hyperlink) {
+            self.atspi_bus
+                .unregister_interface::<HyperlinkInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::Hypertext) {
+            self.atspi_bus
+                .unregister_interface::<HypertextInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::Selection) {
+            self.atspi_bus
+                .unregister_interface::<SelectionInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::Table) {
+            self.atspi_bus
+                .unregister_interface::<TableInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::TableCell) {
+            self.atspi_bus
+                .unregister_interface::<TableCellInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::Text) {
+            self.atspi_bus
+                .unregister_interface::<TextInterface>(&path)?;
+        }
+        if old_interfaces.contains(Interface::EditableText) {
+            self.atspi_bus
+                .unregister_interface::<EditableTextInterface>(&path)?;
+        }
         if old_interfaces.contains(Interface::Value) {
             self.atspi_bus
                 .unregister_interface::<ValueInterface>(&path)?;
@@ -139,18 +468,29 @@ impl Adapter {
         Ok(true)
     }
 
-    pub fn set_root_window_bounds(&self, outer: Rect, inner: Rect) {
+    fn set_root_window_bounds(&self, outer: Rect, inner: Rect) {
         let mut bounds = self.context.root_window_bounds.write().unwrap();
         bounds.outer = outer;
         bounds.inner = inner;
     }
 
-    /// Apply the provided update to the tree.
-    pub fn update(&self, update: TreeUpdate) {
+    fn update(&self, update: TreeUpdate) {
         struct Handler<'a> {
-            adapter: &'a Adapter,
+            adapter: &'a AdapterImpl,
+            live_changes: LiveRegionChanges,
+            text_changes: HashMap<NodeId, TextChanges>,
         }
         impl Handler<'_> {
+            fn add_text_box(&mut self, node: &Node) {
+                if let Some(container) = text_container(node) {
+                    if let Some(content) = TextBoxContent::new(&container, node) {
+                        self.text_changes
+                            .entry(container.id())
+                            .or_insert_with(|| TextChanges::new(&container))
+                            .set_new_content(node.id(), content);
+                    }
+                }
+            }
             fn add_node(&mut self, node: &Node) {
                 let interfaces = NodeWrapper::Node(node).interfaces();
                 self.adapter
@@ -162,47 +502,84 @@ impl Adapter {
                 self.adapter
                     .events
                     .send_blocking(Event::Object {
-                        target: node.id(),
+                        target: node.id(self.adapter.id),
                         event: ObjectEvent::StateChanged(State::Defunct, true),
                     })
                     .unwrap();
                 self.adapter
-                    .unregister_interfaces(&node.id(), node.interfaces())
+                    .unregister_interfaces(&node.id(self.adapter.id), node.interfaces())
                     .unwrap();
             }
         }
         impl TreeChangeHandler for Handler<'_> {
             fn node_added(&mut self, node: &Node) {
+                if node.role() == Role::InlineTextBox {
+                    self.add_text_box(node);
+                }
                 if filter(node) == FilterResult::Include {
                     self.add_node(node);
+                    self.live_changes.node_added(node);
                 }
             }
-            fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node) {
+            fn node_updated(
+                &mut self,
+                old_node: &DetachedNode,
+                new_node: &Node,
+                changes: &NodeChanges,
+            ) {
+                if changes.text_content {
+                    self.add_text_box(new_node);
+                } else if new_node.parent_id() != old_node.parent_id() {
+                    // A subtree that moved to a new parent was counted
+                    // as removed from its old one.
+                    let mut text_boxes = Vec::new();
+                    add_text_boxes(new_node, &mut text_boxes);
+                    for text_box in text_boxes {
+                        self.add_text_box(&text_box);
+                    }
+                }
                 let filter_old = filter_detached(old_node);
                 let filter_new = filter(new_node);
                 if filter_new != filter_old {
                     if filter_new == FilterResult::Include {
                         self.add_node(new_node);
+                        self.live_changes.node_added(new_node);
                     } else if filter_old == FilterResult::Include {
                         self.remove_node(old_node);
+                        self.live_changes.node_removed(old_node, Some(new_node));
                     }
                 } else if filter_new == FilterResult::Include {
+                    self.live_changes.node_updated(old_node, new_node);
                     let old_wrapper = NodeWrapper::DetachedNode(old_node);
                     let new_wrapper = NodeWrapper::Node(new_node);
                     let old_interfaces = old_wrapper.interfaces();
                     let new_interfaces = new_wrapper.interfaces();
                     let kept_interfaces = old_interfaces & new_interfaces;
                     self.adapter
-                        .unregister_interfaces(&new_wrapper.id(), old_interfaces ^ kept_interfaces)
+                        .unregister_interfaces(
+                            &new_wrapper.id(self.adapter.id),
+                            old_interfaces ^ kept_interfaces,
+                        )
                         .unwrap();
                     self.adapter
                         .register_interfaces(new_node.id(), new_interfaces ^ kept_interfaces)
                         .unwrap();
                     new_wrapper.notify_changes(
+                        self.adapter.id,
                         &self.adapter.context.read_root_window_bounds(),
                         &self.adapter.events,
                         &old_wrapper,
+                        changes,
                     );
+                    if new_node.is_selected() != old_node.is_selected() {
+                        notify_selection_changes(new_node, self.adapter.id, &self.adapter.events);
+                    }
+                }
+            }
+            fn reverse_relations_changed(&mut self, node: &Node) {
+                if filter(node) == FilterResult::Include {
+                    NodeWrapper::Node(node)
+                        .notify_relation_set_changes(self.adapter.id, &self.adapter.events);
                 }
             }
             fn focus_moved(
@@ -228,7 +605,7 @@ impl Adapter {
                     self.adapter
                         .events
                         .send_blocking(Event::Object {
-                            target: node.id(),
+                            target: node.id(self.adapter.id),
                             event: ObjectEvent::StateChanged(State::Focused, true),
                         })
                         .unwrap();
@@ -237,34 +614,64 @@ impl Adapter {
                     self.adapter
                         .events
                         .send_blocking(Event::Object {
-                            target: node.id(),
+                            target: node.id(self.adapter.id),
                             event: ObjectEvent::StateChanged(State::Focused, false),
                         })
                         .unwrap();
                 }
             }
-            fn node_removed(&mut self, node: &DetachedNode, _: &TreeState) {
+            fn node_removed(&mut self, node: &DetachedNode, current_state: &TreeState) {
+                let parent = node.parent_id().and_then(|id| current_state.node_by_id(id));
                 if filter_detached(node) == FilterResult::Include {
                     self.remove_node(node);
+                    self.live_changes.node_removed(node, parent.as_ref());
+                } else {
+                    self.live_changes.node_removed(node, None);
                 }
             }
+            fn filter_detached(&self, node: &DetachedNode) -> FilterResult {
+                filter_detached(node)
+            }
         }
-        let mut handler = Handler { adapter: self };
         let mut tree = self.context.tree.write().unwrap();
+        let mut handler = Handler {
+            adapter: self,
+            live_changes: LiveRegionChanges::default(),
+            text_changes: old_text_changes(tree.state(), &update),
+        };
         tree.update_and_process_changes(update, &mut handler);
+        self.context.clear_hypertexts();
+        for (id, changes) in handler.text_changes {
+            if let Some(node) = tree.state().node_by_id(id) {
+                if node.supports_text_ranges() && filter(&node) == FilterResult::Include {
+                    changes.notify(self.id, &node, &self.events);
+                }
+            }
+        }
+        for announcement in handler.live_changes.announcements(tree.state()) {
+            self.events
+                .send_blocking(Event::Object {
+                    target: ObjectId::node(self.id, announcement.target),
+                    event: ObjectEvent::Announcement {
+                        text: announcement.text,
+                        politeness: announcement.politeness,
+                    },
+                })
+                .unwrap();
+        }
     }
 
     fn window_activated(&self, window: &NodeWrapper, events: &Sender<Event>) {
         events
             .send_blocking(Event::Window {
-                target: window.id(),
+                target: window.id(self.id),
                 name: window.name(),
                 event: WindowEvent::Activated,
             })
             .unwrap();
         events
             .send_blocking(Event::Object {
-                target: window.id(),
+                target: window.id(self.id),
                 event: ObjectEvent::StateChanged(State::Active, true),
             })
             .unwrap();
@@ -273,20 +680,72 @@ impl Adapter {
     fn window_deactivated(&self, window: &NodeWrapper, events: &Sender<Event>) {
         events
             .send_blocking(Event::Window {
-                target: window.id(),
+                target: window.id(self.id),
                 name: window.name(),
                 event: WindowEvent::Deactivated,
             })
             .unwrap();
         events
             .send_blocking(Event::Object {
-                target: window.id(),
+                target: window.id(self.id),
                 event: ObjectEvent::StateChanged(State::Active, false),
             })
             .unwrap();
     }
 }
 
+impl Drop for AdapterImpl {
+    fn drop(&mut self) {
+        // The connection is shared with the application's other windows,
+        // so this window's objects must be removed from it explicitly.
+        // Once the last window is gone, the shared state is released, which
+        // closes the connection and thus removes the application root;
+        // the next adapter to be activated registers a new one.
+        let root_id = self.context.read_tree().state().root().id();
+        let events = {
+            let mut app_state = APP_STATE.lock().unwrap();
+            let mut app_context = self.context.app_context.write().unwrap();
+            let events = remove_window(&mut app_context, self.id, root_id);
+            let is_shared = match &*app_state {
+                Some((_, shared)) => Arc::ptr_eq(shared, &self.context.app_context),
+                None => false,
+            };
+            if is_shared && app_context.adapters.is_empty() {
+                *app_state = None;
+            }
+            events
+        };
+        // The event task is dropped along with the adapter, so these
+        // can't be queued like the others.
+        for event in events {
+            if let Event::Object { target, event } = event {
+                let _ = zbus::block_on(self.atspi_bus.emit_object_event(target, event));
+            }
+        }
+        let tree = self.context.read_tree();
+        let tree_state = tree.state();
+        for id in included_node_ids(tree_state) {
+            let interfaces = NodeWrapper::Node(&tree_state.node_by_id(id).unwrap()).interfaces();
+            let _ = self.unregister_interfaces(&ObjectId::node(self.id, id), interfaces);
+        }
+    }
+}
+
+/// Returns the IDs of the root and of every node that is exposed
+/// to assistive technologies.
+fn included_node_ids(tree_state: &TreeState) -> Vec<NodeId> {
+    fn add_children(node: Node<'_>, to_add: &mut Vec<NodeId>) {
+        for child in node.filtered_children(&filter) {
+            to_add.push(child.id());
+            add_children(child, to_add);
+        }
+    }
+
+    let mut ids = vec![tree_state.root().id()];
+    add_children(tree_state.root(), &mut ids);
+    ids
+}
+
 fn root_window(current_state: &TreeState) -> Option<Node> {
     const WINDOW_ROLES: &[Role] = &[Role::AlertDialog, Role::Dialog, Role::Window];
     let root = current_state.root();
@@ -297,6 +756,66 @@ fn root_window(current_state: &TreeState) -> Option<Node> {
     }
 }
 
+fn add_text_boxes<'a>(node: &Node<'a>, text_boxes: &mut Vec<Node<'a>>) {
+    if node.role() == Role::InlineTextBox {
+        text_boxes.push(*node);
+    } else {
+        for child in node.children() {
+            add_text_boxes(&child, text_boxes);
+        }
+    }
+}
+
+/// Captures, before an update is applied, the content of each inline
+/// text box that the update edits or detaches from its parent, and the
+/// text selection of each node whose text or selection may change,
+/// so that text events can be derived once the update has been applied.
+fn old_text_changes(state: &TreeState, update: &TreeUpdate) -> HashMap<NodeId, TextChanges> {
+    let mut result = HashMap::new();
+    for (id, data) in &update.nodes {
+        let node = match state.node_by_id(*id) {
+            Some(node) => node,
+            None => continue,
+        };
+        let mut text_boxes = Vec::new();
+        if node.role() == Role::InlineTextBox && node.value() != data.value() {
+            text_boxes.push(node);
+        }
+        let children_changed = !node.child_ids().eq(data.children().iter().copied());
+        if children_changed {
+            for child in node.children() {
+                if !data.children().contains(&child.id()) {
+                    add_text_boxes(&child, &mut text_boxes);
+                }
+            }
+        }
+        if !children_changed
+            && text_boxes.is_empty()
+            && node.raw_text_selection() == data.text_selection()
+        {
+            continue;
+        }
+        if let Some(container) = text_container(&node) {
+            result
+                .entry(container.id())
+                .or_insert_with(|| TextChanges::new(&container));
+        }
+        for text_box in text_boxes {
+            let container = match text_container(&text_box) {
+                Some(container) => container,
+                None => continue,
+            };
+            if let Some(content) = TextBoxContent::new(&container, &text_box) {
+                result
+                    .entry(container.id())
+                    .or_insert_with(|| TextChanges::new(&container))
+                    .set_old_content(text_box.id(), content);
+            }
+        }
+    }
+    result
+}
+
 async fn handle_events(bus: Bus, mut events: Receiver<Event>) {
     while let Some(event) = events.next().await {
         let _ = match event {
@@ -5,17 +5,22 @@
 
 use crate::{
     atspi::{interfaces::*, object_address::*, ObjectId},
-    context::Context,
+    util::AppContext,
     PlatformRootNode,
 };
+use accesskit::Live;
 use atspi::{bus::BusProxyBlocking, socket::SocketProxyBlocking, EventBody};
 use serde::Serialize;
-use std::{collections::HashMap, env::var, sync::Arc};
+use std::{
+    collections::HashMap,
+    env::var,
+    sync::{Arc, RwLock},
+};
 use zbus::{
     blocking::{Connection, ConnectionBuilder},
     names::{BusName, InterfaceName, MemberName, OwnedUniqueName},
     zvariant::{ObjectPath, Str, Value},
-    Address, Result,
+    Address, Proxy, Result,
 };
 
 #[derive(Clone)]
@@ -53,8 +58,8 @@ impl Bus {
         self.conn.object_server().remove::<T, _>(path)
     }
 
-    pub fn register_root_node(&mut self, context: &Arc<Context>) -> Result<bool> {
-        let node = PlatformRootNode::new(context);
+    pub fn register_root_node(&mut self, app_context: &Arc<RwLock<AppContext>>) -> Result<bool> {
+        let node = PlatformRootNode::new(app_context);
         let path = format!("{}{}", ACCESSIBLE_PATH_PREFIX, ObjectId::root().as_str());
         let registered = self
             .conn
@@ -69,7 +74,7 @@ impl Bus {
                 self.unique_name().as_str(),
                 ObjectPath::from_str_unchecked(ROOT_PATH),
             ))?;
-            context.app_context.write().unwrap().desktop_address = Some(desktop.into());
+            app_context.write().unwrap().desktop_address = Some(desktop.into());
             Ok(true)
         } else {
             Ok(false)
@@ -83,13 +88,53 @@ impl Bus {
     ) -> Result<()> {
         let interface = "org.a11y.atspi.Event.Object";
         let signal = match event {
+            ObjectEvent::Announcement { .. } => "Announcement",
+            ObjectEvent::AttributesChanged => "AttributesChanged",
             ObjectEvent::BoundsChanged(_) => "BoundsChanged",
+            ObjectEvent::CaretMoved(_) => "TextCaretMoved",
             ObjectEvent::ChildAdded(_, _) | ObjectEvent::ChildRemoved(_) => "ChildrenChanged",
             ObjectEvent::PropertyChanged(_) => "PropertyChange",
+            ObjectEvent::SelectionChanged => "SelectionChanged",
             ObjectEvent::StateChanged(_, _) => "StateChanged",
+            ObjectEvent::TextInserted { .. } | ObjectEvent::TextRemoved { .. } => "TextChanged",
+            ObjectEvent::TextSelectionChanged => "TextSelectionChanged",
         };
         let properties = HashMap::new();
         match event {
+            ObjectEvent::Announcement { text, politeness } => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "",
+                        detail1: match politeness {
+                            Live::Off => 0,
+                            Live::Polite => 1,
+                            Live::Assertive => 2,
+                        },
+                        detail2: 0,
+                        any_data: Str::from(text).into(),
+                        properties,
+                    },
+                )
+                .await
+            }
+            ObjectEvent::AttributesChanged => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "",
+                        detail1: 0,
+                        detail2: 0,
+                        any_data: 0i32.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
             ObjectEvent::BoundsChanged(bounds) => {
                 self.emit_event(
                     target,
@@ -105,6 +150,21 @@ impl Bus {
                 )
                 .await
             }
+            ObjectEvent::CaretMoved(offset) => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "",
+                        detail1: offset,
+                        detail2: 0,
+                        any_data: 0i32.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
             ObjectEvent::ChildAdded(index, child) => {
                 self.emit_event(
                     target,
@@ -145,6 +205,7 @@ impl Bus {
                             Property::Name(_) => "accessible-name",
                             Property::Description(_) => "accessible-description",
                             Property::Parent(_) => "accessible-parent",
+                            Property::RelationSet => "accessible-relation-set",
                             Property::Role(_) => "accessible-role",
                             Property::Value(_) => "accessible-value",
                         },
@@ -159,6 +220,7 @@ impl Bus {
                             Property::Parent(None) => {
                                 OwnedObjectAddress::root(self.unique_name().clone()).into()
                             }
+                            Property::RelationSet => 0i32.into(),
                             Property::Role(value) => Value::U32(value as u32),
                             Property::Value(value) => Value::F64(value),
                         },
@@ -167,6 +229,21 @@ impl Bus {
                 )
                 .await
             }
+            ObjectEvent::SelectionChanged => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "",
+                        detail1: 0,
+                        detail2: 0,
+                        any_data: 0i32.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
             ObjectEvent::StateChanged(state, value) => {
                 self.emit_event(
                     target,
@@ -182,6 +259,59 @@ impl Bus {
                 )
                 .await
             }
+            ObjectEvent::TextInserted {
+                start_index,
+                length,
+                content,
+            } => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "insert",
+                        detail1: start_index,
+                        detail2: length,
+                        any_data: content.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
+            ObjectEvent::TextRemoved {
+                start_index,
+                length,
+                content,
+            } => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "delete",
+                        detail1: start_index,
+                        detail2: length,
+                        any_data: content.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
+            ObjectEvent::TextSelectionChanged => {
+                self.emit_event(
+                    target,
+                    interface,
+                    signal,
+                    EventBody {
+                        kind: "",
+                        detail1: 0,
+                        detail2: 0,
+                        any_data: 0i32.into(),
+                        properties,
+                    },
+                )
+                .await
+            }
         }
     }
 
@@ -245,3 +375,16 @@ fn a11y_bus() -> Option<Connection> {
     let address: Address = address.as_str().try_into().ok()?;
     ConnectionBuilder::address(address).ok()?.build().ok()
 }
+
+/// Returns a proxy for the accessibility status that assistive technologies
+/// set on the session bus, through the `IsEnabled` and `ScreenReaderEnabled`
+/// properties, when they start.
+pub(crate) async fn a11y_status(session_bus: &zbus::Connection) -> Result<Proxy<'static>> {
+    Proxy::new(
+        session_bus,
+        "org.a11y.Bus",
+        "/org/a11y/bus",
+        "org.a11y.Status",
+    )
+    .await
+}
@@ -5,10 +5,10 @@
 
 use crate::{
     atspi::{ObjectId, ObjectRef, OwnedObjectAddress},
-    unknown_object, PlatformNode, PlatformRootNode,
+    PlatformNode, PlatformRootNode,
 };
 use atspi::{accessible::Role, Interface, InterfaceSet, StateSet};
-use std::convert::TryInto;
+use std::{collections::HashMap, convert::TryInto};
 use zbus::{fdo, names::OwnedUniqueName, MessageHeader};
 
 pub(crate) struct AccessibleInterface<T> {
@@ -93,6 +93,30 @@ impl AccessibleInterface<PlatformNode> {
         self.node.state()
     }
 
+    fn get_attributes(&self) -> fdo::Result<HashMap<&'static str, String>> {
+        self.node.attributes()
+    }
+
+    fn get_relation_set(&self) -> fdo::Result<Vec<(u32, Vec<OwnedObjectAddress>)>> {
+        Ok(self
+            .node
+            .relation_set()?
+            .into_iter()
+            .map(|(relation_type, targets)| {
+                let targets = targets
+                    .into_iter()
+                    .map(|target| match target {
+                        ObjectRef::Managed(id) => {
+                            OwnedObjectAddress::accessible(self.bus_name.clone(), id)
+                        }
+                        ObjectRef::Unmanaged(address) => address,
+                    })
+                    .collect();
+                (relation_type as u32, targets)
+            })
+            .collect())
+    }
+
     fn get_application(
         &self,
         #[zbus(header)] hdr: MessageHeader<'_>,
@@ -113,9 +137,7 @@ impl AccessibleInterface<PlatformRootNode> {
     #[dbus_interface(property)]
     fn name(&self) -> String {
         self.node
-            .context
-            .upgrade()
-            .map(|context| context.read_app_context().name.clone())
+            .resolve_app_context(|app_context| app_context.name.clone())
             .unwrap_or_default()
     }
 
@@ -127,16 +149,17 @@ impl AccessibleInterface<PlatformRootNode> {
     #[dbus_interface(property)]
     fn parent(&self) -> OwnedObjectAddress {
         self.node
-            .context
-            .upgrade()
-            .and_then(|context| context.read_app_context().desktop_address.clone())
+            .resolve_app_context(|app_context| app_context.desktop_address.clone())
+            .ok()
+            .flatten()
             .unwrap_or_else(|| OwnedObjectAddress::null(self.bus_name.clone()))
     }
 
     #[dbus_interface(property)]
     fn child_count(&self) -> i32 {
-        // TODO: Handle multiple top-level windows.
-        1
+        self.node
+            .children()
+            .map_or(0, |children| children.len() as i32)
     }
 
     #[dbus_interface(property)]
@@ -154,30 +177,22 @@ impl AccessibleInterface<PlatformRootNode> {
         #[zbus(header)] hdr: MessageHeader<'_>,
         index: i32,
     ) -> fdo::Result<(OwnedObjectAddress,)> {
-        // TODO: Handle multiple top-level windows.
-        if index != 0 {
-            return super::object_address(hdr.destination()?, None);
-        }
-        let child = self
-            .node
-            .context
-            .upgrade()
-            .map(|context| ObjectRef::Managed(context.read_tree().state().root().id().into()));
+        let child = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.node.children().ok()?.into_iter().nth(index));
         super::object_address(hdr.destination()?, child)
     }
 
     fn get_children(&self) -> fdo::Result<Vec<OwnedObjectAddress>> {
-        // TODO: Handle multiple top-level windows.
-        self.node
-            .context
-            .upgrade()
-            .map(|context| {
-                vec![OwnedObjectAddress::accessible(
-                    self.bus_name.clone(),
-                    context.read_tree().state().root().id().into(),
-                )]
+        Ok(self
+            .node
+            .children()?
+            .into_iter()
+            .map(|child| match child {
+                ObjectRef::Managed(id) => OwnedObjectAddress::accessible(self.bus_name.clone(), id),
+                ObjectRef::Unmanaged(address) => address,
             })
-            .ok_or_else(|| unknown_object(&ObjectId::root()))
+            .collect())
     }
 
     fn get_index_in_parent(&self) -> i32 {
@@ -202,6 +217,14 @@ impl AccessibleInterface<PlatformRootNode> {
         )
     }
 
+    fn get_attributes(&self) -> HashMap<&'static str, String> {
+        HashMap::new()
+    }
+
+    fn get_relation_set(&self) -> Vec<(u32, Vec<OwnedObjectAddress>)> {
+        Vec::new()
+    }
+
     fn get_interfaces(&self) -> InterfaceSet {
         InterfaceSet::new(Interface::Accessible | Interface::Application)
     }
@@ -29,8 +29,8 @@ impl ActionInterface {
         self.0.n_actions().unwrap_or(0)
     }
 
-    fn get_description(&self, _index: i32) -> &str {
-        ""
+    fn get_description(&self, index: i32) -> fdo::Result<String> {
+        self.0.get_action_description(index)
     }
 
     fn get_name(&self, index: i32) -> fdo::Result<String> {
@@ -38,11 +38,11 @@ impl ActionInterface {
     }
 
     fn get_localized_name(&self, index: i32) -> fdo::Result<String> {
-        self.0.get_action_name(index)
+        self.0.get_action_localized_name(index)
     }
 
-    fn get_key_binding(&self, _index: i32) -> &str {
-        ""
+    fn get_key_binding(&self, index: i32) -> fdo::Result<String> {
+        self.0.get_action_key_binding(index)
     }
 
     fn get_actions(&self) -> fdo::Result<Vec<Action>> {
@@ -13,18 +13,14 @@ impl ApplicationInterface {
     #[dbus_interface(property)]
     fn toolkit_name(&self) -> String {
         self.0
-            .context
-            .upgrade()
-            .map(|context| context.read_app_context().toolkit_name.clone())
+            .resolve_app_context(|app_context| app_context.toolkit_name.clone())
             .unwrap_or_default()
     }
 
     #[dbus_interface(property)]
     fn version(&self) -> String {
         self.0
-            .context
-            .upgrade()
-            .map(|context| context.read_app_context().toolkit_version.clone())
+            .resolve_app_context(|app_context| app_context.toolkit_version.clone())
             .unwrap_or_default()
     }
 
@@ -36,18 +32,18 @@ impl ApplicationInterface {
     #[dbus_interface(property)]
     fn id(&self) -> i32 {
         self.0
-            .context
-            .upgrade()
-            .and_then(|context| context.read_app_context().id)
+            .resolve_app_context(|app_context| app_context.id)
+            .ok()
+            .flatten()
             .unwrap_or(-1)
     }
 
     #[dbus_interface(property)]
     fn set_id(&mut self, id: i32) -> fdo::Result<()> {
         self.0
-            .context
+            .app_context
             .upgrade()
-            .map(|context| context.app_context.write().unwrap().id = Some(id))
+            .map(|app_context| app_context.write().unwrap().id = Some(id))
             .ok_or_else(|| unknown_object(&ObjectId::root()))
     }
 }
@@ -4,7 +4,7 @@
 // the LICENSE-MIT file), at your option.
 
 use crate::{
-    atspi::{OwnedObjectAddress, Rect},
+    atspi::{OwnedObjectAddress, Rect, ScrollType},
     PlatformNode,
 };
 use atspi::{component::Layer, CoordType};
@@ -49,7 +49,44 @@ impl ComponentInterface {
         self.node.grab_focus()
     }
 
+    fn scroll_to(&self, scroll_type: u32) -> fdo::Result<bool> {
+        let scroll_type = ScrollType::try_from(scroll_type)
+            .map_err(|_| fdo::Error::InvalidArgs("Unknown scroll type.".into()))?;
+        self.node.scroll_to(scroll_type)
+    }
+
     fn scroll_to_point(&self, coord_type: CoordType, x: i32, y: i32) -> fdo::Result<bool> {
         self.node.scroll_to_point(coord_type, x, y)
     }
+
+    // AccessKit has no way to move or resize a node, so the following
+    // three methods always report failure, as the specification allows.
+    fn set_extents(
+        &self,
+        _x: i32,
+        _y: i32,
+        _width: i32,
+        _height: i32,
+        _coord_type: CoordType,
+    ) -> bool {
+        false
+    }
+
+    fn set_position(&self, _x: i32, _y: i32, _coord_type: CoordType) -> bool {
+        false
+    }
+
+    fn set_size(&self, _width: i32, _height: i32) -> bool {
+        false
+    }
+
+    fn get_alpha(&self) -> f64 {
+        1.0
+    }
+
+    /// Returns -1, since no node is in the MDI layer.
+    #[dbus_interface(name = "GetMDIZOrder")]
+    fn get_mdi_z_order(&self) -> i16 {
+        -1
+    }
 }
@@ -0,0 +1,44 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::PlatformNode;
+use zbus::fdo;
+
+pub(crate) struct EditableTextInterface {
+    node: PlatformNode,
+}
+
+impl EditableTextInterface {
+    pub fn new(node: PlatformNode) -> Self {
+        Self { node }
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.EditableText")]
+impl EditableTextInterface {
+    fn set_text_contents(&self, new_contents: &str) -> fdo::Result<bool> {
+        self.node.set_text_contents(new_contents)
+    }
+
+    fn insert_text(&self, position: i32, text: &str, length: i32) -> fdo::Result<bool> {
+        self.node.insert_text(position, text, length)
+    }
+
+    fn copy_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<()> {
+        self.node.copy_text(start_pos, end_pos)
+    }
+
+    fn cut_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<bool> {
+        self.node.cut_text(start_pos, end_pos)
+    }
+
+    fn delete_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<bool> {
+        self.node.delete_text(start_pos, end_pos)
+    }
+
+    fn paste_text(&self, position: i32) -> fdo::Result<bool> {
+        self.node.paste_text(position)
+    }
+}
@@ -4,6 +4,7 @@
 // the LICENSE-MIT file), at your option.
 
 use crate::atspi::{ObjectId, ObjectRef, Rect};
+use accesskit::Live;
 use atspi::{accessible::Role, State};
 
 pub(crate) enum Event {
@@ -22,17 +23,36 @@ pub(crate) enum Property {
     Name(String),
     Description(String),
     Parent(Option<ObjectRef>),
+    RelationSet,
     Role(Role),
     Value(f64),
 }
 
 #[allow(clippy::enum_variant_names)]
 pub(crate) enum ObjectEvent {
+    Announcement {
+        text: String,
+        politeness: Live,
+    },
+    AttributesChanged,
     BoundsChanged(Rect),
+    CaretMoved(i32),
     ChildAdded(usize, ObjectRef),
     ChildRemoved(ObjectRef),
     PropertyChanged(Property),
+    SelectionChanged,
     StateChanged(State, bool),
+    TextInserted {
+        start_index: i32,
+        length: i32,
+        content: String,
+    },
+    TextRemoved {
+        start_index: i32,
+        length: i32,
+        content: String,
+    },
+    TextSelectionChanged,
 }
 
 pub(crate) enum WindowEvent {
@@ -0,0 +1,52 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::{atspi::OwnedObjectAddress, PlatformNode};
+use zbus::{fdo, MessageHeader};
+
+pub(crate) struct HyperlinkInterface {
+    node: PlatformNode,
+}
+
+impl HyperlinkInterface {
+    pub fn new(node: PlatformNode) -> Self {
+        Self { node }
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.Hyperlink")]
+impl HyperlinkInterface {
+    #[dbus_interface(property)]
+    fn n_anchors(&self) -> i16 {
+        self.node.hyperlink_n_anchors().unwrap_or(0)
+    }
+
+    #[dbus_interface(property)]
+    fn start_index(&self) -> i32 {
+        self.node.hyperlink_start_index().unwrap_or(-1)
+    }
+
+    #[dbus_interface(property)]
+    fn end_index(&self) -> i32 {
+        self.node.hyperlink_end_index().unwrap_or(-1)
+    }
+
+    fn get_object(
+        &self,
+        #[zbus(header)] hdr: MessageHeader<'_>,
+        i: i32,
+    ) -> fdo::Result<(OwnedObjectAddress,)> {
+        super::object_address(hdr.destination()?, self.node.hyperlink_object(i)?)
+    }
+
+    #[dbus_interface(name = "GetURI")]
+    fn get_uri(&self, i: i32) -> fdo::Result<String> {
+        self.node.hyperlink_uri(i)
+    }
+
+    fn is_valid(&self) -> fdo::Result<bool> {
+        self.node.hyperlink_is_valid()
+    }
+}
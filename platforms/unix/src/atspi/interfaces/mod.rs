@@ -7,7 +7,14 @@ mod accessible;
 mod action;
 mod application;
 mod component;
+mod editable_text;
 mod events;
+mod hyperlink;
+mod hypertext;
+mod selection;
+mod table;
+mod table_cell;
+mod text;
 mod value;
 
 use crate::atspi::{ObjectRef, OwnedObjectAddress};
@@ -38,5 +45,12 @@ pub(crate) use accessible::*;
 pub(crate) use action::*;
 pub(crate) use application::*;
 pub(crate) use component::*;
+pub(crate) use editable_text::*;
 pub(crate) use events::*;
+pub(crate) use hyperlink::*;
+pub(crate) use hypertext::*;
+pub(crate) use selection::*;
+pub(crate) use table::*;
+pub(crate) use table_cell::*;
+pub(crate) use text::*;
 pub(crate) use value::*;
@@ -0,0 +1,60 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::{atspi::OwnedObjectAddress, PlatformNode};
+use zbus::{fdo, MessageHeader};
+
+pub(crate) struct SelectionInterface {
+    node: PlatformNode,
+}
+
+impl SelectionInterface {
+    pub fn new(node: PlatformNode) -> Self {
+        Self { node }
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.Selection")]
+impl SelectionInterface {
+    #[dbus_interface(property)]
+    fn n_selected_children(&self) -> i32 {
+        self.node.n_selected_children().unwrap_or(0)
+    }
+
+    fn get_selected_child(
+        &self,
+        #[zbus(header)] hdr: MessageHeader<'_>,
+        selected_child_index: i32,
+    ) -> fdo::Result<(OwnedObjectAddress,)> {
+        super::object_address(
+            hdr.destination()?,
+            self.node.selected_child(selected_child_index)?,
+        )
+    }
+
+    fn select_child(&self, child_index: i32) -> fdo::Result<bool> {
+        self.node.select_child(child_index)
+    }
+
+    fn deselect_selected_child(&self, selected_child_index: i32) -> fdo::Result<bool> {
+        self.node.deselect_selected_child(selected_child_index)
+    }
+
+    fn is_child_selected(&self, child_index: i32) -> fdo::Result<bool> {
+        self.node.is_child_selected(child_index)
+    }
+
+    fn select_all(&self) -> fdo::Result<bool> {
+        self.node.select_all()
+    }
+
+    fn clear_selection(&self) -> fdo::Result<bool> {
+        self.node.clear_selection()
+    }
+
+    fn deselect_child(&self, child_index: i32) -> fdo::Result<bool> {
+        self.node.deselect_child(child_index)
+    }
+}
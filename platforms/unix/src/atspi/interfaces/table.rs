@@ -0,0 +1,158 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::{
+    atspi::{ObjectRef, OwnedObjectAddress},
+    PlatformNode,
+};
+use zbus::{fdo, names::OwnedUniqueName, MessageHeader};
+
+pub(crate) struct TableInterface {
+    bus_name: OwnedUniqueName,
+    node: PlatformNode,
+}
+
+impl TableInterface {
+    pub fn new(bus_name: OwnedUniqueName, node: PlatformNode) -> Self {
+        Self { bus_name, node }
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.Table")]
+impl TableInterface {
+    #[dbus_interface(property)]
+    fn n_rows(&self) -> i32 {
+        self.node.n_rows().unwrap_or(0)
+    }
+
+    #[dbus_interface(property)]
+    fn n_columns(&self) -> i32 {
+        self.node.n_columns().unwrap_or(0)
+    }
+
+    #[dbus_interface(property)]
+    fn caption(&self) -> OwnedObjectAddress {
+        match self.node.caption() {
+            Ok(Some(ObjectRef::Managed(id))) => {
+                OwnedObjectAddress::accessible(self.bus_name.clone(), id)
+            }
+            Ok(Some(ObjectRef::Unmanaged(address))) => address,
+            _ => OwnedObjectAddress::null(self.bus_name.clone()),
+        }
+    }
+
+    /// AccessKit has no notion of a table summary, which ATK deprecated
+    /// along with the HTML `summary` attribute, so this is always null.
+    #[dbus_interface(property)]
+    fn summary(&self) -> OwnedObjectAddress {
+        OwnedObjectAddress::null(self.bus_name.clone())
+    }
+
+    #[dbus_interface(property)]
+    fn n_selected_rows(&self) -> i32 {
+        self.node.n_selected_rows().unwrap_or(0)
+    }
+
+    #[dbus_interface(property)]
+    fn n_selected_columns(&self) -> i32 {
+        self.node.n_selected_columns().unwrap_or(0)
+    }
+
+    fn get_accessible_at(
+        &self,
+        #[zbus(header)] hdr: MessageHeader<'_>,
+        row: i32,
+        column: i32,
+    ) -> fdo::Result<(OwnedObjectAddress,)> {
+        super::object_address(hdr.destination()?, self.node.accessible_at(row, column)?)
+    }
+
+    fn get_index_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.node.index_at(row, column)
+    }
+
+    fn get_row_at_index(&self, index: i32) -> fdo::Result<i32> {
+        self.node.row_at_index(index)
+    }
+
+    fn get_column_at_index(&self, index: i32) -> fdo::Result<i32> {
+        self.node.column_at_index(index)
+    }
+
+    fn get_row_description(&self, row: i32) -> fdo::Result<String> {
+        self.node.row_description(row)
+    }
+
+    fn get_column_description(&self, column: i32) -> fdo::Result<String> {
+        self.node.column_description(column)
+    }
+
+    fn get_row_extent_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.node.row_extent_at(row, column)
+    }
+
+    fn get_column_extent_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.node.column_extent_at(row, column)
+    }
+
+    fn get_row_header(
+        &self,
+        #[zbus(header)] hdr: MessageHeader<'_>,
+        row: i32,
+    ) -> fdo::Result<(OwnedObjectAddress,)> {
+        super::object_address(hdr.destination()?, self.node.row_header(row)?)
+    }
+
+    fn get_column_header(
+        &self,
+        #[zbus(header)] hdr: MessageHeader<'_>,
+        column: i32,
+    ) -> fdo::Result<(OwnedObjectAddress,)> {
+        super::object_address(hdr.destination()?, self.node.column_header(column)?)
+    }
+
+    fn get_selected_rows(&self) -> fdo::Result<Vec<i32>> {
+        self.node.selected_rows()
+    }
+
+    fn get_selected_columns(&self) -> fdo::Result<Vec<i32>> {
+        self.node.selected_columns()
+    }
+
+    fn is_row_selected(&self, row: i32) -> fdo::Result<bool> {
+        self.node.is_row_selected(row)
+    }
+
+    fn is_column_selected(&self, column: i32) -> fdo::Result<bool> {
+        self.node.is_column_selected(column)
+    }
+
+    fn is_selected(&self, row: i32, column: i32) -> fdo::Result<bool> {
+        self.node.is_selected(row, column)
+    }
+
+    fn add_row_selection(&self, row: i32) -> fdo::Result<bool> {
+        self.node.add_row_selection(row)
+    }
+
+    fn add_column_selection(&self, column: i32) -> fdo::Result<bool> {
+        self.node.add_column_selection(column)
+    }
+
+    fn remove_row_selection(&self, row: i32) -> fdo::Result<bool> {
+        self.node.remove_row_selection(row)
+    }
+
+    fn remove_column_selection(&self, column: i32) -> fdo::Result<bool> {
+        self.node.remove_column_selection(column)
+    }
+
+    fn get_row_column_extents_at_index(
+        &self,
+        index: i32,
+    ) -> fdo::Result<(bool, i32, i32, i32, i32, bool)> {
+        self.node.row_column_extents_at_index(index)
+    }
+}
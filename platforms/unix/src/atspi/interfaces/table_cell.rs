@@ -0,0 +1,71 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::{
+    atspi::{ObjectRef, OwnedObjectAddress},
+    PlatformNode,
+};
+use zbus::{fdo, names::OwnedUniqueName};
+
+pub(crate) struct TableCellInterface {
+    bus_name: OwnedUniqueName,
+    node: PlatformNode,
+}
+
+impl TableCellInterface {
+    pub fn new(bus_name: OwnedUniqueName, node: PlatformNode) -> Self {
+        Self { bus_name, node }
+    }
+
+    fn object_addresses(&self, refs: Vec<ObjectRef>) -> Vec<OwnedObjectAddress> {
+        refs.into_iter()
+            .map(|object| match object {
+                ObjectRef::Managed(id) => OwnedObjectAddress::accessible(self.bus_name.clone(), id),
+                ObjectRef::Unmanaged(address) => address,
+            })
+            .collect()
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.TableCell")]
+impl TableCellInterface {
+    #[dbus_interface(property)]
+    fn column_span(&self) -> i32 {
+        self.node.cell_column_span().unwrap_or(1)
+    }
+
+    #[dbus_interface(property)]
+    fn position(&self) -> (i32, i32) {
+        self.node.cell_position().unwrap_or((-1, -1))
+    }
+
+    #[dbus_interface(property)]
+    fn row_span(&self) -> i32 {
+        self.node.cell_row_span().unwrap_or(1)
+    }
+
+    #[dbus_interface(property)]
+    fn table(&self) -> OwnedObjectAddress {
+        match self.node.cell_table() {
+            Ok(Some(ObjectRef::Managed(id))) => {
+                OwnedObjectAddress::accessible(self.bus_name.clone(), id)
+            }
+            Ok(Some(ObjectRef::Unmanaged(address))) => address,
+            _ => OwnedObjectAddress::null(self.bus_name.clone()),
+        }
+    }
+
+    fn get_row_header_cells(&self) -> fdo::Result<Vec<OwnedObjectAddress>> {
+        Ok(self.object_addresses(self.node.cell_row_header_cells()?))
+    }
+
+    fn get_column_header_cells(&self) -> fdo::Result<Vec<OwnedObjectAddress>> {
+        Ok(self.object_addresses(self.node.cell_column_header_cells()?))
+    }
+
+    fn get_row_column_span(&self) -> fdo::Result<(i32, i32, i32, i32)> {
+        self.node.cell_row_column_span()
+    }
+}
@@ -0,0 +1,101 @@
+// Copyright 2023 The AccessKit Authors. All rights reserved.
+// Licensed under the Apache License, Version 2.0 (found in
+// the LICENSE-APACHE file) or the MIT license (found in
+// the LICENSE-MIT file), at your option.
+
+use crate::{atspi::Granularity, PlatformNode};
+use atspi::CoordType;
+use std::convert::TryFrom;
+use zbus::fdo;
+
+pub(crate) struct TextInterface {
+    node: PlatformNode,
+}
+
+impl TextInterface {
+    pub fn new(node: PlatformNode) -> Self {
+        Self { node }
+    }
+}
+
+#[dbus_interface(name = "org.a11y.atspi.Text")]
+impl TextInterface {
+    #[dbus_interface(property)]
+    fn character_count(&self) -> i32 {
+        self.node.character_count().unwrap_or(0)
+    }
+
+    #[dbus_interface(property)]
+    fn caret_offset(&self) -> i32 {
+        self.node.caret_offset().unwrap_or(-1)
+    }
+
+    fn get_string_at_offset(
+        &self,
+        offset: i32,
+        granularity: u32,
+    ) -> fdo::Result<(String, i32, i32)> {
+        let granularity = Granularity::try_from(granularity)
+            .map_err(|_| fdo::Error::InvalidArgs("Unknown granularity.".into()))?;
+        self.node.string_at_offset(offset, granularity)
+    }
+
+    fn get_text(&self, start_offset: i32, end_offset: i32) -> fdo::Result<String> {
+        self.node.text(start_offset, end_offset)
+    }
+
+    fn set_caret_offset(&self, offset: i32) -> fdo::Result<bool> {
+        self.node.set_caret_offset(offset)
+    }
+
+    fn get_character_extents(
+        &self,
+        offset: i32,
+        coord_type: CoordType,
+    ) -> fdo::Result<(i32, i32, i32, i32)> {
+        let extents = self.node.character_extents(offset, coord_type)?;
+        Ok((extents.x, extents.y, extents.width, extents.height))
+    }
+
+    fn get_offset_at_point(&self, x: i32, y: i32, coord_type: CoordType) -> fdo::Result<i32> {
+        self.node.offset_at_point(x, y, coord_type)
+    }
+
+    fn get_n_selections(&self) -> fdo::Result<i32> {
+        self.node.n_selections()
+    }
+
+    fn get_selection(&self, selection_num: i32) -> fdo::Result<(i32, i32)> {
+        self.node.selection(selection_num)
+    }
+
+    fn add_selection(&self, start_offset: i32, end_offset: i32) -> fdo::Result<bool> {
+        self.node.add_selection(start_offset, end_offset)
+    }
+
+    fn remove_selection(&self, selection_num: i32) -> fdo::Result<bool> {
+        self.node.remove_selection(selection_num)
+    }
+
+    fn set_selection(
+        &self,
+        selection_num: i32,
+        start_offset: i32,
+        end_offset: i32,
+    ) -> fdo::Result<bool> {
+        self.node
+            .set_selection(selection_num, start_offset, end_offset)
+    }
+
+    fn get_range_extents(
+        &self,
+        start_offset: i32,
+        end_offset: i32,
+        coord_type: CoordType,
+    ) -> fdo::Result<(i32, i32, i32, i32)> {
+        let extents = self
+            .node
+            .range_extents(start_offset, end_offset, coord_type)?;
+        Ok((extents.x, extents.y, extents.width, extents.height))
+    }
+}
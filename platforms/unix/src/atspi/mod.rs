@@ -40,7 +40,63 @@ impl From<accesskit::Rect> for Rect {
     }
 }
 
-pub(crate) use bus::Bus;
+/// The unit of text that `GetStringAtOffset` of the Text interface
+/// should return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Granularity {
+    Char,
+    Word,
+    Sentence,
+    Line,
+    Paragraph,
+}
+
+impl TryFrom<u32> for Granularity {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Char),
+            1 => Ok(Self::Word),
+            2 => Ok(Self::Sentence),
+            3 => Ok(Self::Line),
+            4 => Ok(Self::Paragraph),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where `ScrollTo` of the Component interface should place
+/// the object within the visible area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ScrollType {
+    TopLeft,
+    BottomRight,
+    TopEdge,
+    BottomEdge,
+    LeftEdge,
+    RightEdge,
+    Anywhere,
+}
+
+impl TryFrom<u32> for ScrollType {
+    type Error = ();
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::TopLeft),
+            1 => Ok(Self::BottomRight),
+            2 => Ok(Self::TopEdge),
+            3 => Ok(Self::BottomEdge),
+            4 => Ok(Self::LeftEdge),
+            5 => Ok(Self::RightEdge),
+            6 => Ok(Self::Anywhere),
+            _ => Err(()),
+        }
+    }
+}
+
+pub(crate) use bus::{a11y_status, Bus};
 pub(crate) use object_address::*;
 pub(crate) use object_id::*;
 pub(crate) use object_ref::*;
@@ -15,13 +15,14 @@ impl<'a> ObjectId<'a> {
         ObjectId(Str::from("root"))
     }
 
-    pub(crate) fn as_str(&self) -> &str {
-        self.0.as_str()
+    /// Returns the ID of a node in the tree of the adapter with the given ID.
+    /// Node IDs are only unique within a tree, so they are qualified by
+    /// the adapter when more than one window shares the application.
+    pub(crate) fn node(adapter_id: usize, node_id: NodeId) -> ObjectId<'static> {
+        ObjectId(Str::from(format!("{}_{}", adapter_id, node_id.0)))
     }
-}
 
-impl From<NodeId> for ObjectId<'static> {
-    fn from(value: NodeId) -> Self {
-        Self(Str::from(value.0.to_string()))
+    pub(crate) fn as_str(&self) -> &str {
+        self.0.as_str()
     }
 }
@@ -4,7 +4,6 @@
 // the LICENSE-MIT file), at your option.
 
 use crate::atspi::{ObjectId, OwnedObjectAddress};
-use accesskit::NodeId;
 use zbus::{names::OwnedUniqueName, zvariant::Value};
 
 #[derive(Debug, PartialEq)]
@@ -23,12 +22,6 @@ impl<'a> ObjectRef {
     }
 }
 
-impl From<NodeId> for ObjectRef {
-    fn from(value: NodeId) -> ObjectRef {
-        ObjectRef::Managed(value.into())
-    }
-}
-
 impl From<ObjectId<'static>> for ObjectRef {
     fn from(value: ObjectId<'static>) -> ObjectRef {
         ObjectRef::Managed(value)
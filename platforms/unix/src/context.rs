@@ -3,30 +3,44 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use accesskit::ActionHandler;
-use accesskit_consumer::Tree;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use accesskit::{ActionHandler, NodeId};
+use accesskit_consumer::{Node, Tree};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock, RwLockReadGuard},
+};
 
-use crate::util::{AppContext, WindowBounds};
+use crate::{
+    node::Hypertext,
+    util::{AppContext, WindowBounds},
+};
 
 pub(crate) struct Context {
+    pub(crate) adapter_id: usize,
     pub(crate) tree: RwLock<Tree>,
     pub(crate) action_handler: Box<dyn ActionHandler + Send + Sync>,
-    pub(crate) app_context: RwLock<AppContext>,
+    pub(crate) app_context: Arc<RwLock<AppContext>>,
     pub(crate) root_window_bounds: RwLock<WindowBounds>,
+    /// The hypertext of the nodes that were queried through
+    /// the Text, Hypertext or Hyperlink interfaces since the tree
+    /// was last updated.
+    hypertexts: Mutex<HashMap<NodeId, Arc<Hypertext>>>,
 }
 
 impl Context {
     pub(crate) fn new(
+        adapter_id: usize,
         tree: Tree,
         action_handler: Box<dyn ActionHandler + Send + Sync>,
-        app_context: AppContext,
+        app_context: &Arc<RwLock<AppContext>>,
     ) -> Arc<Self> {
         Arc::new(Self {
+            adapter_id,
             tree: RwLock::new(tree),
             action_handler,
-            app_context: RwLock::new(app_context),
+            app_context: app_context.clone(),
             root_window_bounds: RwLock::new(Default::default()),
+            hypertexts: Mutex::new(HashMap::new()),
         })
     }
 
@@ -34,6 +48,21 @@ impl Context {
         self.tree.read().unwrap()
     }
 
+    pub(crate) fn hypertext(&self, node: &Node) -> Arc<Hypertext> {
+        let mut hypertexts = self.hypertexts.lock().unwrap();
+        Arc::clone(
+            hypertexts
+                .entry(node.id())
+                .or_insert_with(|| Arc::new(Hypertext::new(node))),
+        )
+    }
+
+    /// Discards the cached hypertext, which must be done whenever
+    /// the tree is updated.
+    pub(crate) fn clear_hypertexts(&self) {
+        self.hypertexts.lock().unwrap().clear();
+    }
+
     pub(crate) fn read_app_context(&self) -> RwLockReadGuard<'_, AppContext> {
         self.app_context.read().unwrap()
     }
@@ -11,24 +11,30 @@
 use crate::{
     atspi::{
         interfaces::{Action as AtspiAction, Event, ObjectEvent, Property},
-        ObjectId, ObjectRef, Rect as AtspiRect, ACCESSIBLE_PATH_PREFIX,
+        Granularity, ObjectId, ObjectRef, Rect as AtspiRect, ScrollType, ACCESSIBLE_PATH_PREFIX,
     },
     context::Context,
-    util::WindowBounds,
+    util::{AppContext, WindowBounds},
 };
 use accesskit::{
-    Action, ActionData, ActionRequest, Affine, CheckedState, DefaultActionVerb, NodeId, Point,
-    Rect, Role,
+    Action, ActionData, ActionRequest, AriaCurrent, CheckedState, CustomAction, DefaultActionVerb,
+    HasPopup, KeyCombination, KeyModifiers, Live, NodeId, Point, Rect, Role, SortDirection,
+    TextSelection,
+};
+use accesskit_consumer::{
+    DetachedNode, FilterResult, Node, NodeChanges, NodeState, Relation, Table, TableCell,
+    TextPosition, TextRange, TreeState,
 };
-use accesskit_consumer::{DetachedNode, FilterResult, Node, NodeState, TreeState};
 use async_channel::Sender;
 use atspi::{
-    accessible::Role as AtspiRole, component::Layer, CoordType, Interface, InterfaceSet, State,
-    StateSet,
+    accessible::{RelationType, Role as AtspiRole},
+    component::Layer,
+    CoordType, Interface, InterfaceSet, State, StateSet,
 };
 use std::{
+    collections::{HashMap, HashSet},
     iter::FusedIterator,
-    sync::{Arc, Weak},
+    sync::{Arc, RwLock, Weak},
 };
 use zbus::fdo;
 
@@ -61,6 +67,251 @@ pub(crate) fn filter_detached(node: &DetachedNode) -> FilterResult {
     filter_common(node.state())
 }
 
+/// Returns the GDK keysym name of a key, given its name in the form used
+/// by [`KeyCombination::key`].
+fn gtk_key_name(key: &str) -> &str {
+    match key {
+        "Space" => "space",
+        "Plus" => "plus",
+        "Backspace" => "BackSpace",
+        "Enter" => "Return",
+        "ArrowUp" => "Up",
+        "ArrowDown" => "Down",
+        "ArrowLeft" => "Left",
+        "ArrowRight" => "Right",
+        "PageUp" => "Page_Up",
+        "PageDown" => "Page_Down",
+        "CapsLock" => "Caps_Lock",
+        "NumLock" => "Num_Lock",
+        "ScrollLock" => "Scroll_Lock",
+        "PrintScreen" => "Print",
+        "ContextMenu" => "Menu",
+        "AltGraph" => "ISO_Level3_Shift",
+        "BrowserBack" => "XF86Back",
+        "BrowserForward" => "XF86Forward",
+        "BrowserRefresh" => "XF86Refresh",
+        "BrowserStop" => "XF86Stop",
+        "BrowserSearch" => "XF86Search",
+        "BrowserFavorites" => "XF86Favorites",
+        "BrowserHome" => "XF86HomePage",
+        "AudioVolumeUp" => "XF86AudioRaiseVolume",
+        "AudioVolumeDown" => "XF86AudioLowerVolume",
+        "AudioVolumeMute" => "XF86AudioMute",
+        "MediaPlayPause" => "XF86AudioPlay",
+        "MediaStop" => "XF86AudioStop",
+        "MediaTrackNext" => "XF86AudioNext",
+        "MediaTrackPrevious" => "XF86AudioPrev",
+        "Copy" => "XF86Copy",
+        "Cut" => "XF86Cut",
+        "Paste" => "XF86Paste",
+        "ZoomIn" => "XF86ZoomIn",
+        "ZoomOut" => "XF86ZoomOut",
+        "Eject" => "XF86Eject",
+        "!" => "exclam",
+        "\"" => "quotedbl",
+        "#" => "numbersign",
+        "$" => "dollar",
+        "%" => "percent",
+        "&" => "ampersand",
+        "'" => "apostrophe",
+        "(" => "parenleft",
+        ")" => "parenright",
+        "*" => "asterisk",
+        "," => "comma",
+        "-" => "minus",
+        "." => "period",
+        "/" => "slash",
+        ":" => "colon",
+        ";" => "semicolon",
+        "<" => "less",
+        "=" => "equal",
+        ">" => "greater",
+        "?" => "question",
+        "@" => "at",
+        "[" => "bracketleft",
+        "\\" => "backslash",
+        "]" => "bracketright",
+        "^" => "asciicircum",
+        "_" => "underscore",
+        "`" => "grave",
+        "{" => "braceleft",
+        "|" => "bar",
+        "}" => "braceright",
+        "~" => "asciitilde",
+        // The remaining named keys, such as "Tab", "Escape", "Delete",
+        // "Home" and "F1", have the same name as their keysym.
+        key => key,
+    }
+}
+
+fn key_combination_to_atspi(combination: &KeyCombination) -> String {
+    let KeyModifiers {
+        alt,
+        control,
+        meta,
+        shift,
+    } = combination.modifiers;
+    let mut result = String::new();
+    for (is_set, name) in [
+        (control, "<Control>"),
+        (alt, "<Alt>"),
+        (shift, "<Shift>"),
+        (meta, "<Super>"),
+    ] {
+        if is_set {
+            result.push_str(name);
+        }
+    }
+    result.push_str(gtk_key_name(&combination.key));
+    result
+}
+
+struct StandardAction {
+    action: Action,
+    is_supported: fn(&NodeState) -> bool,
+    name: &'static str,
+    localized_name: &'static str,
+    description: &'static str,
+}
+
+static STANDARD_ACTIONS: [StandardAction; 7] = [
+    StandardAction {
+        action: Action::Increment,
+        is_supported: NodeState::supports_increment,
+        name: "increment",
+        localized_name: "Increment",
+        description: "Increases the value by one step",
+    },
+    StandardAction {
+        action: Action::Decrement,
+        is_supported: NodeState::supports_decrement,
+        name: "decrement",
+        localized_name: "Decrement",
+        description: "Decreases the value by one step",
+    },
+    StandardAction {
+        action: Action::Expand,
+        is_supported: NodeState::supports_expand,
+        name: "expand",
+        localized_name: "Expand",
+        description: "Expands the element",
+    },
+    StandardAction {
+        action: Action::Collapse,
+        is_supported: NodeState::supports_collapse,
+        name: "collapse",
+        localized_name: "Collapse",
+        description: "Collapses the element",
+    },
+    StandardAction {
+        action: Action::ShowContextMenu,
+        is_supported: NodeState::supports_show_context_menu,
+        name: "showContextMenu",
+        localized_name: "Show context menu",
+        description: "Shows the context menu of the element",
+    },
+    StandardAction {
+        action: Action::ScrollIntoView,
+        is_supported: NodeState::supports_scroll_into_view,
+        name: "scrollIntoView",
+        localized_name: "Scroll into view",
+        description: "Scrolls the element into view",
+    },
+    StandardAction {
+        action: Action::Focus,
+        is_supported: NodeState::is_focusable,
+        name: "focus",
+        localized_name: "Focus",
+        description: "Moves the keyboard focus to the element",
+    },
+];
+
+/// An action exposed through the AT-SPI Action interface. The default
+/// action, if any, always comes first, followed by the other supported
+/// standard actions and finally the custom actions, so that indices
+/// remain stable as long as the set of supported actions doesn't change.
+enum NodeAction<'a> {
+    Default(DefaultActionVerb),
+    Standard(&'static StandardAction),
+    Custom(&'a CustomAction),
+}
+
+impl NodeAction<'_> {
+    fn name(&self) -> String {
+        match self {
+            Self::Default(verb) => String::from(match verb {
+                DefaultActionVerb::Click => "click",
+                DefaultActionVerb::Focus => "focus",
+                DefaultActionVerb::Check => "check",
+                DefaultActionVerb::Uncheck => "uncheck",
+                DefaultActionVerb::ClickAncestor => "clickAncestor",
+                DefaultActionVerb::Jump => "jump",
+                DefaultActionVerb::Open => "open",
+                DefaultActionVerb::Press => "press",
+                DefaultActionVerb::Select => "select",
+            }),
+            Self::Standard(action) => action.name.into(),
+            Self::Custom(action) => format!("custom:{}", action.id),
+        }
+    }
+
+    fn localized_name(&self) -> String {
+        match self {
+            Self::Default(verb) => String::from(match verb {
+                DefaultActionVerb::Click => "Click",
+                DefaultActionVerb::Focus => "Focus",
+                DefaultActionVerb::Check => "Check",
+                DefaultActionVerb::Uncheck => "Uncheck",
+                DefaultActionVerb::ClickAncestor => "Click ancestor",
+                DefaultActionVerb::Jump => "Jump",
+                DefaultActionVerb::Open => "Open",
+                DefaultActionVerb::Press => "Press",
+                DefaultActionVerb::Select => "Select",
+            }),
+            Self::Standard(action) => action.localized_name.into(),
+            Self::Custom(action) => action.description.to_string(),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            Self::Default(verb) => String::from(match verb {
+                DefaultActionVerb::Click => "Clicks the element",
+                DefaultActionVerb::Focus => "Moves the keyboard focus to the element",
+                DefaultActionVerb::Check => "Checks the element",
+                DefaultActionVerb::Uncheck => "Unchecks the element",
+                DefaultActionVerb::ClickAncestor => "Clicks the nearest clickable ancestor",
+                DefaultActionVerb::Jump => "Jumps to the target of the element",
+                DefaultActionVerb::Open => "Opens the element",
+                DefaultActionVerb::Press => "Presses the element",
+                DefaultActionVerb::Select => "Selects the element",
+            }),
+            Self::Standard(action) => action.description.into(),
+            Self::Custom(action) => action.description.to_string(),
+        }
+    }
+
+    fn request(&self, target: NodeId) -> ActionRequest {
+        match self {
+            Self::Default(_) => ActionRequest {
+                action: Action::Default,
+                target,
+                data: None,
+            },
+            Self::Standard(action) => ActionRequest {
+                action: action.action,
+                target,
+                data: None,
+            },
+            Self::Custom(action) => ActionRequest {
+                action: Action::CustomAction,
+                target,
+                data: Some(ActionData::CustomAction(action.id)),
+            },
+        }
+    }
+}
+
 pub(crate) enum NodeWrapper<'a> {
     Node(&'a Node<'a>),
     DetachedNode(&'a DetachedNode),
@@ -83,24 +334,38 @@ impl<'a> NodeWrapper<'a> {
     }
 
     pub fn description(&self) -> String {
-        String::new()
+        match self {
+            Self::Node(node) => node.description(),
+            Self::DetachedNode(node) => node.description(),
+        }
+        .unwrap_or_default()
+    }
+
+    /// Equivalent to calling [`NodeWrapper::name`] and
+    /// [`NodeWrapper::description`], but only computes the name once.
+    fn name_and_description(&self) -> (String, String) {
+        let (name, description) = match self {
+            Self::Node(node) => node.name_and_description(),
+            Self::DetachedNode(node) => (node.name(), node.description()),
+        };
+        (name.unwrap_or_default(), description.unwrap_or_default())
     }
 
     pub fn parent_id(&self) -> Option<NodeId> {
         self.node_state().parent_id()
     }
 
-    pub fn filtered_parent(&self) -> Option<ObjectRef> {
+    pub fn filtered_parent(&self, adapter_id: usize) -> Option<ObjectRef> {
         match self {
             Self::Node(node) => node
                 .filtered_parent(&filter)
-                .map(|parent| parent.id().into()),
+                .map(|parent| ObjectRef::Managed(ObjectId::node(adapter_id, parent.id()))),
             _ => unreachable!(),
         }
     }
 
-    pub fn id(&self) -> ObjectId<'static> {
-        self.node_state().id().into()
+    pub fn id(&self, adapter_id: usize) -> ObjectId<'static> {
+        ObjectId::node(adapter_id, self.node_state().id())
     }
 
     fn child_ids(
@@ -357,6 +622,9 @@ impl<'a> NodeWrapper<'a> {
         if atspi_role != AtspiRole::ToggleButton && state.checked_state().is_some() {
             atspi_state.insert(State::Checkable);
         }
+        if state.is_multiselectable() {
+            atspi_state.insert(State::Multiselectable);
+        }
         if let Some(selected) = state.is_selected() {
             if !state.is_disabled() {
                 atspi_state.insert(State::Selectable);
@@ -365,6 +633,9 @@ impl<'a> NodeWrapper<'a> {
                 atspi_state.insert(State::Selected);
             }
         }
+        if self.supports_editable_text() {
+            atspi_state.insert(State::Editable);
+        }
         if state.is_text_field() {
             atspi_state.insert(State::SelectableText);
             atspi_state.insert(match state.is_multiline() {
@@ -408,49 +679,115 @@ impl<'a> NodeWrapper<'a> {
         }
     }
 
+    fn supports_text_ranges(&self) -> bool {
+        match self {
+            Self::Node(node) => node.supports_text_ranges(),
+            Self::DetachedNode(node) => node.supports_text_ranges(),
+        }
+    }
+
+    fn supports_selection(&self) -> bool {
+        matches!(
+            self.node_state().role(),
+            Role::ListBox
+                | Role::TabList
+                | Role::Tree
+                | Role::TreeGrid
+                | Role::Grid
+                | Role::ListGrid
+                | Role::Menu
+                | Role::MenuBar
+                | Role::RadioGroup
+        )
+    }
+
+    fn supports_editable_text(&self) -> bool {
+        let state = self.node_state();
+        self.supports_text_ranges()
+            && (state.is_editable() || state.is_text_field())
+            && !state.is_read_only_or_disabled()
+            && (state.supports_set_value() || state.supports_replace_selected_text())
+    }
+
     pub fn interfaces(&self) -> InterfaceSet {
         let state = self.node_state();
         let mut interfaces = InterfaceSet::new(Interface::Accessible);
-        if state.default_action_verb().is_some() {
+        if !self.actions().is_empty() {
             interfaces.insert(Interface::Action);
         }
         if state.raw_bounds().is_some() || self.is_root() {
             interfaces.insert(Interface::Component);
         }
+        if state.is_table() {
+            interfaces.insert(Interface::Table);
+        }
+        if state.is_table_cell() {
+            interfaces.insert(Interface::TableCell);
+        }
+        if self.supports_text_ranges() {
+            interfaces.insert(Interface::Text);
+        }
+        if self.supports_editable_text() {
+            interfaces.insert(Interface::EditableText);
+        }
+        if self.supports_text_ranges() {
+            interfaces.insert(Interface::Hypertext);
+        }
+        if state.role() == Role::Link {
+            interfaces.insert(Interface::Hyperlink);
+        }
+        if self.supports_selection() {
+            interfaces.insert(Interface::Selection);
+        }
         if self.current_value().is_some() {
             interfaces.insert(Interface::Value);
         }
         interfaces
     }
 
-    fn n_actions(&self) -> i32 {
-        match self.node_state().default_action_verb() {
-            Some(_) => 1,
-            None => 0,
+    fn actions(&self) -> Vec<NodeAction<'_>> {
+        let state = self.node_state();
+        let default_verb = state.default_action_verb();
+        let mut actions = Vec::new();
+        if let Some(verb) = default_verb {
+            actions.push(NodeAction::Default(verb));
+        }
+        for action in &STANDARD_ACTIONS {
+            if action.action == Action::Focus && default_verb == Some(DefaultActionVerb::Focus) {
+                continue;
+            }
+            if (action.is_supported)(state) {
+                actions.push(NodeAction::Standard(action));
+            }
         }
+        actions.extend(state.custom_actions().iter().map(NodeAction::Custom));
+        actions
     }
 
-    fn get_action_name(&self, index: i32) -> String {
-        if index != 0 {
+    /// The key binding of the given action, in the "mnemonic;sequence;shortcut"
+    /// form that ATK established. Keyboard shortcuts always activate
+    /// the default action, so the other actions have no key binding.
+    fn action_key_binding(&self, action: &NodeAction<'_>) -> String {
+        if !matches!(action, NodeAction::Default(_)) {
             return String::new();
         }
-        String::from(match self.node_state().default_action_verb() {
-            Some(DefaultActionVerb::Click) => "click",
-            Some(DefaultActionVerb::Focus) => "focus",
-            Some(DefaultActionVerb::Check) => "check",
-            Some(DefaultActionVerb::Uncheck) => "uncheck",
-            Some(DefaultActionVerb::ClickAncestor) => "clickAncestor",
-            Some(DefaultActionVerb::Jump) => "jump",
-            Some(DefaultActionVerb::Open) => "open",
-            Some(DefaultActionVerb::Press) => "press",
-            Some(DefaultActionVerb::Select) => "select",
-            None => "",
-        })
-    }
-
-    fn raw_bounds_and_transform(&self) -> (Option<Rect>, Affine) {
         let state = self.node_state();
-        (state.raw_bounds(), state.direct_transform())
+        let shortcuts = state.key_shortcuts();
+        let mnemonic = shortcuts
+            .and_then(|shortcuts| shortcuts.mnemonic.as_ref())
+            .map(key_combination_to_atspi)
+            .or_else(|| state.access_key().map(|key| format!("<Alt>{}", key)));
+        let shortcut = shortcuts
+            .and_then(|shortcuts| shortcuts.accelerators.first())
+            .map(key_combination_to_atspi);
+        if mnemonic.is_none() && shortcut.is_none() {
+            return String::new();
+        }
+        format!(
+            "{};;{}",
+            mnemonic.unwrap_or_default(),
+            shortcut.unwrap_or_default()
+        )
     }
 
     fn extents(&self, window_bounds: &WindowBounds) -> AtspiRect {
@@ -478,65 +815,193 @@ impl<'a> NodeWrapper<'a> {
         self.node_state().numeric_value()
     }
 
+    fn layer(&self) -> Layer {
+        if self.is_root() {
+            return Layer::Window;
+        }
+        match self.node_state().role() {
+            Role::Window => Layer::Window,
+            Role::Menu | Role::MenuListPopup | Role::Tooltip => Layer::Popup,
+            _ => Layer::Widget,
+        }
+    }
+
+    /// Returns the AT-SPI object attributes of this node, following
+    /// the names used by Chromium and Firefox.
+    pub fn attributes(&self) -> HashMap<&'static str, String> {
+        let state = self.node_state();
+        let mut attributes = HashMap::new();
+        if let Some(level) = state.hierarchical_level() {
+            attributes.insert("level", level.to_string());
+        }
+        if let Some(size) = state.size_of_set() {
+            attributes.insert("setsize", size.to_string());
+        }
+        if let Some(position) = state.position_in_set() {
+            attributes.insert("posinset", position.to_string());
+        }
+        if let Some(role) = state.aria_role() {
+            attributes.insert("xml-roles", role.into());
+        }
+        if let Some(live) = state.explicit_live() {
+            attributes.insert("live", live_name(live).into());
+        }
+        // "container-live" is deliberately not exposed: Orca speaks
+        // the changes within such containers on its own, which would
+        // duplicate our announcements.
+        if state.is_live_atomic() {
+            attributes.insert("atomic", "true".into());
+        }
+        if let Some(relevant) = state.live_relevant() {
+            attributes.insert("relevant", relevant.into());
+        }
+        if state.is_busy() {
+            attributes.insert("busy", "true".into());
+        }
+        if let Some(has_popup) = state.has_popup() {
+            let value = match has_popup {
+                HasPopup::True => "true",
+                HasPopup::Menu => "menu",
+                HasPopup::Listbox => "listbox",
+                HasPopup::Tree => "tree",
+                HasPopup::Grid => "grid",
+                HasPopup::Dialog => "dialog",
+            };
+            attributes.insert("haspopup", value.into());
+        }
+        if let Some(description) = state.role_description() {
+            attributes.insert("roledescription", description.into());
+        }
+        if let Some(placeholder) = state.placeholder() {
+            attributes.insert("placeholder-text", placeholder.into());
+        }
+        if let Some(shortcuts) = state
+            .key_shortcuts()
+            .filter(|shortcuts| !shortcuts.accelerators.is_empty())
+        {
+            attributes.insert("keyshortcuts", shortcuts.to_string());
+        }
+        let sort = match state.sort_direction() {
+            Some(SortDirection::Ascending) => Some("ascending"),
+            Some(SortDirection::Descending) => Some("descending"),
+            Some(SortDirection::Other) => Some("other"),
+            Some(SortDirection::Unsorted) | None => None,
+        };
+        if let Some(sort) = sort {
+            attributes.insert("sort", sort.into());
+        }
+        let current = match state.aria_current() {
+            Some(AriaCurrent::True) => Some("true"),
+            Some(AriaCurrent::Page) => Some("page"),
+            Some(AriaCurrent::Step) => Some("step"),
+            Some(AriaCurrent::Location) => Some("location"),
+            Some(AriaCurrent::Date) => Some("date"),
+            Some(AriaCurrent::Time) => Some("time"),
+            Some(AriaCurrent::False) | None => None,
+        };
+        if let Some(current) = current {
+            attributes.insert("current", current.into());
+        }
+        // AT-SPI row and column indices are 1-based, like their ARIA
+        // counterparts, while AccessKit's are 0-based.
+        if let Some(index) = state.table_cell_column_index() {
+            attributes.insert("colindex", (index + 1).to_string());
+        }
+        if let Some(index) = state
+            .table_cell_row_index()
+            .or_else(|| state.table_row_index())
+        {
+            attributes.insert("rowindex", (index + 1).to_string());
+        }
+        if let Some(class_name) = state.class_name() {
+            attributes.insert("class", class_name.into());
+        }
+        attributes
+    }
+
     pub fn notify_changes(
         &self,
+        adapter_id: usize,
         window_bounds: &WindowBounds,
         events: &Sender<Event>,
         old: &NodeWrapper,
+        changes: &NodeChanges,
     ) {
-        self.notify_state_changes(events, old);
-        self.notify_property_changes(events, old);
-        self.notify_bounds_changes(window_bounds, events, old);
-        self.notify_children_changes(events, old);
+        self.notify_state_changes(adapter_id, events, old);
+        self.notify_property_changes(adapter_id, events, old, changes);
+        if changes.data.bounds() || changes.data.transform() {
+            self.notify_bounds_changes(adapter_id, window_bounds, events);
+        }
+        self.notify_children_changes(adapter_id, events, old);
     }
 
-    fn notify_state_changes(&self, events: &Sender<Event>, old: &NodeWrapper) {
+    fn notify_state_changes(&self, adapter_id: usize, events: &Sender<Event>, old: &NodeWrapper) {
         let old_state = old.state();
         let new_state = self.state();
         let changed_states = old_state ^ new_state;
         for state in changed_states.iter() {
             events
                 .send_blocking(Event::Object {
-                    target: self.id(),
+                    target: self.id(adapter_id),
                     event: ObjectEvent::StateChanged(state, new_state.contains(state)),
                 })
                 .unwrap();
         }
     }
 
-    fn notify_property_changes(&self, events: &Sender<Event>, old: &NodeWrapper) {
-        let name = self.name();
+    fn notify_property_changes(
+        &self,
+        adapter_id: usize,
+        events: &Sender<Event>,
+        old: &NodeWrapper,
+        changes: &NodeChanges,
+    ) {
+        let (name, description) = self.name_and_description();
         if name != old.name() {
             events
                 .send_blocking(Event::Object {
-                    target: self.id(),
+                    target: self.id(adapter_id),
                     event: ObjectEvent::PropertyChanged(Property::Name(name)),
                 })
                 .unwrap();
         }
-        let description = self.description();
         if description != old.description() {
             events
                 .send_blocking(Event::Object {
-                    target: self.id(),
+                    target: self.id(adapter_id),
                     event: ObjectEvent::PropertyChanged(Property::Description(description)),
                 })
                 .unwrap();
         }
-        let parent_id = self.parent_id();
-        if parent_id != old.parent_id() {
+        if changes.parent_and_index && self.parent_id() != old.parent_id() {
             events
                 .send_blocking(Event::Object {
-                    target: self.id(),
-                    event: ObjectEvent::PropertyChanged(Property::Parent(self.filtered_parent())),
+                    target: self.id(adapter_id),
+                    event: ObjectEvent::PropertyChanged(Property::Parent(
+                        self.filtered_parent(adapter_id),
+                    )),
                 })
                 .unwrap();
         }
-        let role = self.role();
-        if role != old.role() {
+        if RELATION_TYPES
+            .iter()
+            .any(|(relation, _, _)| relation.is_changed(&changes.data))
+        {
+            self.notify_relation_set_changes(adapter_id, events);
+        }
+        if attributes_may_have_changed(changes) && self.attributes() != old.attributes() {
+            events
+                .send_blocking(Event::Object {
+                    target: self.id(adapter_id),
+                    event: ObjectEvent::AttributesChanged,
+                })
+                .unwrap();
+        }
+        if changes.data.role() {
+            let role = self.role();
             events
                 .send_blocking(Event::Object {
-                    target: self.id(),
+                    target: self.id(adapter_id),
                     event: ObjectEvent::PropertyChanged(Property::Role(role)),
                 })
                 .unwrap();
@@ -545,7 +1010,7 @@ impl<'a> NodeWrapper<'a> {
             if Some(value) != old.current_value() {
                 events
                     .send_blocking(Event::Object {
-                        target: self.id(),
+                        target: self.id(adapter_id),
                         event: ObjectEvent::PropertyChanged(Property::Value(value)),
                     })
                     .unwrap();
@@ -553,31 +1018,46 @@ impl<'a> NodeWrapper<'a> {
         }
     }
 
+    pub fn notify_relation_set_changes(&self, adapter_id: usize, events: &Sender<Event>) {
+        events
+            .send_blocking(Event::Object {
+                target: self.id(adapter_id),
+                event: ObjectEvent::PropertyChanged(Property::RelationSet),
+            })
+            .unwrap();
+    }
+
     fn notify_bounds_changes(
         &self,
+        adapter_id: usize,
         window_bounds: &WindowBounds,
         events: &Sender<Event>,
-        old: &NodeWrapper,
     ) {
-        if self.raw_bounds_and_transform() != old.raw_bounds_and_transform() {
-            events
-                .send_blocking(Event::Object {
-                    target: self.id(),
-                    event: ObjectEvent::BoundsChanged(self.extents(window_bounds)),
-                })
-                .unwrap();
-        }
+        events
+            .send_blocking(Event::Object {
+                target: self.id(adapter_id),
+                event: ObjectEvent::BoundsChanged(self.extents(window_bounds)),
+            })
+            .unwrap();
     }
 
-    fn notify_children_changes(&self, events: &Sender<Event>, old: &NodeWrapper) {
+    fn notify_children_changes(
+        &self,
+        adapter_id: usize,
+        events: &Sender<Event>,
+        old: &NodeWrapper,
+    ) {
         let old_children = old.child_ids().collect::<Vec<NodeId>>();
         let filtered_children = self.filtered_child_ids().collect::<Vec<NodeId>>();
         for (index, child) in filtered_children.iter().enumerate() {
             if !old_children.contains(child) {
                 events
                     .send_blocking(Event::Object {
-                        target: self.id(),
-                        event: ObjectEvent::ChildAdded(index, ObjectRef::from(*child)),
+                        target: self.id(adapter_id),
+                        event: ObjectEvent::ChildAdded(
+                            index,
+                            ObjectRef::Managed(ObjectId::node(adapter_id, *child)),
+                        ),
                     })
                     .unwrap();
             }
@@ -586,8 +1066,10 @@ impl<'a> NodeWrapper<'a> {
             if !filtered_children.contains(&child) {
                 events
                     .send_blocking(Event::Object {
-                        target: self.id(),
-                        event: ObjectEvent::ChildRemoved(child.into()),
+                        target: self.id(adapter_id),
+                        event: ObjectEvent::ChildRemoved(ObjectRef::Managed(ObjectId::node(
+                            adapter_id, child,
+                        ))),
                     })
                     .unwrap();
             }
@@ -595,6 +1077,458 @@ impl<'a> NodeWrapper<'a> {
     }
 }
 
+/// A kind of change within a live region, as selected by the
+/// region's relevant setting.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LiveChange {
+    Additions,
+    Removals,
+    Text,
+}
+
+impl LiveChange {
+    fn token(self) -> &'static str {
+        match self {
+            Self::Additions => "additions",
+            Self::Removals => "removals",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// A message to be announced for the changes within a live region.
+pub(crate) struct LiveAnnouncement {
+    pub(crate) target: NodeId,
+    pub(crate) text: String,
+    pub(crate) politeness: Live,
+}
+
+/// The changes within a single live region during a tree update.
+struct LiveRegionUpdate {
+    root: NodeId,
+    /// A node whose whole text is announced instead of the individual
+    /// changes, because it's atomic or the region just stopped being busy.
+    whole: Option<NodeId>,
+    /// The changed nodes and their text, in the order they were reported.
+    changes: Vec<(NodeId, String)>,
+}
+
+/// Collects the changes to live regions during a tree update, so that
+/// each region is announced at most once, with the text of all of its
+/// changes combined.
+#[derive(Default)]
+pub(crate) struct LiveRegionChanges {
+    regions: Vec<LiveRegionUpdate>,
+    removed_parents: HashMap<NodeId, NodeId>,
+}
+
+impl LiveRegionChanges {
+    /// Returns the region containing `node` if a change of the given kind
+    /// should be announced there, along with the nearest atomic node
+    /// between `node` and the region's root.
+    fn region(
+        &mut self,
+        node: &Node,
+        change: LiveChange,
+    ) -> Option<(&mut LiveRegionUpdate, Option<NodeId>)> {
+        if node.live() == Live::Off {
+            return None;
+        }
+        let mut relevant = None;
+        let mut atomic = None;
+        let mut current = *node;
+        loop {
+            if current.is_busy() {
+                return None;
+            }
+            if relevant.is_none() {
+                relevant = current.state().live_relevant();
+            }
+            if atomic.is_none() && current.is_live_atomic() {
+                atomic = Some(current.id());
+            }
+            if current.explicit_live().is_some() {
+                break;
+            }
+            current = current.parent()?;
+        }
+        let relevant = relevant.unwrap_or("additions text");
+        if !relevant
+            .split_ascii_whitespace()
+            .any(|token| token == "all" || token == change.token())
+        {
+            return None;
+        }
+        let root = current.id();
+        let index = match self.regions.iter().position(|region| region.root == root) {
+            Some(index) => index,
+            None => {
+                self.regions.push(LiveRegionUpdate {
+                    root,
+                    whole: None,
+                    changes: Vec::new(),
+                });
+                self.regions.len() - 1
+            }
+        };
+        Some((&mut self.regions[index], atomic))
+    }
+
+    /// Records a change to the node with the given ID within the region
+    /// containing `node`, which for removals is the nearest ancestor of
+    /// the removed node that is still in the tree. `text` is only called
+    /// if the change is relevant to the region and not covered by an
+    /// atomic ancestor.
+    fn add(
+        &mut self,
+        node: &Node,
+        id: NodeId,
+        change: LiveChange,
+        text: impl FnOnce() -> Option<String>,
+    ) {
+        let (region, atomic) = match self.region(node, change) {
+            Some(region) => region,
+            None => return,
+        };
+        if let Some(atomic) = atomic {
+            region.whole = match region.whole {
+                Some(whole) if whole != atomic => Some(region.root),
+                _ => Some(atomic),
+            };
+        } else if region.whole.is_none() {
+            if let Some(text) = text().filter(|text| !text.is_empty()) {
+                region.changes.push((id, text));
+            }
+        }
+    }
+
+    pub(crate) fn node_added(&mut self, node: &Node) {
+        self.add(node, node.id(), LiveChange::Additions, || node.name());
+    }
+
+    pub(crate) fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node) {
+        self.add(new_node, new_node.id(), LiveChange::Text, || {
+            new_node
+                .name()
+                .filter(|name| Some(name) != old_node.name().as_ref())
+        });
+        if old_node.is_busy() && !new_node.is_busy() {
+            // Changes made while the region was busy weren't
+            // announced, so announce its final content.
+            if let Some((region, _)) = self.region(new_node, LiveChange::Additions) {
+                region.whole = Some(region.root);
+            }
+        }
+    }
+
+    /// Records a node that is no longer exposed, either because it was
+    /// removed or because it's now filtered out. `parent` is the nearest
+    /// ancestor that is still in the tree.
+    pub(crate) fn node_removed(&mut self, node: &DetachedNode, parent: Option<&Node>) {
+        if let Some(parent_id) = node.parent_id() {
+            self.removed_parents.insert(node.id(), parent_id);
+        }
+        if let Some(parent) = parent {
+            self.add(parent, node.id(), LiveChange::Removals, || node.name());
+        }
+    }
+
+    fn parent_id(&self, tree_state: &TreeState, id: NodeId) -> Option<NodeId> {
+        match tree_state.node_by_id(id) {
+            Some(node) => node.parent_id(),
+            None => self.removed_parents.get(&id).copied(),
+        }
+    }
+
+    /// Returns one announcement for each live region that changed.
+    pub(crate) fn announcements(self, tree_state: &TreeState) -> Vec<LiveAnnouncement> {
+        let mut announcements = Vec::new();
+        for region in &self.regions {
+            let root = match tree_state.node_by_id(region.root) {
+                Some(root) => root,
+                None => continue,
+            };
+            let politeness = root.live();
+            if politeness == Live::Off {
+                continue;
+            }
+            let text = match region.whole {
+                Some(id) => tree_state
+                    .node_by_id(id)
+                    .map(|node| live_region_text(&node))
+                    .unwrap_or_default(),
+                None => {
+                    // The name of a node that contains another changed node
+                    // is usually computed from that node's text, so only
+                    // the innermost changes are announced.
+                    let mut ancestors = HashSet::new();
+                    for (id, _) in &region.changes {
+                        let mut current = *id;
+                        while current != region.root {
+                            match self.parent_id(tree_state, current) {
+                                Some(parent_id) => {
+                                    ancestors.insert(parent_id);
+                                    current = parent_id;
+                                }
+                                None => break,
+                            }
+                        }
+                    }
+                    let mut changes = region
+                        .changes
+                        .iter()
+                        .filter(|(id, _)| !ancestors.contains(id))
+                        .map(|(id, text)| {
+                            let path = tree_state.node_by_id(*id).map(|node| node.index_path());
+                            (path.is_none(), path, text.as_str())
+                        })
+                        .collect::<Vec<_>>();
+                    // Removed nodes were reported in tree order and come last.
+                    changes.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+                    changes
+                        .into_iter()
+                        .map(|(_, _, text)| text)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }
+            };
+            if text.is_empty() {
+                continue;
+            }
+            let target = if filter(&root) == FilterResult::Include {
+                root.id()
+            } else if let Some(parent) = root.filtered_parent(&filter) {
+                parent.id()
+            } else {
+                continue;
+            };
+            announcements.push(LiveAnnouncement {
+                target,
+                text,
+                politeness,
+            });
+        }
+        announcements
+    }
+}
+
+/// Returns the text of a live region, from its own name or else from
+/// the names of its descendants.
+pub(crate) fn live_region_text(node: &Node) -> String {
+    if let Some(name) = node.name() {
+        return name;
+    }
+    node.filtered_children(&filter)
+        .map(|child| live_region_text(&child))
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Returns the nearest node, starting with the given one, that supports
+/// text ranges and so owns the text of any inline text boxes within it.
+pub(crate) fn text_container<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut current = Some(*node);
+    while let Some(node) = current {
+        if node.supports_text_ranges() {
+            return Some(node);
+        }
+        current = node.parent();
+    }
+    None
+}
+
+/// The text of an inline text box and the offset at which it starts
+/// within the text of its container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TextBoxContent {
+    offset: usize,
+    text: String,
+}
+
+impl TextBoxContent {
+    pub(crate) fn new(container: &Node, text_box: &Node) -> Option<Self> {
+        let range = container.text_range_for_descendant(text_box)?;
+        Some(Self {
+            offset: range.start().to_global_usv_index(),
+            text: text_box.value().unwrap_or_default().into(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TextEdit {
+    Removed { offset: usize, text: String },
+    Inserted { offset: usize, text: String },
+}
+
+/// Returns the edits that turn the old text of a container into its new
+/// text, given the old and new content of each inline text box that
+/// changed within it. Removals come first, from the end of the text
+/// backward, so that their offsets refer to the old text; insertions
+/// follow from the start forward, so that their offsets refer to
+/// the new text.
+pub(crate) fn text_edits<'a>(
+    boxes: impl IntoIterator<Item = &'a (Option<TextBoxContent>, Option<TextBoxContent>)>,
+) -> Vec<TextEdit> {
+    let mut removals = Vec::new();
+    let mut insertions = Vec::new();
+    for (old, new) in boxes {
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                let old_text = old.text.chars().collect::<Vec<_>>();
+                let new_text = new.text.chars().collect::<Vec<_>>();
+                let prefix_len = old_text
+                    .iter()
+                    .zip(&new_text)
+                    .take_while(|(old, new)| old == new)
+                    .count();
+                let suffix_len = old_text[prefix_len..]
+                    .iter()
+                    .rev()
+                    .zip(new_text[prefix_len..].iter().rev())
+                    .take_while(|(old, new)| old == new)
+                    .count();
+                let removed = &old_text[prefix_len..old_text.len() - suffix_len];
+                if !removed.is_empty() {
+                    removals.push((old.offset + prefix_len, removed.iter().collect()));
+                }
+                let inserted = &new_text[prefix_len..new_text.len() - suffix_len];
+                if !inserted.is_empty() {
+                    insertions.push((new.offset + prefix_len, inserted.iter().collect()));
+                }
+            }
+            (Some(old), None) if !old.text.is_empty() => {
+                removals.push((old.offset, old.text.clone()));
+            }
+            (None, Some(new)) if !new.text.is_empty() => {
+                insertions.push((new.offset, new.text.clone()));
+            }
+            _ => (),
+        }
+    }
+    removals.sort_by(|(a, _), (b, _)| b.cmp(a));
+    insertions.sort_by_key(|(offset, _)| *offset);
+    removals
+        .into_iter()
+        .map(|(offset, text)| TextEdit::Removed { offset, text })
+        .chain(
+            insertions
+                .into_iter()
+                .map(|(offset, text)| TextEdit::Inserted { offset, text }),
+        )
+        .collect()
+}
+
+/// The changes to the text and text selection of a node that supports
+/// text ranges. The old state is captured before an update, only for
+/// the inline text boxes that the update edits or detaches; the new
+/// state of each changed inline text box is added as the update
+/// is processed.
+pub(crate) struct TextChanges {
+    boxes: HashMap<NodeId, (Option<TextBoxContent>, Option<TextBoxContent>)>,
+    old_hypertext: Hypertext,
+    old_selection: Option<(usize, usize)>,
+    old_caret_offset: Option<usize>,
+}
+
+impl TextChanges {
+    pub(crate) fn new(container: &Node) -> Self {
+        let old_hypertext = Hypertext::new(container);
+        Self {
+            boxes: HashMap::new(),
+            old_selection: text_selection_offsets(container, &old_hypertext),
+            old_caret_offset: caret_offset(container, &old_hypertext),
+            old_hypertext,
+        }
+    }
+
+    pub(crate) fn set_old_content(&mut self, id: NodeId, content: TextBoxContent) {
+        self.boxes.entry(id).or_default().0 = Some(content);
+    }
+
+    pub(crate) fn set_new_content(&mut self, id: NodeId, content: TextBoxContent) {
+        self.boxes.entry(id).or_default().1 = Some(content);
+    }
+
+    pub(crate) fn notify(&self, adapter_id: usize, node: &Node, events: &Sender<Event>) {
+        let target = || ObjectId::node(adapter_id, node.id());
+        let hypertext = Hypertext::new(node);
+        for edit in text_edits(self.boxes.values()) {
+            let (offset, text, inserted) = match edit {
+                TextEdit::Removed { offset, text } => {
+                    (self.old_hypertext.offset_from_index(offset), text, false)
+                }
+                TextEdit::Inserted { offset, text } => {
+                    (hypertext.offset_from_index(offset), text, true)
+                }
+            };
+            let (start_index, length) =
+                match (text_offset(offset), text_offset(text.chars().count())) {
+                    (Ok(start_index), Ok(length)) => (start_index, length),
+                    _ => continue,
+                };
+            let event = if inserted {
+                ObjectEvent::TextInserted {
+                    start_index,
+                    length,
+                    content: text,
+                }
+            } else {
+                ObjectEvent::TextRemoved {
+                    start_index,
+                    length,
+                    content: text,
+                }
+            };
+            events
+                .send_blocking(Event::Object {
+                    target: target(),
+                    event,
+                })
+                .unwrap();
+        }
+        let selected_range =
+            |selection: Option<(usize, usize)>| selection.filter(|(start, end)| start != end);
+        if selected_range(text_selection_offsets(node, &hypertext))
+            != selected_range(self.old_selection)
+        {
+            events
+                .send_blocking(Event::Object {
+                    target: target(),
+                    event: ObjectEvent::TextSelectionChanged,
+                })
+                .unwrap();
+        }
+        if let Some(caret_offset) = caret_offset(node, &hypertext) {
+            if Some(caret_offset) != self.old_caret_offset {
+                if let Ok(caret_offset) = text_offset(caret_offset) {
+                    events
+                        .send_blocking(Event::Object {
+                            target: target(),
+                            event: ObjectEvent::CaretMoved(caret_offset),
+                        })
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
+
+fn text_selection_offsets(node: &Node, hypertext: &Hypertext) -> Option<(usize, usize)> {
+    node.text_selection().map(|range| {
+        (
+            hypertext.offset_from_index(range.start().to_global_usv_index()),
+            hypertext.offset_from_index(range.end().to_global_usv_index()),
+        )
+    })
+}
+
+fn caret_offset(node: &Node, hypertext: &Hypertext) -> Option<usize> {
+    node.text_selection_focus()
+        .map(|focus| hypertext.offset_from_index(focus.to_global_usv_index()))
+}
+
 pub(crate) fn unknown_object(id: &ObjectId) -> fdo::Error {
     fdo::Error::UnknownObject(format!("{}{}", ACCESSIBLE_PATH_PREFIX, id.as_str()))
 }
@@ -602,6 +1536,7 @@ pub(crate) fn unknown_object(id: &ObjectId) -> fdo::Error {
 #[derive(Clone)]
 pub(crate) struct PlatformNode {
     context: Weak<Context>,
+    adapter_id: usize,
     node_id: NodeId,
 }
 
@@ -609,10 +1544,15 @@ impl PlatformNode {
     pub(crate) fn new(context: &Arc<Context>, node_id: NodeId) -> Self {
         Self {
             context: Arc::downgrade(context),
+            adapter_id: context.adapter_id,
             node_id,
         }
     }
 
+    fn object_ref(&self, node_id: NodeId) -> ObjectRef {
+        ObjectRef::Managed(ObjectId::node(self.adapter_id, node_id))
+    }
+
     fn upgrade_context(&self) -> fdo::Result<Arc<Context>> {
         if let Some(context) = self.context.upgrade() {
             Ok(context)
@@ -684,7 +1624,7 @@ impl PlatformNode {
         self.resolve(|node| {
             Ok(node
                 .filtered_parent(&filter)
-                .map(|parent| parent.id().into())
+                .map(|parent| self.object_ref(parent.id()))
                 .unwrap_or_else(|| ObjectRef::Managed(ObjectId::root())))
         })
     }
@@ -697,7 +1637,7 @@ impl PlatformNode {
     }
 
     pub fn accessible_id(&self) -> ObjectId<'static> {
-        self.node_id.into()
+        ObjectId::node(self.adapter_id, self.node_id)
     }
 
     pub fn child_at_index(&self, index: usize) -> fdo::Result<Option<ObjectRef>> {
@@ -705,7 +1645,7 @@ impl PlatformNode {
             let child = node
                 .filtered_children(&filter)
                 .nth(index)
-                .map(|child| child.id().into());
+                .map(|child| self.object_ref(child.id()));
             Ok(child)
         })
     }
@@ -714,16 +1654,24 @@ impl PlatformNode {
         self.resolve(|node| {
             let children = node
                 .filtered_children(&filter)
-                .map(|child| child.id().into())
+                .map(|child| self.object_ref(child.id()))
                 .collect();
             Ok(children)
         })
     }
 
     pub fn index_in_parent(&self) -> fdo::Result<i32> {
-        self.resolve(|node| {
-            i32::try_from(node.preceding_filtered_siblings(&filter).count())
-                .map_err(|_| fdo::Error::Failed("Index is too big.".into()))
+        self.resolve_with_context(|node, context| {
+            // The root of each window is a child of the application root.
+            let index = if node.is_root() {
+                context
+                    .read_app_context()
+                    .adapter_index(self.adapter_id)
+                    .unwrap_or_default()
+            } else {
+                node.preceding_filtered_siblings(&filter).count()
+            };
+            i32::try_from(index).map_err(|_| fdo::Error::Failed("Index is too big.".into()))
         })
     }
 
@@ -741,6 +1689,24 @@ impl PlatformNode {
         })
     }
 
+    pub fn relation_set(&self) -> fdo::Result<Vec<(RelationType, Vec<ObjectRef>)>> {
+        self.resolve(|node| {
+            Ok(relation_set(&node)
+                .into_iter()
+                .map(|(relation_type, ids)| {
+                    (
+                        relation_type,
+                        ids.into_iter().map(|id| self.object_ref(id)).collect(),
+                    )
+                })
+                .collect())
+        })
+    }
+
+    pub fn attributes(&self) -> fdo::Result<HashMap<&'static str, String>> {
+        self.resolve(|node| Ok(NodeWrapper::Node(&node).attributes()))
+    }
+
     pub fn interfaces(&self) -> fdo::Result<InterfaceSet> {
         self.resolve(|node| {
             let wrapper = NodeWrapper::Node(&node);
@@ -751,42 +1717,66 @@ impl PlatformNode {
     pub fn n_actions(&self) -> fdo::Result<i32> {
         self.resolve(|node| {
             let wrapper = NodeWrapper::Node(&node);
-            Ok(wrapper.n_actions())
+            Ok(wrapper.actions().len() as i32)
         })
     }
 
-    pub fn get_action_name(&self, index: i32) -> fdo::Result<String> {
-        self.resolve(|node| {
+    fn resolve_action<F, T>(&self, index: i32, f: F) -> fdo::Result<Option<T>>
+    where
+        F: FnOnce(&NodeWrapper<'_>, &NodeAction<'_>) -> T,
+    {
+        self.resolve(|node| {
             let wrapper = NodeWrapper::Node(&node);
-            Ok(wrapper.get_action_name(index))
+            let actions = wrapper.actions();
+            Ok(usize::try_from(index)
+                .ok()
+                .and_then(|index| actions.get(index))
+                .map(|action| f(&wrapper, action)))
         })
     }
 
+    pub fn get_action_name(&self, index: i32) -> fdo::Result<String> {
+        self.resolve_action(index, |_, action| action.name())
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn get_action_localized_name(&self, index: i32) -> fdo::Result<String> {
+        self.resolve_action(index, |_, action| action.localized_name())
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn get_action_description(&self, index: i32) -> fdo::Result<String> {
+        self.resolve_action(index, |_, action| action.description())
+            .map(Option::unwrap_or_default)
+    }
+
+    pub fn get_action_key_binding(&self, index: i32) -> fdo::Result<String> {
+        self.resolve_action(index, |wrapper, action| wrapper.action_key_binding(action))
+            .map(Option::unwrap_or_default)
+    }
+
     pub fn get_actions(&self) -> fdo::Result<Vec<AtspiAction>> {
         self.resolve(|node| {
             let wrapper = NodeWrapper::Node(&node);
-            let n_actions = wrapper.n_actions() as usize;
-            let mut actions = Vec::with_capacity(n_actions);
-            for i in 0..n_actions {
-                actions.push(AtspiAction {
-                    localized_name: wrapper.get_action_name(i as i32),
-                    description: "".into(),
-                    key_binding: "".into(),
-                });
-            }
-            Ok(actions)
+            Ok(wrapper
+                .actions()
+                .iter()
+                .map(|action| AtspiAction {
+                    localized_name: action.localized_name(),
+                    description: action.description(),
+                    key_binding: wrapper.action_key_binding(action),
+                })
+                .collect())
         })
     }
 
     pub fn do_action(&self, index: i32) -> fdo::Result<bool> {
-        if index != 0 {
-            return Ok(false);
-        }
-        self.do_action_internal(|_, _| ActionRequest {
-            action: Action::Default,
-            target: self.node_id,
-            data: None,
-        })?;
+        let request = match self.resolve_action(index, |_, action| action.request(self.node_id))? {
+            Some(request) => request,
+            None => return Ok(false),
+        };
+        let context = self.upgrade_context()?;
+        context.action_handler.do_action(request);
         Ok(true)
     }
 
@@ -795,7 +1785,7 @@ impl PlatformNode {
             let window_bounds = context.read_root_window_bounds();
             let bounds = match node.bounding_box() {
                 Some(node_bounds) => {
-                    let top_left = window_bounds.top_left(coord_type, node.is_root());
+                    let top_left = window_bounds.top_left(coord_type, &node);
                     let new_origin =
                         Point::new(top_left.x + node_bounds.x0, top_left.y + node_bounds.y0);
                     node_bounds.with_origin(new_origin)
@@ -803,9 +1793,8 @@ impl PlatformNode {
                 None if node.is_root() => {
                     let bounds = window_bounds.outer;
                     match coord_type {
-                        CoordType::Screen => bounds,
+                        CoordType::Screen | CoordType::Parent => bounds,
                         CoordType::Window => bounds.with_origin(Point::ZERO),
-                        _ => unimplemented!(),
                     }
                 }
                 _ => return Err(unknown_object(&self.accessible_id())),
@@ -822,12 +1811,12 @@ impl PlatformNode {
     ) -> fdo::Result<Option<ObjectRef>> {
         self.resolve_with_context(|node, context| {
             let window_bounds = context.read_root_window_bounds();
-            let top_left = window_bounds.top_left(coord_type, node.is_root());
+            let top_left = window_bounds.top_left(coord_type, &node);
             let point = Point::new(f64::from(x) - top_left.x, f64::from(y) - top_left.y);
             let point = node.transform().inverse() * point;
             Ok(node
                 .node_at_point(point, &filter)
-                .map(|node| ObjectRef::Managed(NodeWrapper::Node(&node).id())))
+                .map(|node| self.object_ref(node.id())))
         })
     }
 
@@ -836,7 +1825,7 @@ impl PlatformNode {
             let window_bounds = context.read_root_window_bounds();
             match node.bounding_box() {
                 Some(node_bounds) => {
-                    let top_left = window_bounds.top_left(coord_type, node.is_root());
+                    let top_left = window_bounds.top_left(coord_type, &node);
                     let new_origin =
                         Point::new(top_left.x + node_bounds.x0, top_left.y + node_bounds.y0);
                     Ok((node_bounds.with_origin(new_origin).into(),))
@@ -844,9 +1833,8 @@ impl PlatformNode {
                 None if node.is_root() => {
                     let bounds = window_bounds.outer;
                     Ok((match coord_type {
-                        CoordType::Screen => bounds.into(),
+                        CoordType::Screen | CoordType::Parent => bounds.into(),
                         CoordType::Window => bounds.with_origin(Point::ZERO).into(),
-                        _ => unimplemented!(),
                     },))
                 }
                 _ => Err(unknown_object(&self.accessible_id())),
@@ -857,11 +1845,7 @@ impl PlatformNode {
     pub fn get_layer(&self) -> fdo::Result<Layer> {
         self.resolve(|node| {
             let wrapper = NodeWrapper::Node(&node);
-            if wrapper.role() == AtspiRole::Window {
-                Ok(Layer::Window)
-            } else {
-                Ok(Layer::Widget)
-            }
+            Ok(wrapper.layer())
         })
     }
 
@@ -877,8 +1861,8 @@ impl PlatformNode {
     pub fn scroll_to_point(&self, coord_type: CoordType, x: i32, y: i32) -> fdo::Result<bool> {
         self.do_action_internal(|tree_state, context| {
             let window_bounds = context.read_root_window_bounds();
-            let is_root = self.node_id == tree_state.root_id();
-            let top_left = window_bounds.top_left(coord_type, is_root);
+            let node = tree_state.node_by_id(self.node_id).unwrap();
+            let top_left = window_bounds.top_left(coord_type, &node);
             let point = Point::new(f64::from(x) - top_left.x, f64::from(y) - top_left.y);
             ActionRequest {
                 action: Action::ScrollToPoint,
@@ -889,6 +1873,21 @@ impl PlatformNode {
         Ok(true)
     }
 
+    pub fn scroll_to(&self, scroll_type: ScrollType) -> fdo::Result<bool> {
+        self.do_action_internal(|tree_state, _| {
+            let node = tree_state.node_by_id(self.node_id).unwrap();
+            let rect = node
+                .raw_bounds()
+                .and_then(|bounds| scroll_target_rect(bounds, scroll_type));
+            ActionRequest {
+                action: Action::ScrollIntoView,
+                target: self.node_id,
+                data: rect.map(ActionData::ScrollTargetRect),
+            }
+        })?;
+        Ok(true)
+    }
+
     pub fn minimum_value(&self) -> fdo::Result<f64> {
         self.resolve(|node| Ok(node.state().min_numeric_value().unwrap_or(std::f64::MIN)))
     }
@@ -915,17 +1914,2414 @@ impl PlatformNode {
             data: Some(ActionData::NumericValue(value)),
         })
     }
-}
 
-#[derive(Clone)]
-pub(crate) struct PlatformRootNode {
-    pub(crate) context: Weak<Context>,
-}
+    fn resolve_for_text_with_context<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>, &Context) -> fdo::Result<T>,
+    {
+        self.resolve_with_context(|node, context| {
+            if node.supports_text_ranges() {
+                f(node, context)
+            } else {
+                Err(unsupported_interface(&self.accessible_id(), "Text"))
+            }
+        })
+    }
 
-impl PlatformRootNode {
-    pub fn new(context: &Arc<Context>) -> Self {
-        Self {
-            context: Arc::downgrade(context),
+    fn resolve_for_text<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>, &Hypertext) -> fdo::Result<T>,
+    {
+        self.resolve_for_text_with_context(|node, context| f(node, &context.hypertext(&node)))
+    }
+
+    fn set_text_selection<F>(&self, f: F) -> fdo::Result<bool>
+    where
+        for<'a> F: FnOnce(Node<'a>, &Hypertext) -> Option<TextSelection>,
+    {
+        let context = self.upgrade_context()?;
+        let tree = context.read_tree();
+        let node = match tree.state().node_by_id(self.node_id) {
+            Some(node) if node.supports_text_ranges() => node,
+            Some(_) => return Err(unsupported_interface(&self.accessible_id(), "Text")),
+            None => return Err(unknown_object(&self.accessible_id())),
+        };
+        let selection = match f(node, &context.hypertext(&node)) {
+            Some(selection) => selection,
+            None => return Ok(false),
+        };
+        drop(tree);
+        context.action_handler.do_action(ActionRequest {
+            action: Action::SetTextSelection,
+            target: self.node_id,
+            data: Some(ActionData::SetTextSelection(selection)),
+        });
+        Ok(true)
+    }
+
+    pub fn character_count(&self) -> fdo::Result<i32> {
+        self.resolve_for_text(|_, hypertext| text_offset(hypertext.character_count()))
+    }
+
+    pub fn caret_offset(&self) -> fdo::Result<i32> {
+        self.resolve_for_text(|node, hypertext| match node.text_selection_focus() {
+            Some(focus) => text_offset(hypertext.offset_from_index(focus.to_global_usv_index())),
+            None => Ok(-1),
+        })
+    }
+
+    pub fn string_at_offset(
+        &self,
+        offset: i32,
+        granularity: Granularity,
+    ) -> fdo::Result<(String, i32, i32)> {
+        self.resolve_for_text(|node, hypertext| {
+            let pos = match text_position_from_offset(&node, hypertext, offset) {
+                Some(pos) => pos,
+                None => return Ok((String::new(), -1, -1)),
+            };
+            // The offset isn't negative, since it has a text position.
+            let offset = offset as usize;
+            let mut range = pos.to_degenerate_range();
+            match granularity {
+                Granularity::Char => {
+                    if !pos.is_document_end() {
+                        range.set_end(pos.forward_to_character_end());
+                    }
+                }
+                Granularity::Word => {
+                    let start = if pos.is_word_start() {
+                        pos
+                    } else {
+                        pos.backward_to_word_start()
+                    };
+                    range = start.to_degenerate_range();
+                    if !start.is_document_end() {
+                        range.set_end(start.forward_to_word_start());
+                    }
+                }
+                Granularity::Line => {
+                    let start = if pos.is_line_start() {
+                        pos
+                    } else {
+                        pos.backward_to_line_start()
+                    };
+                    range = start.to_degenerate_range();
+                    if !start.is_document_end() {
+                        range.set_end(start.forward_to_line_end());
+                    }
+                }
+                Granularity::Sentence | Granularity::Paragraph => {
+                    let start = if pos.is_paragraph_start() {
+                        pos
+                    } else {
+                        pos.backward_to_paragraph_start()
+                    };
+                    range = start.to_degenerate_range();
+                    if !start.is_document_end() {
+                        range.set_end(start.forward_to_paragraph_end());
+                    }
+                }
+            }
+            let start = range.start().to_global_usv_index();
+            let end = range.end().to_global_usv_index();
+            let (start, end) = match granularity {
+                // The character at an offset may be an embedded object,
+                // which the text position skips.
+                Granularity::Char if hypertext.object_at(offset).is_some() => (offset, offset + 1),
+                Granularity::Char => (offset, offset + end - start),
+                _ => (
+                    hypertext.offset_from_index(start),
+                    hypertext.offset_from_index(end),
+                ),
+            };
+            let characters = hypertext
+                .text(&node)
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<Vec<_>>();
+            if granularity == Granularity::Sentence {
+                // Sentences are found within the enclosing paragraph.
+                let (sentence_start, sentence_end) = sentence_bounds(&characters, offset - start);
+                return Ok((
+                    characters[sentence_start..sentence_end].iter().collect(),
+                    text_offset(start + sentence_start)?,
+                    text_offset(start + sentence_end)?,
+                ));
+            }
+            Ok((
+                characters.into_iter().collect(),
+                text_offset(start)?,
+                text_offset(end)?,
+            ))
+        })
+    }
+
+    pub fn text(&self, start_offset: i32, end_offset: i32) -> fdo::Result<String> {
+        self.resolve_for_text(|node, hypertext| {
+            let text = hypertext.text(&node);
+            let start = usize::try_from(start_offset).unwrap_or(0);
+            let end = usize::try_from(end_offset).unwrap_or(usize::MAX);
+            Ok(text
+                .chars()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .collect())
+        })
+    }
+
+    pub fn set_caret_offset(&self, offset: i32) -> fdo::Result<bool> {
+        self.set_text_selection(|node, hypertext| {
+            text_position_from_offset(&node, hypertext, offset)
+                .map(|pos| pos.to_degenerate_range().to_text_selection())
+        })
+    }
+
+    pub fn character_extents(&self, offset: i32, coord_type: CoordType) -> fdo::Result<AtspiRect> {
+        self.resolve_for_text_with_context(|node, context| {
+            let hypertext = context.hypertext(&node);
+            let window_bounds = context.read_root_window_bounds();
+            let object = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| hypertext.object_at(offset))
+                .and_then(|id| node.tree_state.node_by_id(id));
+            if let Some(object) = object {
+                return Ok(object.bounding_box().map_or(AtspiRect::INVALID, |bounds| {
+                    let top_left = window_bounds.top_left(coord_type, &object);
+                    bounds
+                        .with_origin(Point::new(top_left.x + bounds.x0, top_left.y + bounds.y0))
+                        .into()
+                }));
+            }
+            let pos = match text_position_from_offset(&node, &hypertext, offset) {
+                Some(pos) => pos,
+                None => return Ok(AtspiRect::INVALID),
+            };
+            let mut range = pos.to_degenerate_range();
+            if !pos.is_document_end() {
+                range.set_end(pos.forward_to_character_end());
+            }
+            Ok(text_range_extents(
+                &node,
+                &range,
+                &window_bounds,
+                coord_type,
+            ))
+        })
+    }
+
+    pub fn offset_at_point(&self, x: i32, y: i32, coord_type: CoordType) -> fdo::Result<i32> {
+        self.resolve_for_text_with_context(|node, context| {
+            let window_bounds = context.read_root_window_bounds();
+            let top_left = window_bounds.top_left(coord_type, &node);
+            let point = Point::new(f64::from(x) - top_left.x, f64::from(y) - top_left.y);
+            let point = node.transform().inverse() * point;
+            let index = node.text_position_at_point(point).to_global_usv_index();
+            text_offset(context.hypertext(&node).offset_from_index(index))
+        })
+    }
+
+    pub fn n_selections(&self) -> fdo::Result<i32> {
+        self.resolve_for_text(|node, _| {
+            Ok(match node.text_selection() {
+                Some(selection) if !selection.is_degenerate() => 1,
+                _ => 0,
+            })
+        })
+    }
+
+    pub fn selection(&self, selection_num: i32) -> fdo::Result<(i32, i32)> {
+        self.resolve_for_text(|node, hypertext| match node.text_selection() {
+            Some(selection) if selection_num == 0 && !selection.is_degenerate() => Ok((
+                text_offset(hypertext.offset_from_index(selection.start().to_global_usv_index()))?,
+                text_offset(hypertext.offset_from_index(selection.end().to_global_usv_index()))?,
+            )),
+            _ => Ok((0, 0)),
+        })
+    }
+
+    pub fn add_selection(&self, start_offset: i32, end_offset: i32) -> fdo::Result<bool> {
+        // We only support a single selection.
+        self.set_text_selection(|node, hypertext| match node.text_selection() {
+            Some(selection) if !selection.is_degenerate() => None,
+            _ => text_range_from_offsets(&node, hypertext, start_offset, end_offset)
+                .map(|range| range.to_text_selection()),
+        })
+    }
+
+    pub fn remove_selection(&self, selection_num: i32) -> fdo::Result<bool> {
+        self.set_text_selection(|node, _| {
+            if selection_num != 0 {
+                return None;
+            }
+            match node.text_selection() {
+                Some(selection) if !selection.is_degenerate() => {
+                    let focus = node.text_selection_focus().unwrap();
+                    Some(focus.to_degenerate_range().to_text_selection())
+                }
+                _ => None,
+            }
+        })
+    }
+
+    pub fn set_selection(
+        &self,
+        selection_num: i32,
+        start_offset: i32,
+        end_offset: i32,
+    ) -> fdo::Result<bool> {
+        self.set_text_selection(|node, hypertext| {
+            if selection_num != 0 {
+                return None;
+            }
+            text_range_from_offsets(&node, hypertext, start_offset, end_offset)
+                .map(|range| range.to_text_selection())
+        })
+    }
+
+    pub fn range_extents(
+        &self,
+        start_offset: i32,
+        end_offset: i32,
+        coord_type: CoordType,
+    ) -> fdo::Result<AtspiRect> {
+        self.resolve_for_text_with_context(|node, context| {
+            match text_range_from_offsets(
+                &node,
+                &context.hypertext(&node),
+                start_offset,
+                end_offset,
+            ) {
+                Some(range) => {
+                    let window_bounds = context.read_root_window_bounds();
+                    Ok(text_range_extents(
+                        &node,
+                        &range,
+                        &window_bounds,
+                        coord_type,
+                    ))
+                }
+                None => Ok(AtspiRect::INVALID),
+            }
+        })
+    }
+
+    fn resolve_for_selection<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> fdo::Result<T>,
+    {
+        self.resolve(|node| {
+            if NodeWrapper::Node(&node).supports_selection() {
+                f(node)
+            } else {
+                Err(unsupported_interface(&self.accessible_id(), "Selection"))
+            }
+        })
+    }
+
+    /// Sends the requests returned by `f`, which returns `None` if
+    /// the selection can't be changed as requested.
+    fn do_selection_requests<F>(&self, f: F) -> fdo::Result<bool>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> fdo::Result<Option<Vec<ActionRequest>>>,
+    {
+        let context = self.upgrade_context()?;
+        let tree = context.read_tree();
+        let node = tree
+            .state()
+            .node_by_id(self.node_id)
+            .ok_or_else(|| unknown_object(&self.accessible_id()))?;
+        let requests = f(node)?;
+        drop(tree);
+        match requests {
+            Some(requests) => {
+                for request in requests {
+                    context.action_handler.do_action(request);
+                }
+                Ok(true)
+            }
+            None => Ok(false),
         }
     }
+
+    fn do_selection_actions<F>(&self, f: F) -> fdo::Result<bool>
+    where
+        for<'a> F: FnOnce(Node<'a>) -> Option<Vec<ActionRequest>>,
+    {
+        self.do_selection_requests(|node| {
+            if NodeWrapper::Node(&node).supports_selection() {
+                Ok(f(node))
+            } else {
+                Err(unsupported_interface(&self.accessible_id(), "Selection"))
+            }
+        })
+    }
+
+    pub fn n_selected_children(&self) -> fdo::Result<i32> {
+        self.resolve_for_selection(|node| {
+            i32::try_from(selected_children(&node).count())
+                .map_err(|_| fdo::Error::Failed("Too many children.".into()))
+        })
+    }
+
+    pub fn selected_child(&self, selected_child_index: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_selection(|node| {
+            let child = usize::try_from(selected_child_index)
+                .ok()
+                .and_then(|index| selected_children(&node).nth(index))
+                .map(|child| self.object_ref(child.id()));
+            Ok(child)
+        })
+    }
+
+    pub fn select_child(&self, child_index: i32) -> fdo::Result<bool> {
+        self.do_selection_actions(|node| {
+            let child = filtered_child_at_index(&node, child_index)?;
+            match child.is_selected() {
+                Some(true) => Some(Vec::new()),
+                Some(false) if !child.is_disabled() => Some(vec![toggle_selection(&child)]),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn deselect_selected_child(&self, selected_child_index: i32) -> fdo::Result<bool> {
+        self.do_selection_actions(|node| {
+            if !node.is_multiselectable() {
+                return None;
+            }
+            let index = usize::try_from(selected_child_index).ok()?;
+            let child = selected_children(&node).nth(index)?;
+            (!child.is_disabled()).then(|| vec![toggle_selection(&child)])
+        })
+    }
+
+    pub fn is_child_selected(&self, child_index: i32) -> fdo::Result<bool> {
+        self.resolve_for_selection(|node| {
+            Ok(filtered_child_at_index(&node, child_index)
+                .and_then(|child| child.is_selected())
+                .unwrap_or(false))
+        })
+    }
+
+    pub fn select_all(&self) -> fdo::Result<bool> {
+        self.do_selection_actions(|node| set_all_selected(&node, true))
+    }
+
+    pub fn clear_selection(&self) -> fdo::Result<bool> {
+        self.do_selection_actions(|node| set_all_selected(&node, false))
+    }
+
+    pub fn deselect_child(&self, child_index: i32) -> fdo::Result<bool> {
+        self.do_selection_actions(|node| {
+            if !node.is_multiselectable() {
+                return None;
+            }
+            let child = filtered_child_at_index(&node, child_index)?;
+            match child.is_selected() {
+                Some(true) if !child.is_disabled() => Some(vec![toggle_selection(&child)]),
+                Some(false) => Some(Vec::new()),
+                _ => None,
+            }
+        })
+    }
+
+    fn resolve_for_table<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(Table<'a>) -> fdo::Result<T>,
+    {
+        self.resolve(|node| match node.as_table() {
+            Some(table) => f(table),
+            None => Err(unsupported_interface(&self.accessible_id(), "Table")),
+        })
+    }
+
+    fn resolve_for_table_cell<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(TableCell<'a>) -> fdo::Result<T>,
+    {
+        self.resolve(|node| match node.as_table_cell() {
+            Some(cell) => f(cell),
+            None => Err(unsupported_interface(&self.accessible_id(), "TableCell")),
+        })
+    }
+
+    fn do_table_selection_actions<F>(&self, f: F) -> fdo::Result<bool>
+    where
+        for<'a> F: FnOnce(Table<'a>) -> Option<Vec<ActionRequest>>,
+    {
+        self.do_selection_requests(|node| match node.as_table() {
+            Some(table) => Ok(f(table)),
+            None => Err(unsupported_interface(&self.accessible_id(), "Table")),
+        })
+    }
+
+    pub fn n_rows(&self) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| Ok(table.row_count() as i32))
+    }
+
+    pub fn n_columns(&self) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| Ok(table.column_count() as i32))
+    }
+
+    pub fn caption(&self) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_table(|table| {
+            Ok(table.caption().map(|caption| self.object_ref(caption.id())))
+        })
+    }
+
+    pub fn n_selected_rows(&self) -> fdo::Result<i32> {
+        self.selected_rows().map(|rows| rows.len() as i32)
+    }
+
+    pub fn n_selected_columns(&self) -> fdo::Result<i32> {
+        self.selected_columns().map(|columns| columns.len() as i32)
+    }
+
+    pub fn accessible_at(&self, row: i32, column: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at(&table, row, column).map(|cell| self.object_ref(cell.node().id())))
+        })
+    }
+
+    pub fn index_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at(&table, row, column).map_or(-1, |cell| {
+                (cell.row_index() * table.column_count() + cell.column_index()) as i32
+            }))
+        })
+    }
+
+    pub fn row_at_index(&self, index: i32) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at_index(&table, index).map_or(-1, |cell| cell.row_index() as i32))
+        })
+    }
+
+    pub fn column_at_index(&self, index: i32) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at_index(&table, index).map_or(-1, |cell| cell.column_index() as i32))
+        })
+    }
+
+    pub fn row_description(&self, row: i32) -> fdo::Result<String> {
+        self.resolve_for_table(|table| {
+            Ok(usize::try_from(row)
+                .ok()
+                .and_then(|row| {
+                    table
+                        .row_headers(row)
+                        .first()
+                        .and_then(|header| header.name())
+                })
+                .unwrap_or_default())
+        })
+    }
+
+    pub fn column_description(&self, column: i32) -> fdo::Result<String> {
+        self.resolve_for_table(|table| {
+            Ok(usize::try_from(column)
+                .ok()
+                .and_then(|column| {
+                    table
+                        .column_headers(column)
+                        .first()
+                        .and_then(|header| header.name())
+                })
+                .unwrap_or_default())
+        })
+    }
+
+    pub fn row_extent_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at(&table, row, column).map_or(0, |cell| cell.row_span() as i32))
+        })
+    }
+
+    pub fn column_extent_at(&self, row: i32, column: i32) -> fdo::Result<i32> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at(&table, row, column).map_or(0, |cell| cell.column_span() as i32))
+        })
+    }
+
+    pub fn row_header(&self, row: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_table(|table| {
+            Ok(usize::try_from(row).ok().and_then(|row| {
+                table
+                    .row_headers(row)
+                    .first()
+                    .map(|header| self.object_ref(header.id()))
+            }))
+        })
+    }
+
+    pub fn column_header(&self, column: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_table(|table| {
+            Ok(usize::try_from(column).ok().and_then(|column| {
+                table
+                    .column_headers(column)
+                    .first()
+                    .map(|header| self.object_ref(header.id()))
+            }))
+        })
+    }
+
+    pub fn selected_rows(&self) -> fdo::Result<Vec<i32>> {
+        self.resolve_for_table(|table| {
+            Ok((0..table.row_count())
+                .filter(|row| table.is_row_selected(*row))
+                .map(|row| row as i32)
+                .collect())
+        })
+    }
+
+    pub fn selected_columns(&self) -> fdo::Result<Vec<i32>> {
+        self.resolve_for_table(|table| {
+            Ok((0..table.column_count())
+                .filter(|column| table.is_column_selected(*column))
+                .map(|column| column as i32)
+                .collect())
+        })
+    }
+
+    pub fn is_row_selected(&self, row: i32) -> fdo::Result<bool> {
+        self.resolve_for_table(|table| {
+            Ok(matches!(usize::try_from(row), Ok(row) if table.is_row_selected(row)))
+        })
+    }
+
+    pub fn is_column_selected(&self, column: i32) -> fdo::Result<bool> {
+        self.resolve_for_table(|table| {
+            Ok(matches!(usize::try_from(column), Ok(column) if table.is_column_selected(column)))
+        })
+    }
+
+    pub fn add_row_selection(&self, row: i32) -> fdo::Result<bool> {
+        self.do_table_selection_actions(|table| set_row_selected(&table, row, true))
+    }
+
+    pub fn add_column_selection(&self, column: i32) -> fdo::Result<bool> {
+        self.do_table_selection_actions(|table| set_column_selected(&table, column, true))
+    }
+
+    pub fn remove_row_selection(&self, row: i32) -> fdo::Result<bool> {
+        self.do_table_selection_actions(|table| set_row_selected(&table, row, false))
+    }
+
+    pub fn remove_column_selection(&self, column: i32) -> fdo::Result<bool> {
+        self.do_table_selection_actions(|table| set_column_selected(&table, column, false))
+    }
+
+    pub fn is_selected(&self, row: i32, column: i32) -> fdo::Result<bool> {
+        self.resolve_for_table(|table| {
+            Ok(table_cell_at(&table, row, column)
+                .filter(|cell| cell.is_selected())
+                .is_some())
+        })
+    }
+
+    pub fn row_column_extents_at_index(
+        &self,
+        index: i32,
+    ) -> fdo::Result<(bool, i32, i32, i32, i32, bool)> {
+        self.resolve_for_table(|table| match table_cell_at_index(&table, index) {
+            Some(cell) => Ok((
+                true,
+                cell.row_index() as i32,
+                cell.column_index() as i32,
+                cell.row_span() as i32,
+                cell.column_span() as i32,
+                cell.is_selected(),
+            )),
+            None => Ok((false, 0, 0, 0, 0, false)),
+        })
+    }
+
+    pub fn cell_row_span(&self) -> fdo::Result<i32> {
+        self.resolve_for_table_cell(|cell| Ok(cell.row_span() as i32))
+    }
+
+    pub fn cell_column_span(&self) -> fdo::Result<i32> {
+        self.resolve_for_table_cell(|cell| Ok(cell.column_span() as i32))
+    }
+
+    pub fn cell_position(&self) -> fdo::Result<(i32, i32)> {
+        self.resolve_for_table_cell(|cell| {
+            Ok((cell.row_index() as i32, cell.column_index() as i32))
+        })
+    }
+
+    pub fn cell_row_column_span(&self) -> fdo::Result<(i32, i32, i32, i32)> {
+        self.resolve_for_table_cell(|cell| {
+            Ok((
+                cell.row_index() as i32,
+                cell.column_index() as i32,
+                cell.row_span() as i32,
+                cell.column_span() as i32,
+            ))
+        })
+    }
+
+    pub fn cell_table(&self) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_table_cell(|cell| Ok(Some(self.object_ref(cell.table().node().id()))))
+    }
+
+    pub fn cell_row_header_cells(&self) -> fdo::Result<Vec<ObjectRef>> {
+        self.resolve_for_table_cell(|cell| {
+            Ok(cell
+                .row_header_cells()
+                .into_iter()
+                .map(|header| self.object_ref(header.id()))
+                .collect())
+        })
+    }
+
+    pub fn cell_column_header_cells(&self) -> fdo::Result<Vec<ObjectRef>> {
+        self.resolve_for_table_cell(|cell| {
+            Ok(cell
+                .column_header_cells()
+                .into_iter()
+                .map(|header| self.object_ref(header.id()))
+                .collect())
+        })
+    }
+
+    fn resolve_for_hypertext<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        F: FnOnce(&Hypertext) -> fdo::Result<T>,
+    {
+        self.resolve_with_context(|node, context| {
+            if node.supports_text_ranges() {
+                f(&context.hypertext(&node))
+            } else {
+                Err(unsupported_interface(&self.accessible_id(), "Hypertext"))
+            }
+        })
+    }
+
+    fn resolve_for_hyperlink<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        for<'a> F: FnOnce(Node<'a>, &Context) -> fdo::Result<T>,
+    {
+        self.resolve_with_context(|node, context| {
+            if node.role() == Role::Link {
+                f(node, context)
+            } else {
+                Err(unsupported_interface(&self.accessible_id(), "Hyperlink"))
+            }
+        })
+    }
+
+    pub fn n_links(&self) -> fdo::Result<i32> {
+        self.resolve_for_hypertext(|hypertext| text_offset(hypertext.links().len()))
+    }
+
+    pub fn link(&self, link_index: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_hypertext(|hypertext| {
+            Ok(usize::try_from(link_index)
+                .ok()
+                .and_then(|index| hypertext.links().get(index))
+                .map(|(id, _, _)| self.object_ref(*id)))
+        })
+    }
+
+    pub fn link_index(&self, character_index: i32) -> fdo::Result<i32> {
+        self.resolve_for_hypertext(|hypertext| {
+            let character_index = match usize::try_from(character_index) {
+                Ok(index) => index,
+                Err(_) => return Ok(-1),
+            };
+            match hypertext
+                .links()
+                .iter()
+                .position(|(_, start, end)| (*start..*end).contains(&character_index))
+            {
+                Some(index) => text_offset(index),
+                None => Ok(-1),
+            }
+        })
+    }
+
+    pub fn hyperlink_n_anchors(&self) -> fdo::Result<i16> {
+        self.resolve_for_hyperlink(|_, _| Ok(1))
+    }
+
+    fn hyperlink_offsets(&self) -> fdo::Result<Option<(i32, i32)>> {
+        self.resolve_for_hyperlink(|node, context| {
+            let offsets = hyperlink_container(&node)
+                .and_then(|container| context.hypertext(&container).link_offsets(node.id()));
+            match offsets {
+                Some((start, end)) => Ok(Some((text_offset(start)?, text_offset(end)?))),
+                None => Ok(None),
+            }
+        })
+    }
+
+    pub fn hyperlink_start_index(&self) -> fdo::Result<i32> {
+        Ok(self.hyperlink_offsets()?.map_or(-1, |(start, _)| start))
+    }
+
+    pub fn hyperlink_end_index(&self) -> fdo::Result<i32> {
+        Ok(self.hyperlink_offsets()?.map_or(-1, |(_, end)| end))
+    }
+
+    pub fn hyperlink_object(&self, anchor_index: i32) -> fdo::Result<Option<ObjectRef>> {
+        self.resolve_for_hyperlink(|node, _| {
+            Ok((anchor_index == 0).then(|| self.object_ref(node.id())))
+        })
+    }
+
+    pub fn hyperlink_uri(&self, anchor_index: i32) -> fdo::Result<String> {
+        self.resolve_for_hyperlink(|node, _| {
+            Ok(match node.url() {
+                Some(url) if anchor_index == 0 => url.into(),
+                _ => String::new(),
+            })
+        })
+    }
+
+    pub fn hyperlink_is_valid(&self) -> fdo::Result<bool> {
+        self.resolve_for_hyperlink(|node, _| Ok(node.url().is_some()))
+    }
+
+    fn do_editable_text_actions<F>(&self, f: F) -> fdo::Result<bool>
+    where
+        for<'a> F: FnOnce(Node<'a>, &Hypertext) -> Vec<ActionRequest>,
+    {
+        let context = self.upgrade_context()?;
+        let tree = context.read_tree();
+        let node = match tree.state().node_by_id(self.node_id) {
+            Some(node) if NodeWrapper::Node(&node).supports_editable_text() => node,
+            Some(_) => return Err(unsupported_interface(&self.accessible_id(), "EditableText")),
+            None => return Err(unknown_object(&self.accessible_id())),
+        };
+        let requests = f(node, &context.hypertext(&node));
+        drop(tree);
+        if requests.is_empty() {
+            return Ok(false);
+        }
+        for request in requests {
+            context.action_handler.do_action(request);
+        }
+        Ok(true)
+    }
+
+    fn select_text_range_and_do(
+        &self,
+        range: &TextRange,
+        action: Action,
+        data: Option<ActionData>,
+    ) -> Vec<ActionRequest> {
+        vec![
+            ActionRequest {
+                action: Action::SetTextSelection,
+                target: self.node_id,
+                data: Some(ActionData::SetTextSelection(range.to_text_selection())),
+            },
+            ActionRequest {
+                action,
+                target: self.node_id,
+                data,
+            },
+        ]
+    }
+
+    /// Returns the requests that replace the given range of the node's
+    /// text, preferring to replace just that range if the node supports
+    /// it, and otherwise replacing the whole value.
+    fn replace_text_range_requests(
+        &self,
+        node: &Node,
+        range: &TextRange,
+        text: &str,
+    ) -> Vec<ActionRequest> {
+        if node.supports_replace_selected_text() {
+            self.select_text_range_and_do(
+                range,
+                Action::ReplaceSelectedText,
+                Some(ActionData::Value(text.into())),
+            )
+        } else if node.supports_set_value() {
+            let old_text = node.document_range().text();
+            let start = range.start().to_global_usv_index();
+            let end = range.end().to_global_usv_index();
+            let new_text = old_text
+                .chars()
+                .take(start)
+                .chain(text.chars())
+                .chain(old_text.chars().skip(end))
+                .collect::<String>();
+            vec![ActionRequest {
+                action: Action::SetValue,
+                target: self.node_id,
+                data: Some(ActionData::Value(new_text.into())),
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn set_text_contents(&self, new_contents: &str) -> fdo::Result<bool> {
+        self.do_editable_text_actions(|node, _| {
+            if node.supports_set_value() {
+                vec![ActionRequest {
+                    action: Action::SetValue,
+                    target: self.node_id,
+                    data: Some(ActionData::Value(new_contents.into())),
+                }]
+            } else {
+                self.replace_text_range_requests(&node, &node.document_range(), new_contents)
+            }
+        })
+    }
+
+    pub fn insert_text(&self, position: i32, text: &str, length: i32) -> fdo::Result<bool> {
+        // The length is in bytes. Ignore it if it doesn't fall on
+        // a character boundary, as some clients pass a character count.
+        let text = match usize::try_from(length) {
+            Ok(length) if text.is_char_boundary(length) => &text[..length],
+            _ => text,
+        };
+        self.do_editable_text_actions(|node, hypertext| {
+            text_position_from_offset(&node, hypertext, position).map_or_else(Vec::new, |pos| {
+                self.replace_text_range_requests(&node, &pos.to_degenerate_range(), text)
+            })
+        })
+    }
+
+    pub fn delete_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<bool> {
+        self.do_editable_text_actions(|node, hypertext| {
+            text_range_from_offsets(&node, hypertext, start_pos, end_pos)
+                .map_or_else(Vec::new, |range| {
+                    self.replace_text_range_requests(&node, &range, "")
+                })
+        })
+    }
+
+    pub fn copy_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<()> {
+        let copied =
+            self.do_editable_text_actions(|node, hypertext| {
+                match text_range_from_offsets(&node, hypertext, start_pos, end_pos) {
+                    Some(range) if node.supports_copy() => {
+                        self.select_text_range_and_do(&range, Action::Copy, None)
+                    }
+                    _ => Vec::new(),
+                }
+            })?;
+        if copied {
+            Ok(())
+        } else {
+            Err(fdo::Error::NotSupported(
+                "Copying this text is not supported.".into(),
+            ))
+        }
+    }
+
+    pub fn cut_text(&self, start_pos: i32, end_pos: i32) -> fdo::Result<bool> {
+        self.do_editable_text_actions(|node, hypertext| {
+            match text_range_from_offsets(&node, hypertext, start_pos, end_pos) {
+                Some(range) if node.supports_cut() => {
+                    self.select_text_range_and_do(&range, Action::Cut, None)
+                }
+                _ => Vec::new(),
+            }
+        })
+    }
+
+    pub fn paste_text(&self, position: i32) -> fdo::Result<bool> {
+        self.do_editable_text_actions(|node, hypertext| {
+            match text_position_from_offset(&node, hypertext, position) {
+                Some(pos) if node.supports_paste() => {
+                    self.select_text_range_and_do(&pos.to_degenerate_range(), Action::Paste, None)
+                }
+                _ => Vec::new(),
+            }
+        })
+    }
+}
+
+fn unsupported_interface(id: &ObjectId, interface: &str) -> fdo::Error {
+    fdo::Error::UnknownInterface(format!(
+        "{}{} doesn't implement {}",
+        ACCESSIBLE_PATH_PREFIX,
+        id.as_str(),
+        interface
+    ))
+}
+
+fn table_cell_at<'a>(table: &Table<'a>, row: i32, column: i32) -> Option<TableCell<'a>> {
+    let row = usize::try_from(row).ok()?;
+    let column = usize::try_from(column).ok()?;
+    table.cell_at(row, column)
+}
+
+/// Returns the cell at an index in the row-major order of the table's
+/// slots, as used by [`PlatformNode::index_at`].
+fn table_cell_at_index<'a>(table: &Table<'a>, index: i32) -> Option<TableCell<'a>> {
+    let index = usize::try_from(index).ok()?;
+    let column_count = table.column_count();
+    if column_count == 0 {
+        return None;
+    }
+    table.cell_at(index / column_count, index % column_count)
+}
+
+/// Returns the requests that bring a row of a table to the given
+/// selected state, or `None` if the row isn't selectable or is disabled.
+/// As with the Selection interface, rows can only be deselected
+/// in a multi-selectable table.
+fn set_row_selected(table: &Table, row: i32, selected: bool) -> Option<Vec<ActionRequest>> {
+    if !selected && !table.node().is_multiselectable() {
+        return None;
+    }
+    let row = table.row(usize::try_from(row).ok()?)?;
+    match row.is_selected()? {
+        is_selected if is_selected == selected => Some(Vec::new()),
+        _ if row.is_disabled() => None,
+        _ => Some(vec![toggle_selection(&row)]),
+    }
+}
+
+/// Returns the requests that bring every cell in a column of
+/// a multi-selectable table to the given selected state, or `None`
+/// if the column has no cells, or a cell in it isn't selectable
+/// or would need to change but is disabled.
+fn set_column_selected(table: &Table, column: i32, selected: bool) -> Option<Vec<ActionRequest>> {
+    if !table.node().is_multiselectable() {
+        return None;
+    }
+    let cells = table.cells_in_column(usize::try_from(column).ok()?);
+    if cells.is_empty() {
+        return None;
+    }
+    let mut requests = Vec::new();
+    for cell in cells {
+        let cell = cell.node();
+        if cell.is_selected()? != selected {
+            if cell.is_disabled() {
+                return None;
+            }
+            requests.push(toggle_selection(&cell));
+        }
+    }
+    Some(requests)
+}
+
+fn filtered_child_at_index<'a>(node: &Node<'a>, index: i32) -> Option<Node<'a>> {
+    let index = usize::try_from(index).ok()?;
+    node.filtered_children(&filter).nth(index)
+}
+
+/// Returns the items that can be selected within a node that supports
+/// selection, in tree order. Besides the node's children, these include
+/// items nested in intermediate nodes, such as the cells in the rows
+/// of a grid or the items in the groups of a tree, but not the items
+/// of a nested node that supports selection itself. The selected
+/// children of the AT-SPI Selection interface are counted among these
+/// items, while its child indices refer to the node's children.
+fn selectable_items<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    fn add_items<'a>(node: &Node<'a>, items: &mut Vec<Node<'a>>) {
+        for child in node.filtered_children(&filter) {
+            if NodeWrapper::Node(&child).supports_selection() {
+                continue;
+            }
+            if child.is_selected().is_some() {
+                items.push(child);
+            }
+            add_items(&child, items);
+        }
+    }
+
+    let mut items = Vec::new();
+    add_items(node, &mut items);
+    items
+}
+
+fn selected_children<'a>(node: &Node<'a>) -> impl Iterator<Item = Node<'a>> {
+    selectable_items(node)
+        .into_iter()
+        .filter(|item| item.is_selected() == Some(true))
+}
+
+/// Returns the nearest ancestor of a selectable item that supports
+/// selection.
+fn selection_container<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut ancestor = node.filtered_parent(&filter);
+    while let Some(node) = ancestor {
+        if NodeWrapper::Node(&node).supports_selection() {
+            return Some(node);
+        }
+        ancestor = node.filtered_parent(&filter);
+    }
+    None
+}
+
+/// Notifies the container of an item whose selected state changed.
+pub(crate) fn notify_selection_changes(node: &Node, adapter_id: usize, events: &Sender<Event>) {
+    if let Some(container) = selection_container(node) {
+        events
+            .send_blocking(Event::Object {
+                target: ObjectId::node(adapter_id, container.id()),
+                event: ObjectEvent::SelectionChanged,
+            })
+            .unwrap();
+    }
+}
+
+/// Returns the requests that bring every selectable item in a
+/// multi-selectable node to the given selected state, or `None` if that
+/// isn't possible because the node only supports a single selection
+/// or an item that would need to change is disabled.
+fn set_all_selected(node: &Node, selected: bool) -> Option<Vec<ActionRequest>> {
+    if !node.is_multiselectable() {
+        return None;
+    }
+    let items = selectable_items(node)
+        .into_iter()
+        .filter(|item| item.is_selected() == Some(!selected))
+        .collect::<Vec<_>>();
+    if items.iter().any(|item| item.is_disabled()) {
+        return None;
+    }
+    Some(items.iter().map(toggle_selection).collect())
+}
+
+/// AccessKit has no dedicated selection actions, so the selected state
+/// of an item is toggled by activating it, as a pointer click would.
+fn toggle_selection(node: &Node) -> ActionRequest {
+    ActionRequest {
+        action: Action::Default,
+        target: node.id(),
+        data: None,
+    }
+}
+
+/// Returns true if any of the properties from which
+/// [`NodeWrapper::attributes`] are derived changed, or if the node moved
+/// and so may now inherit a different live setting.
+fn attributes_may_have_changed(changes: &NodeChanges) -> bool {
+    let data = &changes.data;
+    changes.parent_and_index
+        || data.hierarchical_level()
+        || data.size_of_set()
+        || data.position_in_set()
+        || data.aria_role()
+        || data.live()
+        || data.is_live_atomic()
+        || data.live_relevant()
+        || data.is_busy()
+        || data.has_popup()
+        || data.role_description()
+        || data.placeholder()
+        || data.key_shortcuts()
+        || data.sort_direction()
+        || data.aria_current()
+        || data.table_cell_column_index()
+        || data.table_cell_row_index()
+        || data.table_row_index()
+        || data.class_name()
+}
+
+fn live_name(live: Live) -> &'static str {
+    match live {
+        Live::Off => "off",
+        Live::Polite => "polite",
+        Live::Assertive => "assertive",
+    }
+}
+
+/// The AT-SPI relation types used for each kind of AccessKit relation
+/// and for its reverse, if AT-SPI defines one.
+const RELATION_TYPES: [(Relation, RelationType, Option<RelationType>); 9] = [
+    (
+        Relation::LabelledBy,
+        RelationType::LabelledBy,
+        Some(RelationType::LabelFor),
+    ),
+    (
+        Relation::Controls,
+        RelationType::ControllerFor,
+        Some(RelationType::ControlledBy),
+    ),
+    (
+        Relation::DescribedBy,
+        RelationType::DescribedBy,
+        Some(RelationType::DescriptionFor),
+    ),
+    (
+        Relation::FlowTo,
+        RelationType::FlowsTo,
+        Some(RelationType::FlowsFrom),
+    ),
+    (
+        Relation::Details,
+        RelationType::Details,
+        Some(RelationType::DetailsFor),
+    ),
+    (
+        Relation::ErrorMessage,
+        RelationType::ErrorMessage,
+        Some(RelationType::ErrorFor),
+    ),
+    (Relation::MemberOf, RelationType::MemberOf, None),
+    (Relation::PopupFor, RelationType::PopupFor, None),
+    (Relation::RadioGroup, RelationType::MemberOf, None),
+];
+
+/// Returns the IDs of the included nodes to which a node is related,
+/// grouped by AT-SPI relation type.
+fn relation_set(node: &Node) -> Vec<(RelationType, Vec<NodeId>)> {
+    let mut relations = Vec::new();
+    for (relation, forward, reverse) in RELATION_TYPES {
+        add_relation_targets(&mut relations, forward, node.relations(relation));
+        if let Some(reverse) = reverse {
+            add_relation_targets(&mut relations, reverse, node.reverse_relations(relation));
+        }
+    }
+    if node.role() == Role::TreeItem {
+        add_relation_targets(
+            &mut relations,
+            RelationType::NodeChildOf,
+            tree_item_parent(node).into_iter(),
+        );
+    }
+    relations
+}
+
+/// Adds the included nodes among `targets` to the entry for the given
+/// relation type, since more than one relation can map to the same type.
+fn add_relation_targets<'a>(
+    relations: &mut Vec<(RelationType, Vec<NodeId>)>,
+    relation_type: RelationType,
+    targets: impl Iterator<Item = Node<'a>>,
+) {
+    for target in targets.filter(|target| filter(target) == FilterResult::Include) {
+        let index = match relations.iter().position(|(t, _)| *t == relation_type) {
+            Some(index) => index,
+            None => {
+                relations.push((relation_type, Vec::new()));
+                relations.len() - 1
+            }
+        };
+        let ids = &mut relations[index].1;
+        if !ids.contains(&target.id()) {
+            ids.push(target.id());
+        }
+    }
+}
+
+/// Returns the tree item that contains the given one, or the tree itself
+/// for a top-level item.
+fn tree_item_parent<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    let mut ancestor = node.filtered_parent(&filter);
+    while let Some(node) = ancestor {
+        if matches!(node.role(), Role::TreeItem | Role::Tree) {
+            return Some(node);
+        }
+        ancestor = node.filtered_parent(&filter);
+    }
+    None
+}
+
+/// The character used in the AT-SPI text of a node to stand in for
+/// an embedded object.
+const EMBEDDED_OBJECT_CHARACTER: char = '\u{fffc}';
+
+/// The text of a node that supports text ranges, as seen through
+/// AT-SPI: an embedded object character stands in for each included
+/// descendant that contributes no text, such as an image, so that
+/// offsets into this text differ from the character indices of
+/// AccessKit text positions. Also holds the links within the text.
+pub(crate) struct Hypertext {
+    /// The number of characters in the node's text, not counting
+    /// embedded objects.
+    text_length: usize,
+    /// The embedded objects in document order, each with the index of
+    /// the character in the node's text that it precedes.
+    objects: Vec<(usize, NodeId)>,
+    /// The links in document order, with their start and end offsets.
+    links: Vec<(NodeId, usize, usize)>,
+}
+
+impl Hypertext {
+    pub(crate) fn new(node: &Node) -> Self {
+        let mut hypertext = Self {
+            text_length: 0,
+            objects: Vec::new(),
+            links: Vec::new(),
+        };
+        hypertext.add_children(node);
+        hypertext
+    }
+
+    fn add_children(&mut self, node: &Node) {
+        for child in node.children() {
+            if child.role() == Role::InlineTextBox {
+                self.text_length += child.value().map_or(0, |value| value.chars().count());
+                continue;
+            }
+            let text_length = self.text_length;
+            let start = self.character_count();
+            let (objects, links) = (self.objects.len(), self.links.len());
+            self.add_children(&child);
+            match filter(&child) {
+                FilterResult::Include => (),
+                FilterResult::ExcludeNode => continue,
+                FilterResult::ExcludeSubtree => {
+                    self.objects.truncate(objects);
+                    self.links.truncate(links);
+                    continue;
+                }
+            }
+            if self.text_length == text_length {
+                self.objects.truncate(objects);
+                self.links.truncate(links);
+                self.objects.push((text_length, child.id()));
+            }
+            if child.role() == Role::Link {
+                self.links
+                    .insert(links, (child.id(), start, self.character_count()));
+            }
+        }
+    }
+
+    /// The number of characters in the text, including embedded objects.
+    pub(crate) fn character_count(&self) -> usize {
+        self.text_length + self.objects.len()
+    }
+
+    /// Returns the offset of the given position in the node's text,
+    /// which is before any embedded objects at that position, except
+    /// at the end of the text.
+    pub(crate) fn offset_from_index(&self, index: usize) -> usize {
+        if index >= self.text_length {
+            return index + self.objects.len();
+        }
+        index
+            + self
+                .objects
+                .partition_point(|(object_index, _)| *object_index < index)
+    }
+
+    /// Returns the position in the node's text of the given offset,
+    /// which is just after the embedded objects that precede it.
+    pub(crate) fn index_from_offset(&self, offset: usize) -> usize {
+        offset
+            - self
+                .objects
+                .iter()
+                .enumerate()
+                .take_while(|(i, (object_index, _))| object_index + i < offset)
+                .count()
+    }
+
+    /// Returns the embedded object at the given offset, if any.
+    pub(crate) fn object_at(&self, offset: usize) -> Option<NodeId> {
+        let index = self.index_from_offset(offset);
+        let i = offset - index;
+        match self.objects.get(i) {
+            Some((object_index, id)) if *object_index == index => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Returns the node's text with an embedded object character
+    /// for each embedded object.
+    pub(crate) fn text(&self, node: &Node) -> String {
+        let mut objects = self.objects.iter().peekable();
+        let mut text = String::new();
+        for (index, c) in node.document_range().text().chars().enumerate() {
+            while objects
+                .next_if(|(object_index, _)| *object_index == index)
+                .is_some()
+            {
+                text.push(EMBEDDED_OBJECT_CHARACTER);
+            }
+            text.push(c);
+        }
+        text.extend(objects.map(|_| EMBEDDED_OBJECT_CHARACTER));
+        text
+    }
+
+    pub(crate) fn links(&self) -> &[(NodeId, usize, usize)] {
+        &self.links
+    }
+
+    /// Returns the start and end offsets of the given link.
+    pub(crate) fn link_offsets(&self, link: NodeId) -> Option<(usize, usize)> {
+        self.links
+            .iter()
+            .find(|(id, _, _)| *id == link)
+            .map(|(_, start, end)| (*start, *end))
+    }
+}
+
+/// Returns the nearest ancestor of a link whose text includes it.
+fn hyperlink_container<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    node.parent().and_then(|parent| text_container(&parent))
+}
+
+/// Converts a character index into an AT-SPI text offset, failing
+/// if the text is too long to be addressed by one.
+fn text_offset(index: usize) -> fdo::Result<i32> {
+    i32::try_from(index).map_err(|_| fdo::Error::Failed("Text is too long.".into()))
+}
+
+fn text_position_from_offset<'a>(
+    node: &'a Node,
+    hypertext: &Hypertext,
+    offset: i32,
+) -> Option<TextPosition<'a>> {
+    let offset = usize::try_from(offset).ok()?;
+    node.text_position_from_global_usv_index(hypertext.index_from_offset(offset))
+}
+
+fn text_range_from_offsets<'a>(
+    node: &'a Node,
+    hypertext: &Hypertext,
+    start_offset: i32,
+    end_offset: i32,
+) -> Option<TextRange<'a>> {
+    let start = text_position_from_offset(node, hypertext, start_offset)?;
+    let end = if end_offset == -1 {
+        node.document_range().end()
+    } else {
+        text_position_from_offset(node, hypertext, end_offset)?
+    };
+    let mut range = start.to_degenerate_range();
+    range.set_end(end);
+    Some(range)
+}
+
+/// Returns the part of `bounds` that `ScrollTo` should bring into view,
+/// or `None` if the whole node should be.
+fn scroll_target_rect(bounds: Rect, scroll_type: ScrollType) -> Option<Rect> {
+    let Rect { x0, y0, x1, y1 } = bounds;
+    match scroll_type {
+        ScrollType::TopLeft => Some(Rect::new(x0, y0, x0, y0)),
+        ScrollType::BottomRight => Some(Rect::new(x1, y1, x1, y1)),
+        ScrollType::TopEdge => Some(Rect::new(x0, y0, x1, y0)),
+        ScrollType::BottomEdge => Some(Rect::new(x0, y1, x1, y1)),
+        ScrollType::LeftEdge => Some(Rect::new(x0, y0, x0, y1)),
+        ScrollType::RightEdge => Some(Rect::new(x1, y0, x1, y1)),
+        ScrollType::Anywhere => None,
+    }
+}
+
+fn text_range_extents(
+    node: &Node,
+    range: &TextRange,
+    window_bounds: &WindowBounds,
+    coord_type: CoordType,
+) -> AtspiRect {
+    let bounds = range
+        .bounding_boxes()
+        .into_iter()
+        .reduce(|bounds, rect| bounds.union(rect));
+    match bounds {
+        Some(bounds) => {
+            let top_left = window_bounds.top_left(coord_type, node);
+            let new_origin = Point::new(top_left.x + bounds.x0, top_left.y + bounds.y0);
+            bounds.with_origin(new_origin).into()
+        }
+        None => AtspiRect::INVALID,
+    }
+}
+
+/// Returns the bounds of the sentence containing the character
+/// at `index`, treating a run of terminal punctuation followed by
+/// whitespace, or by the end of the text, as the end of a sentence.
+/// The trailing whitespace belongs to the sentence it follows.
+fn sentence_bounds(text: &[char], index: usize) -> (usize, usize) {
+    let is_terminator = |c: &char| matches!(c, '.' | '!' | '?');
+    let mut start = 0;
+    let mut i = 0;
+    while i < text.len() {
+        if !is_terminator(&text[i]) {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < text.len() && is_terminator(&text[end]) {
+            end += 1;
+        }
+        if end < text.len() && !text[end].is_whitespace() {
+            i = end;
+            continue;
+        }
+        while end < text.len() && text[end].is_whitespace() {
+            end += 1;
+        }
+        if index < end {
+            return (start, end);
+        }
+        start = end;
+        i = end;
+    }
+    (start, text.len())
+}
+
+#[derive(Clone)]
+pub(crate) struct PlatformRootNode {
+    pub(crate) app_context: Weak<RwLock<AppContext>>,
+}
+
+impl PlatformRootNode {
+    pub fn new(app_context: &Arc<RwLock<AppContext>>) -> Self {
+        Self {
+            app_context: Arc::downgrade(app_context),
+        }
+    }
+
+    pub fn resolve_app_context<F, T>(&self, f: F) -> fdo::Result<T>
+    where
+        F: FnOnce(&AppContext) -> T,
+    {
+        let app_context = self
+            .app_context
+            .upgrade()
+            .ok_or_else(|| unknown_object(&ObjectId::root()))?;
+        let app_context = app_context.read().unwrap();
+        Ok(f(&app_context))
+    }
+
+    /// Returns the root node of each of the application's windows.
+    pub fn children(&self) -> fdo::Result<Vec<ObjectRef>> {
+        self.resolve_app_context(|app_context| {
+            app_context
+                .adapter_contexts()
+                .map(|context| {
+                    let root_id = context.read_tree().state().root().id();
+                    ObjectRef::Managed(ObjectId::node(context.adapter_id, root_id))
+                })
+                .collect()
+        })
+    }
+}
+
+/// Removes a window's adapter from the application, returning the events
+/// that tell assistive technologies the window's root is gone.
+pub(crate) fn remove_window(
+    app_context: &mut AppContext,
+    adapter_id: usize,
+    root_id: NodeId,
+) -> [Event; 2] {
+    app_context.remove_adapter(adapter_id);
+    [
+        Event::Object {
+            target: ObjectId::node(adapter_id, root_id),
+            event: ObjectEvent::StateChanged(State::Defunct, true),
+        },
+        Event::Object {
+            target: ObjectId::root(),
+            event: ObjectEvent::ChildRemoved(ObjectRef::Managed(ObjectId::node(
+                adapter_id, root_id,
+            ))),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use accesskit::{
+        ActionHandler, KeyShortcuts, NodeBuilder, NodeClassSet, Tree as TreeData, TreeUpdate,
+    };
+    use accesskit_consumer::{Tree, TreeChangeHandler};
+    use std::num::NonZeroU128;
+
+    use super::*;
+
+    const NODE_ID_1: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(1) });
+    const NODE_ID_2: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(2) });
+    const NODE_ID_3: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(3) });
+    const NODE_ID_4: NodeId = NodeId(unsafe { NonZeroU128::new_unchecked(4) });
+
+    fn sentence_at(text: &str, index: usize) -> &str {
+        let characters = text.chars().collect::<Vec<_>>();
+        let (start, end) = sentence_bounds(&characters, index);
+        let start = text
+            .char_indices()
+            .nth(start)
+            .map_or(text.len(), |(i, _)| i);
+        let end = text.char_indices().nth(end).map_or(text.len(), |(i, _)| i);
+        &text[start..end]
+    }
+
+    #[test]
+    fn sentence_bounds_include_trailing_whitespace() {
+        let text = "Hello world. How are you?  Fine.";
+        assert_eq!(sentence_at(text, 0), "Hello world. ");
+        assert_eq!(sentence_at(text, 11), "Hello world. ");
+        assert_eq!(sentence_at(text, 12), "Hello world. ");
+        assert_eq!(sentence_at(text, 13), "How are you?  ");
+        assert_eq!(sentence_at(text, 26), "How are you?  ");
+        assert_eq!(sentence_at(text, 27), "Fine.");
+        assert_eq!(sentence_at(text, 31), "Fine.");
+    }
+
+    #[test]
+    fn sentence_bounds_group_terminal_punctuation() {
+        let text = "Really?! Yes...  No";
+        assert_eq!(sentence_at(text, 6), "Really?! ");
+        assert_eq!(sentence_at(text, 7), "Really?! ");
+        assert_eq!(sentence_at(text, 9), "Yes...  ");
+        assert_eq!(sentence_at(text, 17), "No");
+    }
+
+    #[test]
+    fn sentence_bounds_ignore_punctuation_inside_words() {
+        let text = "Version 1.5 is out. Try e.g. this.";
+        assert_eq!(sentence_at(text, 9), "Version 1.5 is out. ");
+        assert_eq!(sentence_at(text, 20), "Try e.g. ");
+        assert_eq!(sentence_at(text, 29), "this.");
+    }
+
+    #[test]
+    fn sentence_bounds_without_terminator() {
+        assert_eq!(sentence_at("no punctuation here", 5), "no punctuation here");
+        assert_eq!(sentence_at("", 0), "");
+        assert_eq!(sentence_at("Done.", 4), "Done.");
+        assert_eq!(sentence_at("Done.", 5), "");
+    }
+
+    #[test]
+    fn sentence_bounds_count_characters() {
+        let text = "Caf\u{e9}! \u{1f60a} ok.";
+        assert_eq!(sentence_at(text, 4), "Caf\u{e9}! ");
+        assert_eq!(sentence_at(text, 6), "\u{1f60a} ok.");
+    }
+
+    fn content(offset: usize, text: &str) -> Option<TextBoxContent> {
+        Some(TextBoxContent {
+            offset,
+            text: text.into(),
+        })
+    }
+
+    fn removed(offset: usize, text: &str) -> TextEdit {
+        TextEdit::Removed {
+            offset,
+            text: text.into(),
+        }
+    }
+
+    fn inserted(offset: usize, text: &str) -> TextEdit {
+        TextEdit::Inserted {
+            offset,
+            text: text.into(),
+        }
+    }
+
+    #[test]
+    fn text_edits_within_a_text_box() {
+        assert_eq!(
+            text_edits(&[(content(10, "helo world"), content(10, "hello world"))]),
+            vec![inserted(13, "l")]
+        );
+        assert_eq!(
+            text_edits(&[(content(10, "hello world"), content(10, "hello"))]),
+            vec![removed(15, " world")]
+        );
+        assert_eq!(
+            text_edits(&[(content(0, "a cat sat"), content(0, "a dog sat"))]),
+            vec![removed(2, "cat"), inserted(2, "dog")]
+        );
+        assert_eq!(
+            text_edits(&[(content(0, "aaa"), content(0, "aaaa"))]),
+            vec![inserted(3, "a")]
+        );
+        assert!(text_edits(&[(content(4, "same"), content(4, "same"))]).is_empty());
+    }
+
+    #[test]
+    fn text_edits_count_characters() {
+        assert_eq!(
+            text_edits(&[(content(1, "caf\u{e9}"), content(1, "caf\u{e9}\u{1f60a}!"))]),
+            vec![inserted(5, "\u{1f60a}!")]
+        );
+        assert_eq!(
+            text_edits(&[(content(1, "\u{1f60a}\u{1f60a}x"), content(1, "\u{1f60a}x"))]),
+            vec![removed(2, "\u{1f60a}")]
+        );
+    }
+
+    #[test]
+    fn text_edits_for_added_and_removed_text_boxes() {
+        assert_eq!(
+            text_edits(&[(None, content(6, "new line\n"))]),
+            vec![inserted(6, "new line\n")]
+        );
+        assert_eq!(
+            text_edits(&[(content(6, "old line\n"), None)]),
+            vec![removed(6, "old line\n")]
+        );
+        assert!(text_edits(&[(None, content(6, "")), (content(6, ""), None)]).is_empty());
+    }
+
+    #[test]
+    fn text_edits_apply_in_order() {
+        // Old text: "one\ntwo\nthree\n"; the first line is edited,
+        // the second removed, and a new line added at the end.
+        let boxes = [
+            (content(0, "one\n"), content(0, "one!\n")),
+            (content(4, "two\n"), None),
+            (None, content(11, "four\n")),
+            (content(8, "three\n"), content(5, "three\n")),
+        ];
+        let edits = text_edits(&boxes);
+        assert_eq!(
+            edits,
+            vec![
+                removed(4, "two\n"),
+                inserted(3, "!"),
+                inserted(11, "four\n")
+            ]
+        );
+        let mut text = "one\ntwo\nthree\n".chars().collect::<Vec<_>>();
+        for edit in edits {
+            match edit {
+                TextEdit::Removed {
+                    offset,
+                    text: removed,
+                } => {
+                    let len = removed.chars().count();
+                    assert_eq!(
+                        text.splice(offset..offset + len, []).collect::<String>(),
+                        removed
+                    );
+                }
+                TextEdit::Inserted {
+                    offset,
+                    text: inserted,
+                } => {
+                    text.splice(offset..offset, inserted.chars());
+                }
+            }
+        }
+        assert_eq!(text.into_iter().collect::<String>(), "one!\nthree\nfour\n");
+    }
+
+    fn text_field_tree(configure: impl FnOnce(&mut NodeBuilder)) -> Tree {
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: vec![
+                (NODE_ID_1, {
+                    let mut builder = NodeBuilder::new(Role::Window);
+                    builder.set_children(vec![NODE_ID_2]);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_2, {
+                    let mut builder = NodeBuilder::new(Role::TextField);
+                    builder.set_children(vec![NODE_ID_3]);
+                    configure(&mut builder);
+                    builder.build(&mut classes)
+                }),
+                (NODE_ID_3, {
+                    let mut builder = NodeBuilder::new(Role::InlineTextBox);
+                    builder.set_value("hi");
+                    builder.set_character_lengths([1, 1]);
+                    builder.build(&mut classes)
+                }),
+            ],
+            tree: Some(TreeData::new(NODE_ID_1)),
+            focus: None,
+        };
+        Tree::new(update)
+    }
+
+    fn has_editable_text(tree: &Tree) -> bool {
+        let node = tree.state().node_by_id(NODE_ID_2).unwrap();
+        NodeWrapper::Node(&node)
+            .interfaces()
+            .contains(Interface::EditableText)
+    }
+
+    #[test]
+    fn editable_text_requires_an_editing_action() {
+        assert!(!has_editable_text(&text_field_tree(|_| ())));
+        assert!(has_editable_text(&text_field_tree(|builder| {
+            builder.add_action(Action::SetValue);
+        })));
+        assert!(has_editable_text(&text_field_tree(|builder| {
+            builder.add_action(Action::ReplaceSelectedText);
+        })));
+        assert!(!has_editable_text(&text_field_tree(|builder| {
+            builder.add_action(Action::SetValue);
+            builder.set_read_only();
+        })));
+        assert!(!has_editable_text(&text_field_tree(|builder| {
+            builder.add_action(Action::Copy);
+        })));
+    }
+
+    /// Builds a tree from `(id, role, children)` triples, the first of which
+    /// is the root, configuring each node with `configure`.
+    fn build_tree(
+        nodes: &[(u128, Role, &[u128])],
+        mut configure: impl FnMut(u128, &mut NodeBuilder),
+    ) -> Tree {
+        let id = node_id;
+        let mut classes = NodeClassSet::new();
+        let update = TreeUpdate {
+            nodes: nodes
+                .iter()
+                .map(|(node_id, role, children)| {
+                    let mut builder = NodeBuilder::new(*role);
+                    builder.set_children(children.iter().copied().map(id).collect::<Vec<_>>());
+                    configure(*node_id, &mut builder);
+                    (id(*node_id), builder.build(&mut classes))
+                })
+                .collect(),
+            tree: Some(TreeData::new(id(nodes[0].0))),
+            focus: None,
+        };
+        Tree::new(update)
+    }
+
+    fn node_id(id: u128) -> NodeId {
+        NodeId(NonZeroU128::new(id).unwrap())
+    }
+
+    fn node(tree: &Tree, id: u128) -> Node<'_> {
+        tree.state().node_by_id(node_id(id)).unwrap()
+    }
+
+    fn ids<'a>(nodes: impl IntoIterator<Item = Node<'a>>) -> Vec<u128> {
+        nodes.into_iter().map(|node| node.id().0.get()).collect()
+    }
+
+    fn grid_tree(multiselectable: bool, disabled: &[u128]) -> Tree {
+        build_tree(
+            &[
+                (1, Role::Window, &[2]),
+                (2, Role::Grid, &[3, 4]),
+                (3, Role::Row, &[5, 6]),
+                (4, Role::Row, &[7, 8]),
+                (5, Role::Cell, &[]),
+                (6, Role::Cell, &[]),
+                (7, Role::Cell, &[]),
+                (8, Role::Cell, &[9]),
+                (9, Role::ListBox, &[10]),
+                (10, Role::ListBoxOption, &[]),
+            ],
+            |id, builder| {
+                if id == 2 && multiselectable {
+                    builder.set_multiselectable();
+                }
+                if matches!(id, 5..=8 | 10) {
+                    builder.set_selected(id == 6 || id == 8 || id == 10);
+                }
+                if disabled.contains(&id) {
+                    builder.set_disabled();
+                }
+            },
+        )
+    }
+
+    #[test]
+    fn selectable_items_in_grid_rows() {
+        let tree = grid_tree(true, &[]);
+        let grid = node(&tree, 2);
+        assert_eq!(ids(selectable_items(&grid)), vec![5, 6, 7, 8]);
+        assert_eq!(ids(selected_children(&grid)), vec![6, 8]);
+        let cell = node(&tree, 7);
+        assert_eq!(ids(selection_container(&cell)), vec![2]);
+        let option = node(&tree, 10);
+        assert_eq!(ids(selection_container(&option)), vec![9]);
+    }
+
+    #[test]
+    fn selectable_items_in_tree_groups() {
+        let tree = build_tree(
+            &[
+                (1, Role::Window, &[2]),
+                (2, Role::Tree, &[3, 4]),
+                (3, Role::TreeItem, &[5]),
+                (4, Role::TreeItem, &[]),
+                (5, Role::Group, &[6, 7]),
+                (6, Role::TreeItem, &[]),
+                (7, Role::TreeItem, &[]),
+            ],
+            |id, builder| {
+                if id != 2 && id != 5 {
+                    builder.set_selected(id == 7);
+                }
+            },
+        );
+        let tree_node = node(&tree, 2);
+        assert_eq!(ids(selectable_items(&tree_node)), vec![3, 6, 7, 4]);
+        assert_eq!(ids(selected_children(&tree_node)), vec![7]);
+        let item = node(&tree, 6);
+        assert_eq!(ids(selection_container(&item)), vec![2]);
+    }
+
+    fn targets(requests: Option<Vec<ActionRequest>>) -> Option<Vec<u128>> {
+        requests.map(|requests| {
+            requests
+                .into_iter()
+                .map(|request| {
+                    assert_eq!(request.action, Action::Default);
+                    request.target.0.get()
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn select_all_and_clear_selection_change_only_differing_items() {
+        let tree = grid_tree(true, &[]);
+        let grid = node(&tree, 2);
+        assert_eq!(targets(set_all_selected(&grid, true)), Some(vec![5, 7]));
+        assert_eq!(targets(set_all_selected(&grid, false)), Some(vec![6, 8]));
+
+        let tree = grid_tree(false, &[]);
+        let grid = node(&tree, 2);
+        assert_eq!(targets(set_all_selected(&grid, true)), None);
+        assert_eq!(targets(set_all_selected(&grid, false)), None);
+
+        let tree = grid_tree(true, &[5]);
+        let grid = node(&tree, 2);
+        assert_eq!(targets(set_all_selected(&grid, true)), None);
+        assert_eq!(targets(set_all_selected(&grid, false)), Some(vec![6, 8]));
+    }
+
+    #[test]
+    fn table_interfaces_and_cell_indices() {
+        // | 5 | 6 |
+        // |   | 7 |
+        let tree = build_tree(
+            &[
+                (1, Role::Window, &[2]),
+                (2, Role::Grid, &[3, 4]),
+                (3, Role::Row, &[5, 6]),
+                (4, Role::Row, &[7]),
+                (5, Role::RowHeader, &[]),
+                (6, Role::Cell, &[]),
+                (7, Role::Cell, &[]),
+            ],
+            |id, builder| {
+                if id == 5 {
+                    builder.set_table_cell_row_span(2);
+                }
+            },
+        );
+        let interfaces = |id| NodeWrapper::Node(&node(&tree, id)).interfaces();
+        assert!(interfaces(2).contains(Interface::Table));
+        assert!(!interfaces(2).contains(Interface::TableCell));
+        assert!(!interfaces(3).contains(Interface::Table));
+        assert!(!interfaces(3).contains(Interface::TableCell));
+        assert!(interfaces(5).contains(Interface::TableCell));
+        assert!(interfaces(7).contains(Interface::TableCell));
+
+        let table = node(&tree, 2).as_table().unwrap();
+        let cell_id = |cell: Option<TableCell>| cell.map(|cell| cell.node().id().0.get());
+        assert_eq!(cell_id(table_cell_at(&table, 1, 0)), Some(5));
+        assert_eq!(cell_id(table_cell_at(&table, 1, 1)), Some(7));
+        assert_eq!(cell_id(table_cell_at(&table, -1, 0)), None);
+        assert_eq!(cell_id(table_cell_at(&table, 0, 2)), None);
+        assert_eq!(cell_id(table_cell_at_index(&table, 1)), Some(6));
+        assert_eq!(cell_id(table_cell_at_index(&table, 2)), Some(5));
+        assert_eq!(cell_id(table_cell_at_index(&table, 3)), Some(7));
+        assert_eq!(cell_id(table_cell_at_index(&table, 4)), None);
+        assert_eq!(cell_id(table_cell_at_index(&table, -1)), None);
+        assert_eq!(ids(table.row_headers(1)), vec![5]);
+    }
+
+    #[test]
+    fn table_row_selection() {
+        let row_tree = |multiselectable: bool, disabled: &[u128]| {
+            build_tree(
+                &[
+                    (1, Role::Window, &[2]),
+                    (2, Role::Grid, &[3, 4]),
+                    (3, Role::Row, &[5]),
+                    (4, Role::Row, &[6]),
+                    (5, Role::Cell, &[]),
+                    (6, Role::Cell, &[]),
+                ],
+                |id, builder| {
+                    if id == 2 && multiselectable {
+                        builder.set_multiselectable();
+                    }
+                    if matches!(id, 3 | 4) {
+                        builder.set_selected(id == 3);
+                    }
+                    if disabled.contains(&id) {
+                        builder.set_disabled();
+                    }
+                },
+            )
+        };
+
+        let tree = row_tree(true, &[]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(targets(set_row_selected(&table, 0, true)), Some(vec![]));
+        assert_eq!(targets(set_row_selected(&table, 1, true)), Some(vec![4]));
+        assert_eq!(targets(set_row_selected(&table, 0, false)), Some(vec![3]));
+        assert_eq!(targets(set_row_selected(&table, 2, true)), None);
+        assert_eq!(targets(set_row_selected(&table, -1, true)), None);
+
+        let tree = row_tree(false, &[4]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(targets(set_row_selected(&table, 1, true)), None);
+        assert_eq!(targets(set_row_selected(&table, 0, false)), None);
+
+        let tree = grid_tree(true, &[]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(targets(set_row_selected(&table, 0, true)), None);
+    }
+
+    #[test]
+    fn table_column_selection() {
+        let tree = grid_tree(true, &[]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(
+            targets(set_column_selected(&table, 0, true)),
+            Some(vec![5, 7])
+        );
+        assert_eq!(targets(set_column_selected(&table, 1, true)), Some(vec![]));
+        assert_eq!(
+            targets(set_column_selected(&table, 1, false)),
+            Some(vec![6, 8])
+        );
+        assert_eq!(targets(set_column_selected(&table, 2, true)), None);
+
+        let tree = grid_tree(false, &[]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(targets(set_column_selected(&table, 0, true)), None);
+
+        let tree = grid_tree(true, &[7]);
+        let table = node(&tree, 2).as_table().unwrap();
+        assert_eq!(targets(set_column_selected(&table, 0, true)), None);
+        assert_eq!(
+            targets(set_column_selected(&table, 1, false)),
+            Some(vec![6, 8])
+        );
+    }
+
+    #[test]
+    fn hypertext_embedded_objects_and_links() {
+        let tree = build_tree(
+            &[
+                (1, Role::Window, &[2]),
+                (2, Role::Document, &[3, 4, 5, 6, 9, 11]),
+                (3, Role::InlineTextBox, &[]),
+                (4, Role::Image, &[]),
+                (5, Role::Link, &[7]),
+                (6, Role::Link, &[8]),
+                (7, Role::InlineTextBox, &[]),
+                (8, Role::Image, &[]),
+                (9, Role::GenericContainer, &[10]),
+                (10, Role::InlineTextBox, &[]),
+                (11, Role::Image, &[]),
+            ],
+            |id, builder| {
+                let value = match id {
+                    3 => "Hi ",
+                    7 => "there",
+                    10 => ".",
+                    11 => {
+                        builder.set_hidden();
+                        return;
+                    }
+                    _ => return,
+                };
+                builder.set_value(value);
+                builder.set_character_lengths(vec![1; value.len()]);
+            },
+        );
+        let document = node(&tree, 2);
+        let hypertext = Hypertext::new(&document);
+        assert_eq!(hypertext.text(&document), "Hi \u{fffc}there\u{fffc}.");
+        assert_eq!(hypertext.character_count(), 11);
+
+        assert_eq!(hypertext.object_at(3), Some(node_id(4)));
+        assert_eq!(hypertext.object_at(4), None);
+        assert_eq!(hypertext.object_at(9), Some(node_id(6)));
+        assert_eq!(hypertext.object_at(11), None);
+
+        let offsets = [0, 3, 4, 8, 9].map(|index| hypertext.offset_from_index(index));
+        assert_eq!(offsets, [0, 3, 5, 9, 11]);
+        let indices = [0, 3, 4, 9, 10, 11].map(|offset| hypertext.index_from_offset(offset));
+        assert_eq!(indices, [0, 3, 3, 8, 8, 9]);
+
+        assert_eq!(hypertext.links(), [(node_id(5), 4, 9), (node_id(6), 9, 10)]);
+        assert_eq!(hypertext.link_offsets(node_id(6)), Some((9, 10)));
+        assert_eq!(hypertext.link_offsets(node_id(4)), None);
+        let link = node(&tree, 5);
+        assert_eq!(ids(hyperlink_container(&link)), vec![2]);
+    }
+
+    #[test]
+    fn relation_sets() {
+        let tree = build_tree(
+            &[
+                (1, Role::Window, &[2, 3, 4, 5, 6, 7, 8, 9, 10]),
+                (2, Role::StaticText, &[]),
+                (3, Role::TextField, &[]),
+                (4, Role::StaticText, &[]),
+                (5, Role::ListBox, &[]),
+                (6, Role::RadioButton, &[]),
+                (7, Role::RadioButton, &[]),
+                (8, Role::GenericContainer, &[]),
+                (9, Role::Group, &[]),
+                (10, Role::Tree, &[11]),
+                (11, Role::TreeItem, &[12]),
+                (12, Role::Group, &[13]),
+                (13, Role::TreeItem, &[]),
+            ],
+            |id, builder| match id {
+                3 => {
+                    builder.set_labelled_by(vec![node_id(2), node_id(8)]);
+                    builder.set_described_by(vec![node_id(4)]);
+                    builder.set_controls(vec![node_id(5)]);
+                }
+                6 => builder.set_radio_group(vec![node_id(6), node_id(7)]),
+                7 => {
+                    builder.set_member_of(node_id(9));
+                    builder.set_radio_group(vec![node_id(6), node_id(7)]);
+                }
+                _ => (),
+            },
+        );
+        let relations = |id| {
+            relation_set(&node(&tree, id))
+                .into_iter()
+                .map(|(relation_type, ids)| {
+                    (
+                        relation_type,
+                        ids.into_iter().map(|id| id.0.get()).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            relations(3),
+            vec![
+                (RelationType::LabelledBy, vec![2]),
+                (RelationType::ControllerFor, vec![5]),
+                (RelationType::DescribedBy, vec![4]),
+            ]
+        );
+        assert_eq!(relations(2), vec![(RelationType::LabelFor, vec![3])]);
+        assert_eq!(relations(4), vec![(RelationType::DescriptionFor, vec![3])]);
+        assert_eq!(relations(5), vec![(RelationType::ControlledBy, vec![3])]);
+        assert_eq!(relations(6), vec![(RelationType::MemberOf, vec![6, 7])]);
+        assert_eq!(relations(7), vec![(RelationType::MemberOf, vec![9, 6, 7])]);
+        assert!(relations(9).is_empty());
+        assert_eq!(relations(11), vec![(RelationType::NodeChildOf, vec![10])]);
+        assert_eq!(relations(13), vec![(RelationType::NodeChildOf, vec![11])]);
+    }
+
+    #[test]
+    fn key_combinations_use_gtk_accelerator_syntax() {
+        let atspi = |s: &str| key_combination_to_atspi(&s.parse::<KeyCombination>().unwrap());
+        assert_eq!(atspi("a"), "a");
+        assert_eq!(
+            atspi("Meta+Shift+Alt+Control+x"),
+            "<Control><Alt><Shift><Super>x"
+        );
+        assert_eq!(atspi("Control+Space"), "<Control>space");
+        assert_eq!(atspi("Control+Plus"), "<Control>plus");
+        assert_eq!(atspi("Alt+Enter"), "<Alt>Return");
+        assert_eq!(atspi("ArrowUp"), "Up");
+        assert_eq!(atspi("Shift+PageDown"), "<Shift>Page_Down");
+        assert_eq!(atspi("Control+,"), "<Control>comma");
+        assert_eq!(atspi("F10"), "F10");
+    }
+
+    #[test]
+    fn key_names_match_gdk_keysyms() {
+        for (key, keysym) in [
+            ("Space", "space"),
+            ("Plus", "plus"),
+            ("Backspace", "BackSpace"),
+            ("Enter", "Return"),
+            ("ArrowUp", "Up"),
+            ("ArrowDown", "Down"),
+            ("ArrowLeft", "Left"),
+            ("ArrowRight", "Right"),
+            ("PageUp", "Page_Up"),
+            ("PageDown", "Page_Down"),
+            ("CapsLock", "Caps_Lock"),
+            ("NumLock", "Num_Lock"),
+            ("ScrollLock", "Scroll_Lock"),
+            ("PrintScreen", "Print"),
+            ("ContextMenu", "Menu"),
+            ("AltGraph", "ISO_Level3_Shift"),
+            ("BrowserBack", "XF86Back"),
+            ("BrowserForward", "XF86Forward"),
+            ("BrowserRefresh", "XF86Refresh"),
+            ("BrowserStop", "XF86Stop"),
+            ("BrowserSearch", "XF86Search"),
+            ("BrowserFavorites", "XF86Favorites"),
+            ("BrowserHome", "XF86HomePage"),
+            ("AudioVolumeUp", "XF86AudioRaiseVolume"),
+            ("AudioVolumeDown", "XF86AudioLowerVolume"),
+            ("AudioVolumeMute", "XF86AudioMute"),
+            ("MediaPlayPause", "XF86AudioPlay"),
+            ("MediaStop", "XF86AudioStop"),
+            ("MediaTrackNext", "XF86AudioNext"),
+            ("MediaTrackPrevious", "XF86AudioPrev"),
+            ("Copy", "XF86Copy"),
+            ("Cut", "XF86Cut"),
+            ("Paste", "XF86Paste"),
+            ("ZoomIn", "XF86ZoomIn"),
+            ("ZoomOut", "XF86ZoomOut"),
+            ("Eject", "XF86Eject"),
+            ("!", "exclam"),
+            ("\"", "quotedbl"),
+            ("#", "numbersign"),
+            ("$", "dollar"),
+            ("%", "percent"),
+            ("&", "ampersand"),
+            ("'", "apostrophe"),
+            ("(", "parenleft"),
+            (")", "parenright"),
+            ("*", "asterisk"),
+            (",", "comma"),
+            ("-", "minus"),
+            (".", "period"),
+            ("/", "slash"),
+            (":", "colon"),
+            (";", "semicolon"),
+            ("<", "less"),
+            ("=", "equal"),
+            (">", "greater"),
+            ("?", "question"),
+            ("@", "at"),
+            ("[", "bracketleft"),
+            ("\\", "backslash"),
+            ("]", "bracketright"),
+            ("^", "asciicircum"),
+            ("_", "underscore"),
+            ("`", "grave"),
+            ("{", "braceleft"),
+            ("|", "bar"),
+            ("}", "braceright"),
+            ("~", "asciitilde"),
+            ("Tab", "Tab"),
+            ("Escape", "Escape"),
+            ("Delete", "Delete"),
+            ("Insert", "Insert"),
+            ("Home", "Home"),
+            ("End", "End"),
+            ("Pause", "Pause"),
+            ("F1", "F1"),
+            ("a", "a"),
+            ("A", "A"),
+            ("1", "1"),
+        ] {
+            assert_eq!(keysym, gtk_key_name(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn action_key_bindings() {
+        let key_bindings = |configure: fn(&mut NodeBuilder)| {
+            let tree = build_tree(
+                &[(1, Role::Window, &[2]), (2, Role::Button, &[])],
+                |id, builder| {
+                    if id == 2 {
+                        builder.set_default_action_verb(DefaultActionVerb::Click);
+                        builder.add_action(Action::Focus);
+                        configure(builder);
+                    }
+                },
+            );
+            let node = node(&tree, 2);
+            let wrapper = NodeWrapper::Node(&node);
+            let actions = wrapper.actions();
+            assert!(matches!(actions[0], NodeAction::Default(_)));
+            assert!(actions.len() > 1);
+            actions
+                .iter()
+                .map(|action| wrapper.action_key_binding(action))
+                .collect::<Vec<_>>()
+        };
+        fn shortcuts(mnemonic: Option<&str>, accelerators: &str) -> KeyShortcuts {
+            let mut shortcuts = accelerators.parse::<KeyShortcuts>().unwrap();
+            shortcuts.mnemonic = mnemonic.map(|mnemonic| mnemonic.parse().unwrap());
+            shortcuts
+        }
+
+        assert!(key_bindings(|_| ())
+            .iter()
+            .all(|binding| binding.is_empty()));
+        let bindings = key_bindings(|builder| {
+            builder.set_key_shortcuts(shortcuts(Some("Alt+o"), "Control+O Meta+O"));
+        });
+        assert_eq!(bindings[0], "<Alt>o;;<Control>O");
+        assert!(bindings[1..].iter().all(|binding| binding.is_empty()));
+        assert_eq!(
+            key_bindings(|builder| builder.set_key_shortcuts(shortcuts(Some("s"), "")))[0],
+            "s;;"
+        );
+        assert_eq!(
+            key_bindings(|builder| builder.set_key_shortcuts(shortcuts(None, "Control+Plus")))[0],
+            ";;<Control>plus"
+        );
+        assert_eq!(
+            key_bindings(|builder| builder.set_access_key("x"))[0],
+            "<Alt>x;;"
+        );
+        assert_eq!(
+            key_bindings(|builder| {
+                builder.set_access_key("x");
+                builder.set_key_shortcuts(shortcuts(Some("y"), ""));
+            })[0],
+            "y;;"
+        );
+    }
+
+    struct LiveRegionHandler(LiveRegionChanges);
+
+    impl TreeChangeHandler for LiveRegionHandler {
+        fn node_added(&mut self, node: &Node) {
+            self.0.node_added(node);
+        }
+        fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node, _: &NodeChanges) {
+            self.0.node_updated(old_node, new_node);
+        }
+        fn focus_moved(&mut self, _: Option<&DetachedNode>, _: Option<&Node>, _: &TreeState) {}
+        fn node_removed(&mut self, node: &DetachedNode, current_state: &TreeState) {
+            let parent = node.parent_id().and_then(|id| current_state.node_by_id(id));
+            self.0.node_removed(node, parent.as_ref());
+        }
+    }
+
+    fn status_update(classes: &mut NodeClassSet, children: &[(NodeId, &str)]) -> TreeUpdate {
+        let mut nodes = vec![(NODE_ID_1, {
+            let mut builder = NodeBuilder::new(Role::Status);
+            builder.set_live(Live::Polite);
+            builder.set_children(children.iter().map(|(id, _)| *id).collect::<Vec<_>>());
+            builder.build(classes)
+        })];
+        for (id, name) in children {
+            let mut builder = NodeBuilder::new(Role::StaticText);
+            builder.set_name(*name);
+            nodes.push((*id, builder.build(classes)));
+        }
+        TreeUpdate {
+            nodes,
+            tree: Some(TreeData::new(NODE_ID_1)),
+            focus: None,
+        }
+    }
+
+    fn live_announcements(
+        initial: &[(NodeId, &str)],
+        updated: &[(NodeId, &str)],
+    ) -> Vec<(NodeId, String)> {
+        let mut classes = NodeClassSet::new();
+        let mut tree = Tree::new(status_update(&mut classes, initial));
+        let mut handler = LiveRegionHandler(LiveRegionChanges::default());
+        tree.update_and_process_changes(status_update(&mut classes, updated), &mut handler);
+        handler
+            .0
+            .announcements(tree.state())
+            .into_iter()
+            .map(|announcement| {
+                assert_eq!(Live::Polite, announcement.politeness);
+                (announcement.target, announcement.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn live_region_additions_are_announced_once() {
+        assert_eq!(
+            vec![(NODE_ID_1, "Downloaded 3 files".into())],
+            live_announcements(&[], &[(NODE_ID_2, "Downloaded"), (NODE_ID_3, "3 files")])
+        );
+        assert_eq!(
+            vec![(NODE_ID_1, "3 files 2 skipped".into())],
+            live_announcements(
+                &[(NODE_ID_2, "Downloaded")],
+                &[
+                    (NODE_ID_2, "Downloaded"),
+                    (NODE_ID_3, "3 files"),
+                    (NODE_ID_4, "2 skipped"),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn live_region_text_changes_are_announced_once() {
+        assert_eq!(
+            vec![(NODE_ID_1, "Uploaded 4 files".into())],
+            live_announcements(
+                &[(NODE_ID_2, "Downloaded"), (NODE_ID_3, "3 files")],
+                &[(NODE_ID_2, "Uploaded"), (NODE_ID_3, "4 files")]
+            )
+        );
+        assert!(live_announcements(&[(NODE_ID_2, "Done")], &[]).is_empty());
+    }
+
+    struct NullActionHandler;
+
+    impl ActionHandler for NullActionHandler {
+        fn do_action(&self, _request: ActionRequest) {}
+    }
+
+    fn window_context(adapter_id: usize, app_context: &Arc<RwLock<AppContext>>) -> Arc<Context> {
+        let mut classes = NodeClassSet::new();
+        let tree = Tree::new(TreeUpdate {
+            nodes: vec![(
+                NODE_ID_1,
+                NodeBuilder::new(Role::Window).build(&mut classes),
+            )],
+            tree: Some(TreeData::new(NODE_ID_1)),
+            focus: None,
+        });
+        let context = Context::new(adapter_id, tree, Box::new(NullActionHandler), app_context);
+        app_context
+            .write()
+            .unwrap()
+            .push_adapter(adapter_id, &context);
+        context
+    }
+
+    #[test]
+    fn removed_window_is_no_longer_a_child_of_the_application() {
+        let app_context = Arc::new(RwLock::new(AppContext::new(
+            String::new(),
+            String::new(),
+            String::new(),
+        )));
+        let _first = window_context(1, &app_context);
+        let _second = window_context(2, &app_context);
+        let root = PlatformRootNode::new(&app_context);
+        assert_eq!(2, root.children().unwrap().len());
+
+        let events = remove_window(&mut app_context.write().unwrap(), 2, NODE_ID_1);
+        assert_eq!(
+            vec![ObjectRef::Managed(ObjectId::node(1, NODE_ID_1))],
+            root.children().unwrap()
+        );
+        assert!(matches!(
+            &events[0],
+            Event::Object {
+                target,
+                event: ObjectEvent::StateChanged(State::Defunct, true),
+            } if *target == ObjectId::node(2, NODE_ID_1)
+        ));
+        assert!(matches!(
+            &events[1],
+            Event::Object {
+                target,
+                event: ObjectEvent::ChildRemoved(child),
+            } if *target == ObjectId::root()
+                && *child == ObjectRef::Managed(ObjectId::node(2, NODE_ID_1))
+        ));
+    }
+
+    #[test]
+    fn text_offset_overflow() {
+        assert_eq!(text_offset(0).unwrap(), 0);
+        assert_eq!(text_offset(i32::MAX as usize).unwrap(), i32::MAX);
+        assert!(text_offset(i32::MAX as usize + 1).is_err());
+    }
 }
@@ -3,9 +3,11 @@
 // the LICENSE-APACHE file) or the MIT license (found in
 // the LICENSE-MIT file), at your option.
 
-use crate::atspi::OwnedObjectAddress;
+use crate::{atspi::OwnedObjectAddress, context::Context, node::filter};
 use accesskit::{Point, Rect};
+use accesskit_consumer::Node;
 use atspi::CoordType;
+use std::sync::{Arc, Weak};
 
 pub(crate) struct AppContext {
     pub(crate) name: String,
@@ -13,6 +15,9 @@ pub(crate) struct AppContext {
     pub(crate) toolkit_version: String,
     pub(crate) id: Option<i32>,
     pub(crate) desktop_address: Option<OwnedObjectAddress>,
+    /// The adapters of the application's windows, in the order
+    /// they were created.
+    pub(crate) adapters: Vec<(usize, Weak<Context>)>,
 }
 
 impl AppContext {
@@ -23,8 +28,27 @@ impl AppContext {
             toolkit_version,
             id: None,
             desktop_address: None,
+            adapters: Vec::new(),
         }
     }
+
+    pub(crate) fn push_adapter(&mut self, adapter_id: usize, context: &Arc<Context>) {
+        self.adapters.push((adapter_id, Arc::downgrade(context)));
+    }
+
+    pub(crate) fn remove_adapter(&mut self, adapter_id: usize) {
+        self.adapters.retain(|(id, _)| *id != adapter_id);
+    }
+
+    pub(crate) fn adapter_index(&self, adapter_id: usize) -> Option<usize> {
+        self.adapters.iter().position(|(id, _)| *id == adapter_id)
+    }
+
+    pub(crate) fn adapter_contexts(&self) -> impl Iterator<Item = Arc<Context>> + '_ {
+        self.adapters
+            .iter()
+            .filter_map(|(_, context)| context.upgrade())
+    }
 }
 
 #[derive(Default)]
@@ -34,9 +58,15 @@ pub(crate) struct WindowBounds {
 }
 
 impl WindowBounds {
-    pub(crate) fn top_left(&self, coord_type: CoordType, is_root: bool) -> Point {
+    /// Returns the position, in the coordinate system identified by
+    /// `coord_type`, of the origin of the coordinate space that the bounds
+    /// of `node` are expressed in.
+    pub(crate) fn top_left(&self, coord_type: CoordType, node: &Node) -> Point {
+        let is_root = node.is_root();
         match coord_type {
-            CoordType::Screen if is_root => self.outer.origin(),
+            // The parent of the root is the application, which has no
+            // extents, so parent coordinates are screen coordinates.
+            CoordType::Screen | CoordType::Parent if is_root => self.outer.origin(),
             CoordType::Screen => self.inner.origin(),
             CoordType::Window if is_root => Point::ZERO,
             CoordType::Window => {
@@ -47,7 +77,24 @@ impl WindowBounds {
                     inner_position.y - outer_position.y,
                 )
             }
-            _ => unimplemented!(),
+            CoordType::Parent => {
+                // Transforms can scale the parent's coordinate space, so the
+                // offset is computed in window coordinates, which are
+                // in physical pixels like the ones we report.
+                let top_left = self.top_left(CoordType::Window, node);
+                let parent_top_left = node
+                    .filtered_parent(&filter)
+                    .and_then(|parent| {
+                        let bounds = parent.bounding_box()?;
+                        let top_left = self.top_left(CoordType::Window, &parent);
+                        Some(Point::new(top_left.x + bounds.x0, top_left.y + bounds.y0))
+                    })
+                    .unwrap_or(Point::ZERO);
+                Point::new(
+                    top_left.x - parent_top_left.x,
+                    top_left.y - parent_top_left.y,
+                )
+            }
         }
     }
 }
@@ -4,7 +4,9 @@
 // the LICENSE-MIT file), at your option.
 
 use accesskit::{ActionHandler, Live, NodeId, Role, TreeUpdate};
-use accesskit_consumer::{DetachedNode, FilterResult, Node, Tree, TreeChangeHandler, TreeState};
+use accesskit_consumer::{
+    DetachedNode, FilterResult, Node, NodeChanges, Tree, TreeChangeHandler, TreeState,
+};
 use std::{collections::HashSet, sync::Arc};
 use windows::Win32::{
     Foundation::*,
@@ -111,8 +113,13 @@ impl Adapter {
                     });
                 }
             }
-            fn node_updated(&mut self, old_node: &DetachedNode, new_node: &Node) {
-                if old_node.value() != new_node.value() {
+            fn node_updated(
+                &mut self,
+                old_node: &DetachedNode,
+                new_node: &Node,
+                changes: &NodeChanges,
+            ) {
+                if changes.text_content {
                     self.insert_text_change_if_needed(new_node);
                 }
                 if filter(new_node) != FilterResult::Include {
@@ -153,6 +160,9 @@ impl Adapter {
             fn node_removed(&mut self, node: &DetachedNode, current_state: &TreeState) {
                 self.insert_text_change_if_needed_for_removed_node(node, current_state);
             }
+            fn filter_detached(&self, node: &DetachedNode) -> FilterResult {
+                filter_detached(node)
+            }
             // TODO: handle other events (#20)
         }
         let mut handler = Handler {
@@ -149,6 +149,15 @@ pub struct Adapter {
 }
 
 impl Adapter {
+    /// Creates a new adapter for the given window.
+    ///
+    /// The platform adapter is initialized lazily, once an assistive
+    /// technology needs it, and `source` is only called then, possibly
+    /// on another thread and long after this function returns. It must
+    /// therefore return the complete tree as it is at that time, not as it
+    /// was when the adapter was created, because the updates provided
+    /// through [`Adapter::update`] and [`Adapter::update_if_active`] before
+    /// then are dropped.
     pub fn new<T: From<ActionRequestEvent> + Send + 'static>(
         window: &Window,
         source: impl 'static + FnOnce() -> TreeUpdate + Send,
@@ -162,6 +171,7 @@ impl Adapter {
     /// rather than dispatching action requests through the winit event loop.
     /// Remember that an AccessKit action handler can be called on any thread,
     /// depending on the underlying AccessKit platform adapter.
+    /// `source` must follow the same requirements as for [`Adapter::new`].
     pub fn with_action_handler(
         window: &Window,
         source: impl 'static + FnOnce() -> TreeUpdate + Send,
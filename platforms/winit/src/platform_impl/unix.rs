@@ -15,7 +15,7 @@ pub struct Adapter {
 impl Adapter {
     pub fn new(
         _: &Window,
-        source: impl 'static + FnOnce() -> TreeUpdate,
+        source: impl 'static + FnOnce() -> TreeUpdate + Send,
         action_handler: ActionHandlerBox,
     ) -> Self {
         let adapter = UnixAdapter::new(
@@ -42,7 +42,7 @@ impl Adapter {
 
     pub fn update_if_active(&self, updater: impl FnOnce() -> TreeUpdate) {
         if let Some(adapter) = &self.adapter {
-            adapter.update(updater());
+            adapter.update_if_active(updater);
         }
     }
 }